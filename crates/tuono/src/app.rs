@@ -13,7 +13,8 @@ use std::path::PathBuf;
 use std::process::Child;
 use std::process::Command;
 use std::process::Stdio;
-use tracing::error;
+use syn::{Item, ReturnType, Type, Visibility};
+use tracing::{error, trace};
 use tuono_internal::config::Config;
 
 const IGNORE_EXTENSIONS: [&str; 3] = ["css", "scss", "sass"];
@@ -39,26 +40,110 @@ const BUILD_TUONO_CONFIG: &str = "./node_modules/.bin/tuono-build-config";
 pub struct App {
     pub route_map: HashMap<String, Route>,
     pub base_path: PathBuf,
-    pub has_app_state: bool,
+    /// How `src/app.rs` creates the `ApplicationState`
+    pub app_state: Option<AppState>,
+    /// `src/app.rs` defines the `authenticate` hook
+    pub has_authenticator: bool,
+    /// `src/app.rs` defines the background `jobs`
+    pub has_jobs: bool,
+    /// `src/app.rs` defines the readiness `health_checks`
+    pub has_health_checks: bool,
     pub config: Option<Config>,
 }
 
-fn has_app_state(base_path: PathBuf) -> std::io::Result<bool> {
+/// The signature of the `main` function of `src/app.rs`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AppState {
+    /// `pub async fn main()`
+    pub is_async: bool,
+    /// `pub fn main() -> Result<ApplicationState, E>`
+    pub is_fallible: bool,
+}
+
+/// The items of `src/app.rs` used by the server entry point
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct AppFile {
+    app_state: Option<AppState>,
+    has_authenticator: bool,
+    has_jobs: bool,
+    has_health_checks: bool,
+}
+
+fn is_result_type(output: &ReturnType) -> bool {
+    let ReturnType::Type(_, ty) = output else {
+        return false;
+    };
+
+    matches!(&**ty, Type::Path(type_path) if type_path
+        .path
+        .segments
+        .last()
+        .is_some_and(|segment| segment.ident == "Result"))
+}
+
+impl From<&str> for AppFile {
+    fn from(contents: &str) -> Self {
+        let Ok(file) = syn::parse_file(contents) else {
+            // Keep the file in the build so that the compiler reports the error
+            trace!("Failed to parse src/app.rs");
+            return AppFile {
+                app_state: contents.contains("fn main").then(AppState::default),
+                has_authenticator: contents.contains("fn authenticate"),
+                has_jobs: contents.contains("fn jobs"),
+                has_health_checks: contents.contains("fn health_checks"),
+            };
+        };
+
+        let mut app_file = AppFile::default();
+
+        for item in file.items {
+            let Item::Fn(item_fn) = item else {
+                continue;
+            };
+            if !matches!(item_fn.vis, Visibility::Public(_)) {
+                continue;
+            }
+
+            if item_fn.sig.ident == "main" {
+                app_file.app_state = Some(AppState {
+                    is_async: item_fn.sig.asyncness.is_some(),
+                    is_fallible: is_result_type(&item_fn.sig.output),
+                });
+            } else if item_fn.sig.ident == "authenticate" {
+                app_file.has_authenticator = true;
+            } else if item_fn.sig.ident == "jobs" {
+                app_file.has_jobs = true;
+            } else if item_fn.sig.ident == "health_checks" {
+                app_file.has_health_checks = true;
+            }
+        }
+
+        app_file
+    }
+}
+
+fn read_app_file(base_path: &Path) -> std::io::Result<String> {
     let file = File::open(base_path.join("src/app.rs"))?;
     let mut buf_reader = BufReader::new(file);
     let mut contents = String::new();
     buf_reader.read_to_string(&mut contents)?;
-    Ok(contents.contains("pub fn main"))
+    Ok(contents)
 }
 
 impl App {
     pub fn new() -> Self {
         let base_path = std::env::current_dir().expect("Failed to read current_dir");
+        let app_file = read_app_file(&base_path)
+            .map(|contents| AppFile::from(contents.as_str()))
+            .unwrap_or_default();
 
         let mut app = App {
             route_map: HashMap::new(),
             base_path: base_path.clone(),
-            has_app_state: has_app_state(base_path).unwrap_or(false),
+            app_state: app_file.app_state,
+            has_authenticator: app_file.has_authenticator,
+            has_jobs: app_file.has_jobs,
+            has_health_checks: app_file.has_health_checks,
             config: None,
         };
 
@@ -142,12 +227,40 @@ impl App {
         self.route_map.iter().any(|(_, route)| route.is_dynamic)
     }
 
+    /// The routes starting with a locale segment (e.g. `/it/about`).
+    /// The locale prefix is removed before the route matching
+    /// so these routes are never reached.
+    pub fn routes_shadowed_by_locales(&self) -> Vec<&str> {
+        let Some(config) = &self.config else {
+            return Vec::new();
+        };
+
+        let mut routes = self
+            .route_map
+            .keys()
+            .filter(|path| {
+                path.split('/')
+                    .nth(1)
+                    .is_some_and(|segment| config.i18n.locales.iter().any(|l| l == segment))
+            })
+            .map(String::as_str)
+            .collect::<Vec<&str>>();
+
+        routes.sort();
+        routes
+    }
+
     pub fn check_server_availability(&self, mode: Mode) {
         // At this point the config should be available
         let config = self.config.as_ref().unwrap();
 
-        let rust_listener =
-            std::net::TcpListener::bind(format!("{}:{}", config.server.host, config.server.port));
+        // The Unix and the inherited sockets are checked by the server
+        let rust_listener = if config.server.binds_address() {
+            std::net::TcpListener::bind(format!("{}:{}", config.server.host, config.server.port))
+                .map(|_| ())
+        } else {
+            Ok(())
+        };
 
         if let Err(_e) = rust_listener {
             eprintln!("Error: Failed to bind to port {}", config.server.port);
@@ -201,7 +314,7 @@ impl App {
             .expect("Failed to run the rust server")
     }
 
-    pub fn build_tuono_config(&mut self) -> io::Result<std::process::Output> {
+    pub fn build_tuono_config(&mut self, mode: Mode) -> io::Result<std::process::Output> {
         if !Path::new(BUILD_TUONO_CONFIG).exists() {
             eprintln!("Failed to find the build script. Please run `npm install`");
             std::process::exit(1);
@@ -213,7 +326,8 @@ impl App {
             .stderr(Stdio::piped())
             .output();
 
-        match Config::get() {
+        // The CLI doesn't load the .env files: the server does
+        match Config::get_with_env_files(mode.env_name()) {
             Ok(config) => self.config = Some(config),
             Err(error) => {
                 match error.kind() {
@@ -254,6 +368,88 @@ impl App {
 mod tests {
     use super::*;
 
+    #[test]
+    fn should_parse_the_app_file() {
+        let cases = [
+            ("", AppFile::default()),
+            (
+                "pub fn main() -> ApplicationState { ApplicationState {} }",
+                AppFile {
+                    app_state: Some(AppState::default()),
+                    has_authenticator: false,
+                    has_jobs: false,
+                    has_health_checks: false,
+                },
+            ),
+            (
+                r#"
+                // pub fn main() is not defined yet
+                fn main() -> ApplicationState { ApplicationState {} }
+                "#,
+                AppFile::default(),
+            ),
+            (
+                r#"
+                pub async fn main() -> Result<ApplicationState, sqlx::Error> {
+                    Ok(ApplicationState { db: connect().await? })
+                }
+
+                pub async fn authenticate(req: Request) -> Option<User> { None }
+                "#,
+                AppFile {
+                    app_state: Some(AppState {
+                        is_async: true,
+                        is_fallible: true,
+                    }),
+                    has_authenticator: true,
+                    has_jobs: false,
+                    has_health_checks: false,
+                },
+            ),
+            (
+                "pub fn main() -> { broken",
+                AppFile {
+                    app_state: Some(AppState::default()),
+                    has_authenticator: false,
+                    has_jobs: false,
+                    has_health_checks: false,
+                },
+            ),
+        ];
+
+        for (contents, expected) in cases {
+            assert_eq!(AppFile::from(contents), expected, "{contents}");
+        }
+    }
+
+    #[test]
+    fn should_detect_the_jobs() {
+        let app_file = AppFile::from(
+            r#"
+            pub fn jobs(state: &ApplicationState) -> Jobs {
+                Jobs::new().every("refresh", Duration::from_secs(60), || refresh())
+            }
+            "#,
+        );
+
+        assert!(app_file.has_jobs);
+        assert!(app_file.app_state.is_none());
+    }
+
+    #[test]
+    fn should_detect_the_health_checks() {
+        let app_file = AppFile::from(
+            r#"
+            pub fn health_checks() -> HealthChecks {
+                HealthChecks::new().check("cache", || ping())
+            }
+            "#,
+        );
+
+        assert!(app_file.has_health_checks);
+        assert!(!app_file.has_jobs);
+    }
+
     #[test]
     fn should_collect_routes() {
         let mut app = App::new();
@@ -515,4 +711,30 @@ mod tests {
 
         assert!(!app3.has_dynamic_routes())
     }
+
+    #[test]
+    fn should_find_the_routes_shadowed_by_the_locales() {
+        let mut app = App::new();
+        app.base_path = "/home/user/Documents/tuono".into();
+        app.config = Some(Config {
+            i18n: tuono_internal::config::I18nConfig {
+                locales: vec!["en".to_string(), "it".to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+
+        let routes = [
+            "/home/user/Documents/tuono/src/routes/index.rs",
+            "/home/user/Documents/tuono/src/routes/italy.rs",
+            "/home/user/Documents/tuono/src/routes/it/about.rs",
+            "/home/user/Documents/tuono/src/routes/en.rs",
+        ];
+
+        routes
+            .into_iter()
+            .for_each(|route| app.collect_route(Ok(PathBuf::from(route))));
+
+        assert_eq!(app.routes_shadowed_by_locales(), vec!["/en", "/it/about"]);
+    }
 }
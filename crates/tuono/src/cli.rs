@@ -1,14 +1,25 @@
 use clap::{Parser, Subcommand};
 use tracing::{Level, span};
 
-use crate::commands::{build, dev, new};
+use std::path::PathBuf;
+
+use crate::commands::{build, dev, new, openapi};
 use crate::mode::Mode;
 use crate::source_builder::SourceBuilder;
 
 #[derive(Subcommand, Debug)]
 enum Actions {
     /// Start the development environment
-    Dev,
+    Dev {
+        /// Override the server host (same as `TUONO_SERVER__HOST`)
+        #[arg(long)]
+        host: Option<String>,
+
+        /// Override the server port (same as `TUONO_SERVER__PORT`).
+        /// The vite dev server listens on the next one.
+        #[arg(short, long)]
+        port: Option<u16>,
+    },
     /// Build the production assets
     Build {
         #[arg(short, long = "static")]
@@ -31,6 +42,12 @@ enum Actions {
         #[arg(long)]
         head: Option<bool>,
     },
+    /// Generate the OpenAPI document of the API routes
+    Openapi {
+        /// The file where the document is saved. Prints it to stdout by default
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
 }
 
 #[derive(Parser, Debug)]
@@ -44,11 +61,23 @@ pub fn app() -> std::io::Result<()> {
     let args = Args::parse();
 
     match args.action {
-        Actions::Dev => {
+        Actions::Dev { host, port } => {
             let span = span!(Level::TRACE, "DEV");
 
             let _guard = span.enter();
 
+            // The overrides are inherited by the rust server
+            // and the vite dev server processes.
+            // SAFETY: no other thread is running yet
+            unsafe {
+                if let Some(host) = host {
+                    std::env::set_var("TUONO_SERVER__HOST", host);
+                }
+                if let Some(port) = port {
+                    std::env::set_var("TUONO_SERVER__PORT", port.to_string());
+                }
+            }
+
             let mut source_builder = SourceBuilder::new(Mode::Dev)?;
 
             source_builder.base_build()?;
@@ -63,6 +92,9 @@ pub fn app() -> std::io::Result<()> {
             let _guard = span.enter();
 
             let mut source_builder = SourceBuilder::new(Mode::Prod)?;
+            if !no_js_emit {
+                source_builder.app.build_tuono_config(Mode::Prod)?;
+            }
             source_builder.base_build()?;
             build::build(source_builder.app, ssg, no_js_emit);
         }
@@ -77,6 +109,13 @@ pub fn app() -> std::io::Result<()> {
 
             new::create_new_project(folder_name, template, head);
         }
+        Actions::Openapi { output } => {
+            let span = span!(Level::TRACE, "OPENAPI");
+
+            let _guard = span.enter();
+
+            openapi::openapi(output)?;
+        }
     }
 
     Ok(())
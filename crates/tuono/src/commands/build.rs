@@ -5,15 +5,89 @@ use std::thread::sleep;
 use std::time::Duration;
 use tracing::{error, trace};
 
+use tuono_internal::config::Config;
+
 use crate::app::App;
 use crate::mode::Mode;
 
+/// Redirect map read by static hosting providers (e.g. Netlify, Cloudflare Pages)
+const STATIC_REDIRECTS_FILE_PATH: &str = "out/static/_redirects";
+
 fn exit_gracefully_with_error(msg: &str) -> ! {
     error!(msg);
     std::process::exit(1);
 }
 
-pub fn build(mut app: App, ssg: bool, no_js_emit: bool) {
+/// Convert a router path (e.g. `/docs/{*path}`) to the
+/// static hosts placeholders syntax (e.g. `/docs/*`).
+fn to_static_host_path(path: &str, catch_all_placeholder: &str) -> String {
+    path.split('/')
+        .map(|segment| {
+            match segment
+                .strip_prefix('{')
+                .and_then(|segment| segment.strip_suffix('}'))
+            {
+                Some(name) if name.starts_with('*') => catch_all_placeholder.to_string(),
+                Some(name) => format!(":{name}"),
+                None => segment.to_string(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("/")
+}
+
+/// Convert the destination placeholders. The catch all parameter
+/// is exposed by the static hosts as `:splat`.
+fn to_static_host_destination(source: &str, destination: &str) -> String {
+    let mut destination = destination.to_string();
+    for segment in source.split('/') {
+        if let Some(name) = segment
+            .strip_prefix("{*")
+            .and_then(|segment| segment.strip_suffix('}'))
+        {
+            destination = destination
+                .replace(&format!("{{*{name}}}"), ":splat")
+                .replace(&format!("{{{name}}}"), ":splat");
+        }
+    }
+    to_static_host_path(&destination, "*")
+}
+
+fn static_redirects(config: &Config) -> String {
+    let redirects = config.redirects.iter().map(|redirect| {
+        (
+            &redirect.source,
+            &redirect.destination,
+            if redirect.permanent { 308 } else { 307 },
+        )
+    });
+    // Rewrites are defined as redirects with a 200 status code
+    let rewrites = config
+        .rewrites
+        .iter()
+        .map(|rewrite| (&rewrite.source, &rewrite.destination, 200));
+
+    redirects
+        .chain(rewrites)
+        .map(|(source, destination, status)| {
+            // The rules are relative to the base path, external destinations excluded
+            let destination = if destination.starts_with('/') {
+                config.with_base_path(destination)
+            } else {
+                destination.clone()
+            };
+
+            format!(
+                "{} {} {}\n",
+                to_static_host_path(&config.with_base_path(source), "*"),
+                to_static_host_destination(source, &destination),
+                status
+            )
+        })
+        .collect()
+}
+
+pub fn build(app: App, ssg: bool, no_js_emit: bool) {
     if no_js_emit {
         println!("Rust build successfully finished");
         std::process::exit(0);
@@ -25,8 +99,16 @@ pub fn build(mut app: App, ssg: bool, no_js_emit: bool) {
         std::process::exit(1);
     }
 
-    app.build_tuono_config()
-        .unwrap_or_else(|_| exit_gracefully_with_error("Failed to build tuono.config.ts"));
+    // The pages are crawled over HTTP from the server listener
+    if ssg
+        && let Some(config) = &app.config
+        && !config.server.binds_address()
+    {
+        println!(
+            "Cannot statically build an app listening on a unix socket or an inherited socket (server.unixSocket, server.socketActivation)"
+        );
+        std::process::exit(1);
+    }
 
     let mut app_build_spinner = Spinner::new(Spinners::Dots, "Building app...".into());
 
@@ -75,21 +157,25 @@ pub fn build(mut app: App, ssg: bool, no_js_emit: bool) {
             exit_gracefully_with_error(msg)
         };
 
+        let config = app.config.as_ref().unwrap();
+
         let reqwest_client = reqwest::blocking::Client::builder()
             .user_agent("")
+            // The pages are crawled from the local server that might
+            // use a self-signed certificate
+            .danger_accept_invalid_certs(config.server.tls.is_some())
             .build()
             .unwrap_or_else(|_| exit_and_shut_server("Failed to build reqwest client"));
 
         // Wait for server
         let mut is_server_ready = false;
-        let config = app.config.as_ref().unwrap();
 
         while !is_server_ready {
             trace!("Checking server availability");
 
             let server_url = match &config.server.origin {
                 Some(origin) => origin.clone(),
-                None => format!("http://{}:{}", config.server.host, config.server.port),
+                None => config.server.base_url(),
             };
 
             if reqwest_client.get(&server_url).send().is_ok() {
@@ -103,16 +189,102 @@ pub fn build(mut app: App, ssg: bool, no_js_emit: bool) {
 
         trace!("Server is ready, starting static site generation");
 
-        for (_, route) in app.route_map {
-            if let Err(msg) = route.save_ssg_file(&reqwest_client) {
-                exit_and_shut_server(&msg);
+        let base_url = format!("{}{}", config.server.base_url(), config.base_path);
+
+        // Every locale gets its own copy of the pages
+        for locale_prefix in config.i18n.path_prefixes() {
+            for route in app.route_map.values() {
+                if let Err(msg) = route.save_ssg_file(&reqwest_client, &base_url, &locale_prefix) {
+                    exit_and_shut_server(&msg);
+                }
             }
         }
 
         // Close server
         let _ = rust_server.kill();
 
+        let redirects = static_redirects(config);
+        if !redirects.is_empty() {
+            trace!("Saving the static redirects file");
+            std::fs::write(STATIC_REDIRECTS_FILE_PATH, redirects).unwrap_or_else(|_| {
+                exit_gracefully_with_error("Failed to write the static redirects file")
+            });
+        }
+
         app_build_static_spinner
             .stop_with_message("\u{2705}Static site generation completed".into());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tuono_internal::config::{RedirectConfig, RewriteConfig};
+
+    #[test]
+    fn it_generates_the_static_redirects_file() {
+        let config = Config {
+            redirects: vec![
+                RedirectConfig {
+                    source: "/pokemons/GOAT".to_string(),
+                    destination: "/pokemons/mewtwo".to_string(),
+                    permanent: false,
+                },
+                RedirectConfig {
+                    source: "/blog/{slug}".to_string(),
+                    destination: "/posts/{slug}".to_string(),
+                    permanent: true,
+                },
+                RedirectConfig {
+                    source: "/docs/{*path}".to_string(),
+                    destination: "https://tuono.dev/documentation/{path}".to_string(),
+                    permanent: true,
+                },
+            ],
+            rewrites: vec![RewriteConfig {
+                source: "/about".to_string(),
+                destination: "/company/about".to_string(),
+            }],
+            ..Config::default()
+        };
+
+        assert_eq!(
+            static_redirects(&config),
+            "/pokemons/GOAT /pokemons/mewtwo 307\n\
+             /blog/:slug /posts/:slug 308\n\
+             /docs/* https://tuono.dev/documentation/:splat 308\n\
+             /about /company/about 200\n"
+        );
+    }
+
+    #[test]
+    fn it_prefixes_the_static_redirects_with_the_base_path() {
+        let config = Config {
+            base_path: "/shop".to_string(),
+            redirects: vec![
+                RedirectConfig {
+                    source: "/blog/{slug}".to_string(),
+                    destination: "/posts/{slug}".to_string(),
+                    permanent: true,
+                },
+                RedirectConfig {
+                    source: "/docs/{*path}".to_string(),
+                    destination: "https://tuono.dev/documentation/{path}".to_string(),
+                    permanent: false,
+                },
+            ],
+            rewrites: vec![RewriteConfig {
+                source: "/".to_string(),
+                destination: "/home".to_string(),
+            }],
+            ..Config::default()
+        };
+
+        assert_eq!(
+            static_redirects(&config),
+            "/shop/blog/:slug /shop/posts/:slug 308\n\
+             /shop/docs/* https://tuono.dev/documentation/:splat 307\n\
+             /shop /shop/home 200\n"
+        );
+    }
+}
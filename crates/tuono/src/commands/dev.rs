@@ -176,6 +176,11 @@ pub async fn watch(source_builder: SourceBuilder) -> Result<()> {
                     _ = builder.refresh_axum_source();
                 }
             }
+            if let Ok(builder) = source_builder.read()
+                && builder.generate_openapi_file().is_err()
+            {
+                error!("Failed to generate the OpenAPI document");
+            }
             if let Ok(mut pm) = process_manager.lock() {
                 pm.restart_process(ProcessId::RunRustDevServer);
             }
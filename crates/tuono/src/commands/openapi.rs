@@ -0,0 +1,28 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use tuono_internal::tuono_println;
+
+use crate::app::App;
+use crate::openapi::generate_openapi_document;
+use crate::typescript::TypesJar;
+
+/// Print the OpenAPI document of the project API routes or,
+/// when `output` is provided, save it in the defined file.
+pub fn openapi(output: Option<PathBuf>) -> io::Result<()> {
+    let app = App::new();
+    let types_jar = TypesJar::from(&app.base_path);
+
+    let document = generate_openapi_document(&app, &types_jar);
+    let document = serde_json::to_string_pretty(&document)?;
+
+    match output {
+        Some(path) => {
+            fs::write(&path, document)?;
+            tuono_println!("OpenAPI document saved at {}", path.display());
+        }
+        None => println!("{document}"),
+    }
+
+    Ok(())
+}
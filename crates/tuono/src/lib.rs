@@ -7,6 +7,7 @@ mod app;
 pub mod cli;
 mod commands;
 mod mode;
+mod openapi;
 mod process_manager;
 mod route;
 mod source_builder;
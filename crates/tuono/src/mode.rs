@@ -11,6 +11,14 @@ impl Mode {
         }
         "Mode::Prod"
     }
+
+    /// The name used by the mode specific `.env` files
+    pub fn env_name(&self) -> &'static str {
+        match self {
+            Mode::Dev => "development",
+            Mode::Prod => "production",
+        }
+    }
 }
 
 #[cfg(test)]
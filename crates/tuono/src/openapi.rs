@@ -0,0 +1,525 @@
+use crate::app::App;
+use crate::route::AxumInfo;
+use crate::typescript::TypesJar;
+use crate::typescript::parser::{remove_dangling_refs, rust_to_json_schema};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::{Map, Value, json};
+use std::collections::BTreeSet;
+use std::fs::read_to_string;
+use std::path::Path;
+use syn::visit::{self, Visit};
+use syn::{Expr, GenericArgument, ItemFn, Meta, PathArguments, ReturnType, Type};
+use tracing::{error, trace};
+
+const OPENAPI_VERSION: &str = "3.1.0";
+
+/// The content of a request or response body
+#[derive(Debug, Clone, PartialEq)]
+struct BodyContent {
+    content_type: &'static str,
+    schema: Value,
+}
+
+/// A single `#[tuono_lib::api(...)]` function found in a route file
+#[derive(Debug, Clone, PartialEq)]
+struct ApiOperation {
+    method: String,
+    fn_name: String,
+    docs: Vec<String>,
+    request_body: Option<BodyContent>,
+    response: Option<BodyContent>,
+}
+
+fn is_api_attribute(path: &syn::Path) -> bool {
+    let segments = path
+        .segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect::<Vec<String>>();
+
+    segments == ["tuono_lib", "api"] || segments == ["api"]
+}
+
+/// Read the HTTP method defined as first argument of the
+/// `#[tuono_lib::api(...)]` attribute.
+fn api_http_method(item: &ItemFn) -> Option<String> {
+    item.attrs.iter().find_map(|attr| {
+        if !is_api_attribute(attr.path()) {
+            return None;
+        }
+
+        let args = attr
+            .parse_args_with(syn::punctuated::Punctuated::<Meta, syn::Token![,]>::parse_terminated)
+            .ok()?;
+
+        match args.first()? {
+            Meta::Path(path) => path
+                .get_ident()
+                .map(|ident| ident.to_string().to_lowercase()),
+            _ => None,
+        }
+    })
+}
+
+fn doc_lines(item: &ItemFn) -> Vec<String> {
+    item.attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(name_value) => match &name_value.value {
+                Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(lit_str),
+                    ..
+                }) => Some(lit_str.value().trim().to_string()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+fn last_segment_generic(type_path: &syn::TypePath) -> Option<&Type> {
+    let last_segment = type_path.path.segments.last()?;
+    if let PathArguments::AngleBracketed(args) = &last_segment.arguments {
+        return args.args.iter().find_map(|arg| match arg {
+            GenericArgument::Type(ty) => Some(ty),
+            _ => None,
+        });
+    }
+    None
+}
+
+/// Infer the response body from the function return type
+fn response_content(ty: &Type) -> Option<BodyContent> {
+    match ty {
+        Type::Reference(reference) => response_content(&reference.elem),
+        Type::Path(type_path) => {
+            let outer_type = type_path.path.segments.last()?.ident.to_string();
+
+            match outer_type.as_str() {
+                "Json" => Some(BodyContent {
+                    content_type: "application/json",
+                    schema: rust_to_json_schema(last_segment_generic(type_path)?, &[]),
+                }),
+                "Result" => response_content(last_segment_generic(type_path)?),
+                "Html" => Some(BodyContent {
+                    content_type: "text/html",
+                    schema: json!({ "type": "string" }),
+                }),
+                "String" | "str" => Some(BodyContent {
+                    content_type: "text/plain",
+                    schema: json!({ "type": "string" }),
+                }),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Collects the types used to parse the request body
+/// with `req.body::<T>()` or `req.form_data::<T>()`
+#[derive(Default)]
+struct RequestBodyVisitor {
+    request_body: Option<BodyContent>,
+}
+
+impl RequestBodyVisitor {
+    fn body_content(method: &str, ty: &Type) -> Option<BodyContent> {
+        let content_type = match method {
+            "body" => "application/json",
+            "form_data" => "application/x-www-form-urlencoded",
+            _ => return None,
+        };
+
+        Some(BodyContent {
+            content_type,
+            schema: rust_to_json_schema(ty, &[]),
+        })
+    }
+
+    /// Unwrap `expr?`, `expr.unwrap()` and `expr.expect(..)` in order
+    /// to find the method call parsing the body.
+    fn find_body_method_call(expr: &Expr) -> Option<&syn::ExprMethodCall> {
+        match expr {
+            Expr::Try(expr_try) => Self::find_body_method_call(&expr_try.expr),
+            Expr::Await(expr_await) => Self::find_body_method_call(&expr_await.base),
+            Expr::MethodCall(method_call) => {
+                let method = method_call.method.to_string();
+                if method == "body" || method == "form_data" {
+                    return Some(method_call);
+                }
+                Self::find_body_method_call(&method_call.receiver)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for RequestBodyVisitor {
+    fn visit_expr_method_call(&mut self, method_call: &'ast syn::ExprMethodCall) {
+        if self.request_body.is_none()
+            && let Some(turbofish) = &method_call.turbofish
+            && let Some(GenericArgument::Type(ty)) = turbofish.args.first()
+        {
+            self.request_body = Self::body_content(&method_call.method.to_string(), ty);
+        }
+        visit::visit_expr_method_call(self, method_call);
+    }
+
+    fn visit_local(&mut self, local: &'ast syn::Local) {
+        if self.request_body.is_none()
+            && let (syn::Pat::Type(pat_type), Some(init)) = (&local.pat, &local.init)
+            && let Some(method_call) = Self::find_body_method_call(&init.expr)
+        {
+            self.request_body = Self::body_content(&method_call.method.to_string(), &pat_type.ty);
+        }
+        visit::visit_local(self, local);
+    }
+}
+
+fn parse_api_operations(file_str: &str) -> syn::Result<Vec<ApiOperation>> {
+    let file = syn::parse_file(file_str)?;
+
+    let operations = file
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            syn::Item::Fn(item_fn) => Some(item_fn),
+            _ => None,
+        })
+        .filter_map(|item_fn| {
+            let method = api_http_method(item_fn)?;
+
+            let mut visitor = RequestBodyVisitor::default();
+            visitor.visit_block(&item_fn.block);
+
+            let response = match &item_fn.sig.output {
+                ReturnType::Type(_, ty) => response_content(ty),
+                ReturnType::Default => None,
+            };
+
+            Some(ApiOperation {
+                method,
+                fn_name: item_fn.sig.ident.to_string(),
+                docs: doc_lines(item_fn),
+                request_body: visitor.request_body,
+                response,
+            })
+        })
+        .collect();
+
+    Ok(operations)
+}
+
+/// Convert the axum route into an OpenAPI path template
+/// and collect its path parameters.
+fn openapi_path(axum_route: &str) -> (String, Vec<String>) {
+    static RE: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"\{\*?(.*?)\}").expect("Failed to create path param regex"));
+
+    let parameters = RE
+        .captures_iter(axum_route)
+        .map(|caps| caps[1].to_string())
+        .collect::<Vec<String>>();
+
+    let path = RE
+        .replace_all(axum_route, |caps: &regex::Captures| {
+            format!("{{{}}}", &caps[1])
+        })
+        .to_string();
+
+    (path, parameters)
+}
+
+fn operation_object(operation: &ApiOperation, module_import: &str, parameters: &[String]) -> Value {
+    let mut operation_object = Map::new();
+
+    operation_object.insert(
+        "operationId".to_string(),
+        json!(format!("{}_{}", operation.method, module_import)),
+    );
+
+    if let Some((summary, description)) = operation.docs.split_first() {
+        operation_object.insert("summary".to_string(), json!(summary));
+        let description = description.join("\n").trim().to_string();
+        if !description.is_empty() {
+            operation_object.insert("description".to_string(), json!(description));
+        }
+    }
+
+    if !parameters.is_empty() {
+        let parameters = parameters
+            .iter()
+            .map(|name| {
+                json!({
+                    "name": name,
+                    "in": "path",
+                    "required": true,
+                    "schema": { "type": "string" },
+                })
+            })
+            .collect::<Vec<Value>>();
+        operation_object.insert("parameters".to_string(), Value::Array(parameters));
+    }
+
+    if let Some(BodyContent {
+        content_type,
+        schema,
+    }) = &operation.request_body
+    {
+        operation_object.insert(
+            "requestBody".to_string(),
+            json!({
+                "required": true,
+                "content": { *content_type: { "schema": schema } },
+            }),
+        );
+    }
+
+    let success_response = match &operation.response {
+        Some(BodyContent {
+            content_type,
+            schema,
+        }) => json!({
+            "description": "Successful response",
+            "content": { *content_type: { "schema": schema } },
+        }),
+        None => json!({ "description": "Successful response" }),
+    };
+
+    operation_object.insert("responses".to_string(), json!({ "200": success_response }));
+
+    Value::Object(operation_object)
+}
+
+/// Read the `name` and `version` fields of the project's `Cargo.toml`.
+/// They are used as the OpenAPI document `info`.
+fn package_info(base_path: &Path) -> (String, String) {
+    static NAME_RE: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r#"(?m)^name\s*=\s*"(.*?)""#).expect("Invalid name regex"));
+    static VERSION_RE: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r#"(?m)^version\s*=\s*"(.*?)""#).expect("Invalid version regex"));
+
+    let cargo_toml = read_to_string(base_path.join("Cargo.toml")).unwrap_or_default();
+
+    let name = NAME_RE
+        .captures(&cargo_toml)
+        .map(|caps| caps[1].to_string())
+        .unwrap_or_else(|| "tuono".to_string());
+    let version = VERSION_RE
+        .captures(&cargo_toml)
+        .map(|caps| caps[1].to_string())
+        .unwrap_or_else(|| "0.0.0".to_string());
+
+    (name, version)
+}
+
+/// Generate the OpenAPI 3.1 document of all the `#[tuono_lib::api]`
+/// routes collected by the [`App`].
+///
+/// The types deriving `Type` are exported as `components.schemas`:
+/// the references to any other type are left unconstrained.
+pub fn generate_openapi_document(app: &App, types_jar: &TypesJar) -> Value {
+    let (title, version) = package_info(&app.base_path);
+
+    let mut routes = app
+        .route_map
+        .iter()
+        .filter(|(_, route)| route.is_api() && !route.is_ws)
+        .collect::<Vec<_>>();
+    routes.sort_by_key(|(path, _)| *path);
+
+    let mut paths = Map::new();
+
+    for (path, route) in routes {
+        let Some(AxumInfo {
+            axum_route,
+            module_import,
+        }) = &route.axum_info
+        else {
+            continue;
+        };
+
+        let file_path = app.base_path.join(format!("src/routes{path}.rs"));
+
+        let operations = match read_to_string(&file_path)
+            .map_err(|err| err.to_string())
+            .and_then(|file_str| parse_api_operations(&file_str).map_err(|err| err.to_string()))
+        {
+            Ok(operations) => operations,
+            Err(err) => {
+                error!("Failed to parse API file {:?}: {}", file_path, err);
+                continue;
+            }
+        };
+
+        let (openapi_path, parameters) = openapi_path(axum_route);
+
+        let path_item = paths
+            .entry(openapi_path)
+            .or_insert_with(|| Value::Object(Map::new()));
+
+        for operation in operations {
+            trace!(
+                "Adding {} {} ({}) to the OpenAPI document",
+                operation.method, path, operation.fn_name
+            );
+            path_item[operation.method.as_str()] =
+                operation_object(&operation, module_import, &parameters);
+        }
+    }
+
+    let mut schemas = types_jar.json_schemas();
+    let known_types: BTreeSet<String> = schemas.keys().cloned().collect();
+
+    let mut paths = Value::Object(paths);
+    remove_dangling_refs(&mut paths, &known_types);
+    for schema in schemas.values_mut() {
+        remove_dangling_refs(schema, &known_types);
+    }
+
+    json!({
+        "openapi": OPENAPI_VERSION,
+        "info": { "title": title, "version": version },
+        "paths": paths,
+        "components": { "schemas": schemas },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_convert_axum_routes_to_openapi_paths() {
+        assert_eq!(
+            openapi_path("/api/users"),
+            ("/api/users".to_string(), Vec::new())
+        );
+        assert_eq!(
+            openapi_path("/api/users/{user}/posts/{post}"),
+            (
+                "/api/users/{user}/posts/{post}".to_string(),
+                vec!["user".to_string(), "post".to_string()]
+            )
+        );
+        assert_eq!(
+            openapi_path("/api/{*all_apis}"),
+            ("/api/{all_apis}".to_string(), vec!["all_apis".to_string()])
+        );
+    }
+
+    #[test]
+    fn should_parse_the_api_operations() {
+        let file_str = r#"
+            use tuono_lib::{Request, axum::Json};
+
+            /// Create a new user
+            ///
+            /// The user is stored in the database
+            #[tuono_lib::api(POST)]
+            async fn create_user(req: Request) -> Json<User> {
+                let payload = req.body::<NewUser>().unwrap();
+                Json(User::from(payload))
+            }
+
+            #[tuono_lib::api(put)]
+            async fn update_user(req: Request) -> StatusCode {
+                let payload: UserForm = req.form_data().expect("Invalid form");
+                StatusCode::OK
+            }
+
+            #[tuono_lib::api(GET)]
+            async fn health(_req: Request) -> String {
+                "ok".to_string()
+            }
+
+            async fn not_an_api() {}
+        "#;
+
+        let operations = parse_api_operations(file_str).expect("Failed to parse file");
+
+        assert_eq!(
+            operations,
+            vec![
+                ApiOperation {
+                    method: "post".to_string(),
+                    fn_name: "create_user".to_string(),
+                    docs: vec![
+                        "Create a new user".to_string(),
+                        "".to_string(),
+                        "The user is stored in the database".to_string()
+                    ],
+                    request_body: Some(BodyContent {
+                        content_type: "application/json",
+                        schema: json!({ "$ref": "#/components/schemas/NewUser" }),
+                    }),
+                    response: Some(BodyContent {
+                        content_type: "application/json",
+                        schema: json!({ "$ref": "#/components/schemas/User" }),
+                    }),
+                },
+                ApiOperation {
+                    method: "put".to_string(),
+                    fn_name: "update_user".to_string(),
+                    docs: Vec::new(),
+                    request_body: Some(BodyContent {
+                        content_type: "application/x-www-form-urlencoded",
+                        schema: json!({ "$ref": "#/components/schemas/UserForm" }),
+                    }),
+                    response: None,
+                },
+                ApiOperation {
+                    method: "get".to_string(),
+                    fn_name: "health".to_string(),
+                    docs: Vec::new(),
+                    request_body: None,
+                    response: Some(BodyContent {
+                        content_type: "text/plain",
+                        schema: json!({ "type": "string" }),
+                    }),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn should_create_the_operation_object() {
+        let operation = ApiOperation {
+            method: "get".to_string(),
+            fn_name: "read_post".to_string(),
+            docs: vec!["Read a post".to_string()],
+            request_body: None,
+            response: Some(BodyContent {
+                content_type: "application/json",
+                schema: json!({ "$ref": "#/components/schemas/Post" }),
+            }),
+        };
+
+        assert_eq!(
+            operation_object(&operation, "api_posts_dyn_post", &["post".to_string()]),
+            json!({
+                "operationId": "get_api_posts_dyn_post",
+                "summary": "Read a post",
+                "parameters": [{
+                    "name": "post",
+                    "in": "path",
+                    "required": true,
+                    "schema": { "type": "string" },
+                }],
+                "responses": {
+                    "200": {
+                        "description": "Successful response",
+                        "content": {
+                            "application/json": {
+                                "schema": { "$ref": "#/components/schemas/Post" }
+                            }
+                        }
+                    }
+                }
+            })
+        );
+    }
+}
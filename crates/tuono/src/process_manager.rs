@@ -91,10 +91,12 @@ impl ProcessManager {
     }
 
     pub fn log_server_address(&self, config: Config) {
-        let server_address = format!("{}:{}", config.server.host, config.server.port);
         // Format the server address as a valid URL so that it becomes clickable in the CLI
         // @see https://github.com/tuono-labs/tuono/issues/460
-        let server_base_url = format!("http://{server_address}");
+        let server_base_url = match &config.server.unix_socket {
+            Some(path) => format!("unix:{}", path.display()),
+            None => config.server.base_url(),
+        };
 
         println!();
         tuono_println!("⚡ Tuono v{}", crate_version!());
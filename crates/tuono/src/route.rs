@@ -1,5 +1,6 @@
 use fs_extra::dir::create_all;
 use http::Method;
+use once_cell::sync::Lazy;
 use regex::Regex;
 use reqwest::Url;
 use reqwest::blocking::Client;
@@ -102,11 +103,24 @@ fn read_http_methods_from_file(path: &String) -> Vec<Method> {
                 // Extract just the element surrounded by the phrantesist.
                 .replace("tuono_lib::api(", "")
                 .replace(")]", "");
+            // The method is followed by the optional traffic control arguments
+            let http_method = http_method.split(',').next().unwrap_or_default().trim();
             Method::from_str(http_method.to_uppercase().as_str()).unwrap_or(Method::GET)
         })
         .collect::<Vec<Method>>()
 }
 
+/// Whether the route file defines a `#[tuono_lib::ws]` handler
+fn has_ws_handler(path: &str) -> bool {
+    static RE: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"#\[tuono_lib::ws[\](]").expect("Failed to create WS regex"));
+
+    let base_path = std::env::current_dir().expect("Failed to get the base_path");
+
+    fs_extra::file::read_to_string(base_path.join(format!("src/routes{path}.rs")))
+        .is_ok_and(|file| RE.is_match(&file))
+}
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct ApiData {
     pub methods: Vec<Method>,
@@ -137,6 +151,8 @@ pub struct Route {
     pub is_dynamic: bool,
     pub axum_info: Option<AxumInfo>,
     pub api_data: Option<ApiData>,
+    /// The route only handles the WebSocket upgrade
+    pub is_ws: bool,
 }
 
 impl Route {
@@ -146,6 +162,7 @@ impl Route {
             axum_info: None,
             is_dynamic: has_dynamic_path(&cleaned_path),
             api_data: ApiData::new(&cleaned_path),
+            is_ws: has_ws_handler(&cleaned_path),
         }
     }
 
@@ -157,22 +174,31 @@ impl Route {
         self.axum_info = Some(AxumInfo::new(self))
     }
 
-    pub fn save_ssg_file(&self, reqwest: &Client) -> Result<(), String> {
-        if self.is_api() {
+    /// Save the HTML page and the data of the route requested from
+    /// the server at `base_url` (including the application base path)
+    /// for the locale with the `locale_prefix` (e.g. `/it`).
+    /// The files are saved relative to the base path.
+    pub fn save_ssg_file(
+        &self,
+        reqwest: &Client,
+        base_url: &str,
+        locale_prefix: &str,
+    ) -> Result<(), String> {
+        if self.is_api() || self.is_ws {
             return Ok(());
         }
 
         let path = &self.path.replace("index", "");
 
-        let url = format!("http://localhost:3000{path}");
+        let url = format!("{base_url}{locale_prefix}{path}");
 
         trace!("Requesting the page: {}", url);
-        let mut response = match reqwest.get(format!("http://localhost:3000{path}")).send() {
+        let mut response = match reqwest.get(&url).send() {
             Ok(response) => response,
             Err(_) => return Err(format!("Failed to get the response: {url}")),
         };
 
-        let file_path = self.output_file_path();
+        let file_path = self.output_file_path(locale_prefix);
 
         let parent_dir = match file_path.parent() {
             Some(parent_dir) => parent_dir,
@@ -204,7 +230,9 @@ impl Route {
         if self.axum_info.is_some() {
             trace!("The route is an axum route, saving the JSON file");
 
-            let data_file_path = PathBuf::from(&format!("out/static/__tuono/data{path}.json"));
+            let data_file_path = PathBuf::from(&format!(
+                "out/static{locale_prefix}/__tuono/data{path}.json"
+            ));
 
             let data_parent_dir = match data_file_path.parent() {
                 Some(parent_dir) => parent_dir,
@@ -223,15 +251,10 @@ impl Route {
                 }
             }
 
-            let base = Url::parse("http://localhost:3000/__tuono/data").unwrap();
-
             let path = if path == "/" { "" } else { path };
 
-            let pathname = &format!("/__tuono/data{path}");
-
-            let url = base
-                .join(pathname)
-                .expect("Failed to build the reqwest URL");
+            let url = Url::parse(&format!("{base_url}{locale_prefix}/__tuono/data{path}"))
+                .map_err(|_| format!("Failed to build the data URL for {path}"))?;
 
             trace!("Requesting the JSON file: {}", url);
 
@@ -260,8 +283,8 @@ impl Route {
         Ok(())
     }
 
-    fn output_file_path(&self) -> PathBuf {
-        let cleaned_path = self.path.replace("index", "");
+    fn output_file_path(&self, locale_prefix: &str) -> PathBuf {
+        let cleaned_path = format!("{locale_prefix}{}", self.path.replace("index", ""));
 
         if NO_HTML_EXTENSIONS
             .iter()
@@ -339,7 +362,22 @@ mod tests {
         for (path, html) in routes {
             let route = Route::new(path.to_string());
 
-            assert_eq!(route.output_file_path(), PathBuf::from(html))
+            assert_eq!(route.output_file_path(""), PathBuf::from(html))
+        }
+    }
+
+    #[test]
+    fn should_prefix_the_html_build_path_with_the_locale() {
+        let routes = [
+            ("/index", "out/static/it/index.html"),
+            ("/documentation", "out/static/it/documentation/index.html"),
+            ("/sitemap.xml", "out/static/it/sitemap.xml"),
+        ];
+
+        for (path, html) in routes {
+            let route = Route::new(path.to_string());
+
+            assert_eq!(route.output_file_path("/it"), PathBuf::from(html))
         }
     }
 }
@@ -5,13 +5,15 @@ use std::path::Path;
 use std::path::PathBuf;
 
 use clap::crate_version;
-use tracing::error;
+use tracing::{error, warn};
 
-use crate::app::App;
+use crate::app::{App, AppState};
 use crate::mode::Mode;
+use crate::openapi::generate_openapi_document;
 use crate::route::AxumInfo;
 use crate::route::Route;
 use crate::typescript::TypesJar;
+use tuono_internal::config::Config;
 
 #[cfg(not(target_os = "windows"))]
 const FALLBACK_HTML: &str = include_str!("../templates/fallback.html");
@@ -28,6 +30,9 @@ const MAIN_FILE_PATH: &str = "./.tuono/main.rs";
 #[cfg(not(target_os = "windows"))]
 const FALLBACK_HTML_PATH: &str = "./.tuono/index.html";
 
+#[cfg(not(target_os = "windows"))]
+const OPENAPI_FILE_PATH: &str = "./.tuono/openapi.json";
+
 const ROUTE_FOLDER: &str = "src/routes";
 const DEV_FOLDER: &str = ".tuono";
 
@@ -46,6 +51,9 @@ const MAIN_FILE_PATH: &str = ".\\.tuono\\main.rs";
 #[cfg(target_os = "windows")]
 const FALLBACK_HTML_PATH: &str = ".\\.tuono\\index.html";
 
+#[cfg(target_os = "windows")]
+const OPENAPI_FILE_PATH: &str = ".\\.tuono\\openapi.json";
+
 // Use this function to instruct the users on how to
 // fix their setup to make tuono work
 fn recoverable_error(message: &str) -> ! {
@@ -53,6 +61,45 @@ fn recoverable_error(message: &str) -> ! {
     std::process::exit(1);
 }
 
+/// Create the `ApplicationState` with the `main` function of `src/app.rs`.
+/// The server doesn't start when the state can't be created.
+fn app_state_definition(app_state: Option<AppState>) -> String {
+    let Some(AppState {
+        is_async,
+        is_fallible,
+    }) = app_state
+    else {
+        return String::new();
+    };
+
+    let call = if is_async {
+        "tuono_main_state::main().await"
+    } else {
+        "tuono_main_state::main()"
+    };
+
+    if is_fallible {
+        format!(
+            "let user_custom_state = match {call} {{
+        Ok(state) => state,
+        Err(err) => tuono_lib::tuono_internal_app_state_failed(err),
+    }};"
+        )
+    } else {
+        format!("let user_custom_state = {call};")
+    }
+}
+
+/// Call the `hook` function of `src/app.rs` before the `ApplicationState`
+/// is moved into the router. The hook receives the state when defined.
+fn state_hook_definition(hook: &str, is_defined: bool, app: &App) -> String {
+    match (is_defined, app.app_state.is_some()) {
+        (false, _) => String::new(),
+        (true, true) => format!("let {hook} = tuono_main_state::{hook}(&user_custom_state);"),
+        (true, false) => format!("let {hook} = tuono_main_state::{hook}();"),
+    }
+}
+
 // Struct to build the source code
 // on both "dev" and "build" commands
 #[derive(Clone, Debug)]
@@ -90,6 +137,22 @@ impl SourceBuilder {
     pub fn base_build(&mut self) -> io::Result<()> {
         let mode = self.mode.clone();
 
+        // The generated routes depend on the config (i.e. `basePath`)
+        if mode == Mode::Dev {
+            self.app.build_tuono_config(Mode::Dev)?;
+        } else if self.app.config.is_none() {
+            // `--no-js-emit` skips the config generation: it might be missing
+            self.app.config = match Config::get_with_env_files(mode.env_name()) {
+                Ok(config) => Some(config),
+                Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+                Err(err) => return Err(err),
+            };
+        }
+
+        for route in self.app.routes_shadowed_by_locales() {
+            warn!("The route {route} is unreachable: its first segment is a configured locale");
+        }
+
         self.refresh_axum_source()?;
         let dev_folder = Path::new(DEV_FOLDER);
         self.create_file(dev_folder.join("server-main.tsx"), SERVER_ENTRY_DATA)?;
@@ -98,9 +161,9 @@ impl SourceBuilder {
         self.types_jar.generate_typescript_file(&self.base_path)?;
 
         if mode == Mode::Dev {
-            self.app.build_tuono_config()?;
             let fallback_html = self.build_html_fallback();
             self.create_file(PathBuf::from(FALLBACK_HTML_PATH), &fallback_html)?;
+            self.generate_openapi_file()?;
         }
 
         Ok(())
@@ -117,7 +180,11 @@ impl SourceBuilder {
             .replace("/*MODE*/", mode.as_str())
             .replace(
                 "//MAIN_FILE_IMPORT//",
-                if app.has_app_state {
+                if app.app_state.is_some()
+                    || app.has_authenticator
+                    || app.has_jobs
+                    || app.has_health_checks
+                {
                     r#"#[path="../src/app.rs"]
                     mod tuono_main_state;
                     "#
@@ -127,16 +194,41 @@ impl SourceBuilder {
             )
             .replace(
                 "//MAIN_FILE_DEFINITION//",
-                if app.has_app_state {
-                    "let user_custom_state = tuono_main_state::main();"
+                &app_state_definition(app.app_state),
+            )
+            .replace(
+                "//MAIN_FILE_USAGE//",
+                if app.app_state.is_some() {
+                    ".layer(tuono_lib::axum::Extension(user_custom_state.clone()))
+        .with_state(user_custom_state)"
                 } else {
                     ""
                 },
             )
             .replace(
-                "//MAIN_FILE_USAGE//",
-                if app.has_app_state {
-                    ".with_state(user_custom_state)"
+                "//AUTHENTICATOR_USAGE//",
+                if app.has_authenticator {
+                    ".with_authenticator(tuono_main_state::authenticate)"
+                } else {
+                    ""
+                },
+            )
+            .replace(
+                "//JOBS_DEFINITION//",
+                &state_hook_definition("jobs", app.has_jobs, app),
+            )
+            .replace(
+                "//JOBS_USAGE//",
+                if app.has_jobs { ".with_jobs(jobs)" } else { "" },
+            )
+            .replace(
+                "//HEALTH_CHECKS_DEFINITION//",
+                &state_hook_definition("health_checks", app.has_health_checks, app),
+            )
+            .replace(
+                "//HEALTH_CHECKS_USAGE//",
+                if app.has_health_checks {
+                    ".with_health_checks(health_checks)"
                 } else {
                     ""
                 },
@@ -182,8 +274,19 @@ impl SourceBuilder {
         self.types_jar.generate_typescript_file(&self.base_path)
     }
 
+    /// Save the OpenAPI document served in development
+    /// at `/__tuono/openapi.json`
+    pub fn generate_openapi_file(&self) -> io::Result<()> {
+        let document = generate_openapi_document(&self.app, &self.types_jar);
+        self.create_file(
+            PathBuf::from(OPENAPI_FILE_PATH),
+            &serde_json::to_string_pretty(&document)?,
+        )
+    }
+
     fn create_routes_declaration(&self) -> String {
         let routes = &self.app.route_map;
+        let config = self.app.config.clone().unwrap_or_default();
         let mut route_declarations = String::from("// ROUTE_BUILDER\n");
 
         for (_, route) in routes.iter() {
@@ -195,19 +298,26 @@ impl SourceBuilder {
                     module_import,
                 } = axum_info.as_ref().unwrap();
 
-                if !route.is_api() {
+                let data_route = config.with_base_path(&format!("/__tuono/data{axum_route}"));
+                let axum_route = config.with_base_path(axum_route);
+
+                if route.is_ws {
+                    route_declarations.push_str(&format!(
+                        r#".route("{axum_route}", get({module_import}::tuono_internal_ws).layer({module_import}::tuono_internal_limits()))"#
+                    ));
+                } else if !route.is_api() {
                     route_declarations.push_str(&format!(
-                        r#".route("{axum_route}", get({module_import}::tuono_internal_route))"#
+                        r#".route("{axum_route}", get({module_import}::tuono_internal_route).layer({module_import}::tuono_internal_limits()))"#
                     ));
 
                     route_declarations.push_str(&format!(
-                            r#".route("/__tuono/data{axum_route}", get({module_import}::tuono_internal_api))"#
+                            r#".route("{data_route}", get({module_import}::tuono_internal_api).layer({module_import}::tuono_internal_limits()))"#
                     ));
                 } else {
                     for method in route.api_data.as_ref().unwrap().methods.clone() {
                         let method = method.to_string().to_lowercase();
                         route_declarations.push_str(&format!(
-                                r#".route("{axum_route}", {method}({module_import}::{method}_tuono_internal_api))"#
+                                r#".route("{axum_route}", {method}({module_import}::{method}_tuono_internal_api).layer({module_import}::{method}_tuono_internal_limits()))"#
                         ));
                     }
                 }
@@ -241,8 +351,7 @@ impl SourceBuilder {
             if let Some(origin) = &config.server.origin {
                 FALLBACK_HTML.replace("[BASE_URL]", origin)
             } else {
-                let url = format!("http://{}:{}", config.server.host, config.server.port);
-                FALLBACK_HTML.replace("[BASE_URL]", url.as_str())
+                FALLBACK_HTML.replace("[BASE_URL]", config.server.base_url().as_str())
             }
         } else {
             "".to_string()
@@ -312,6 +421,157 @@ mod tests {
         assert!(dev_bundle.contains("use tuono_lib::axum::routing::get;"));
     }
 
+    #[test]
+    fn should_prefix_the_routes_with_the_base_path() {
+        let mut source_builder = SourceBuilder {
+            app: App::new(),
+            mode: Mode::Prod,
+            base_path: PathBuf::new(),
+            types_jar: TypesJar::default(),
+        };
+
+        source_builder.app.config = Some(Config {
+            base_path: String::from("/shop"),
+            ..Default::default()
+        });
+
+        for path in ["index", "about"] {
+            let mut route = Route::new(format!("/{path}"));
+            route.update_axum_info();
+            source_builder
+                .app
+                .route_map
+                .insert(format!("/{path}"), route);
+        }
+
+        let prod_bundle = source_builder.generate_axum_source();
+
+        assert!(prod_bundle.contains(r#".route("/shop", get(index::tuono_internal_route)"#));
+        assert!(
+            prod_bundle.contains(r#".route("/shop/__tuono/data/", get(index::tuono_internal_api)"#)
+        );
+        assert!(prod_bundle.contains(r#".route("/shop/about", get(about::tuono_internal_route)"#));
+        assert!(
+            prod_bundle
+                .contains(r#".route("/shop/__tuono/data/about", get(about::tuono_internal_api)"#)
+        );
+    }
+
+    #[test]
+    fn should_create_the_app_state() {
+        let cases = [
+            (None, ""),
+            (
+                Some(AppState::default()),
+                "let user_custom_state = tuono_main_state::main();",
+            ),
+            (
+                Some(AppState {
+                    is_async: true,
+                    is_fallible: false,
+                }),
+                "let user_custom_state = tuono_main_state::main().await;",
+            ),
+            (
+                Some(AppState {
+                    is_async: true,
+                    is_fallible: true,
+                }),
+                "let user_custom_state = match tuono_main_state::main().await {",
+            ),
+        ];
+
+        for (app_state, expected) in cases {
+            let definition = app_state_definition(app_state);
+            assert!(definition.starts_with(expected), "{definition}");
+        }
+
+        assert!(
+            app_state_definition(Some(AppState {
+                is_async: false,
+                is_fallible: true,
+            }))
+            .contains("Err(err) => tuono_lib::tuono_internal_app_state_failed(err),")
+        );
+    }
+
+    #[test]
+    fn should_register_the_authenticate_hook() {
+        let mut source_builder = SourceBuilder {
+            app: App::new(),
+            mode: Mode::Dev,
+            base_path: PathBuf::new(),
+            types_jar: TypesJar::default(),
+        };
+
+        source_builder.app.app_state = None;
+        source_builder.app.has_authenticator = false;
+
+        let bundle = source_builder.generate_axum_source();
+        assert!(!bundle.contains("with_authenticator"));
+        assert!(!bundle.contains("mod tuono_main_state;"));
+
+        source_builder.app.has_authenticator = true;
+
+        let bundle = source_builder.generate_axum_source();
+        assert!(bundle.contains("mod tuono_main_state;"));
+        assert!(
+            bundle.contains(
+                "Server::init(router, MODE).await.with_authenticator(tuono_main_state::authenticate).start().await"
+            )
+        );
+    }
+
+    #[test]
+    fn should_start_the_jobs() {
+        let mut source_builder = SourceBuilder {
+            app: App::new(),
+            mode: Mode::Dev,
+            base_path: PathBuf::new(),
+            types_jar: TypesJar::default(),
+        };
+
+        source_builder.app.app_state = Some(AppState::default());
+        source_builder.app.has_authenticator = false;
+        source_builder.app.has_jobs = true;
+        source_builder.app.has_health_checks = false;
+
+        let bundle = source_builder.generate_axum_source();
+        assert!(bundle.contains("let jobs = tuono_main_state::jobs(&user_custom_state);"));
+        assert!(bundle.contains("Server::init(router, MODE).await.with_jobs(jobs).start().await"));
+
+        source_builder.app.app_state = None;
+
+        let bundle = source_builder.generate_axum_source();
+        assert!(bundle.contains("mod tuono_main_state;"));
+        assert!(bundle.contains("let jobs = tuono_main_state::jobs();"));
+    }
+
+    #[test]
+    fn should_register_the_health_checks() {
+        let mut source_builder = SourceBuilder {
+            app: App::new(),
+            mode: Mode::Dev,
+            base_path: PathBuf::new(),
+            types_jar: TypesJar::default(),
+        };
+
+        source_builder.app.app_state = Some(AppState::default());
+        source_builder.app.has_authenticator = false;
+        source_builder.app.has_jobs = false;
+        source_builder.app.has_health_checks = true;
+
+        let bundle = source_builder.generate_axum_source();
+        assert!(
+            bundle.contains(
+                "let health_checks = tuono_main_state::health_checks(&user_custom_state);"
+            )
+        );
+        assert!(bundle.contains(
+            "Server::init(router, MODE).await.with_health_checks(health_checks).start().await"
+        ));
+    }
+
     #[test]
     fn should_create_fallback_html_with_default_config() {
         let mut app = App::new();
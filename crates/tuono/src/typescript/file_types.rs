@@ -1,5 +1,7 @@
 use super::utils::has_derive_type;
-use crate::typescript::parser::{parse_enum, parse_struct};
+use crate::typescript::parser::{parse_enum, parse_enum_schema, parse_struct, parse_struct_schema};
+use serde_json::Value;
+use std::collections::BTreeMap;
 use std::error::Error;
 use std::path::PathBuf;
 use tracing::trace;
@@ -15,6 +17,9 @@ pub struct FileTypes {
     /// The types found in the file.
     /// Used to check that the types are not duplicated across files.
     pub types: Vec<String>,
+    /// The JSON schema of each type found in the file.
+    /// Used to generate the OpenAPI components.
+    pub schemas: BTreeMap<String, Value>,
 }
 
 impl TryFrom<(PathBuf, String)> for FileTypes {
@@ -26,6 +31,7 @@ impl TryFrom<(PathBuf, String)> for FileTypes {
 
         let mut types_as_string = String::new();
         let mut types = Vec::new();
+        let mut schemas = BTreeMap::new();
 
         for item in file.items {
             match item {
@@ -36,6 +42,8 @@ impl TryFrom<(PathBuf, String)> for FileTypes {
                     trace!("Found struct in file: {:?}", &file_path);
                     let (struct_name, typescript_definition) = parse_struct(&element);
                     types_as_string.push_str(&typescript_definition);
+                    let (_, schema) = parse_struct_schema(&element);
+                    schemas.insert(struct_name.clone(), schema);
                     types.push(struct_name);
                 }
                 syn::Item::Enum(element) => {
@@ -46,6 +54,8 @@ impl TryFrom<(PathBuf, String)> for FileTypes {
 
                     let (enum_name, typescript_definition) = parse_enum(&element);
                     types_as_string.push_str(&typescript_definition);
+                    let (_, schema) = parse_enum_schema(&element);
+                    schemas.insert(enum_name.clone(), schema);
                     types.push(enum_name);
                 }
                 _ => {}
@@ -60,6 +70,7 @@ impl TryFrom<(PathBuf, String)> for FileTypes {
             file_path,
             types_as_string,
             types,
+            schemas,
         })
     }
 }
@@ -95,5 +106,9 @@ mod tests {
             "export interface MyStruct {\n  field1: string;\n  field2: number;\n}\nexport type MyEnum = \"Variant1\" | \"Variant2\";\n"
         );
         assert_eq!(ttype.types, vec!["MyStruct", "MyEnum"]);
+        assert_eq!(
+            ttype.schemas.keys().collect::<Vec<&String>>(),
+            vec!["MyEnum", "MyStruct"]
+        );
     }
 }
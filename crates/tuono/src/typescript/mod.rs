@@ -1,8 +1,12 @@
 mod file_types;
 pub mod parser;
+mod route_events;
+mod route_props;
 
 mod types_jar;
 pub mod utils;
 
 pub use file_types::*;
+pub use route_events::*;
+pub use route_props::*;
 pub use types_jar::*;
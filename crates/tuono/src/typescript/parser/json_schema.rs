@@ -0,0 +1,479 @@
+use crate::typescript::parser::utils::{
+    RenameSerdeOptions, get_field_name, parse_serde_attribute, should_skip_element,
+};
+use serde_json::{Map, Value, json};
+use std::collections::BTreeSet;
+use syn::{GenericArgument, PathArguments};
+use tracing::trace;
+
+/// The prefix used to reference a schema defined in the
+/// OpenAPI `components.schemas` object.
+pub const COMPONENTS_REF_PREFIX: &str = "#/components/schemas/";
+
+fn primitive_to_json_schema(type_name: &str) -> Option<Value> {
+    match type_name {
+        "i8" | "i16" | "i32" | "u8" | "u16" | "u32" => {
+            Some(json!({ "type": "integer", "format": "int32" }))
+        }
+        "i64" | "u64" | "isize" | "usize" => Some(json!({ "type": "integer", "format": "int64" })),
+        // Serialized as JSON numbers exceeding the 64 bits
+        "i128" | "u128" => Some(json!({ "type": "integer" })),
+        "f32" => Some(json!({ "type": "number", "format": "float" })),
+        "f64" => Some(json!({ "type": "number", "format": "double" })),
+        "str" | "String" | "char" => Some(json!({ "type": "string" })),
+        "bool" => Some(json!({ "type": "boolean" })),
+        _ => None,
+    }
+}
+
+/// Convert an identifier into a JSON schema.
+/// Generic parameters are left unconstrained while any other
+/// non primitive type is referenced as an OpenAPI component.
+fn ident_to_json_schema(type_name: &str, generics: &[String]) -> Value {
+    if let Some(schema) = primitive_to_json_schema(type_name) {
+        return schema;
+    }
+
+    if generics.iter().any(|generic| generic == type_name) {
+        return json!({});
+    }
+
+    json!({ "$ref": format!("{COMPONENTS_REF_PREFIX}{type_name}") })
+}
+
+/// Returns true when the type is an `Option<T>`.
+/// Used to define which fields are required.
+fn is_option(ty: &syn::Type) -> bool {
+    if let syn::Type::Path(type_path) = ty {
+        return type_path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Option");
+    }
+    false
+}
+
+pub fn rust_to_json_schema(ty: &syn::Type, generics: &[String]) -> Value {
+    match ty {
+        syn::Type::Tuple(tuple) => {
+            if tuple.elems.is_empty() {
+                return json!({ "type": "null" });
+            }
+            let items: Vec<Value> = tuple
+                .elems
+                .iter()
+                .map(|elem| rust_to_json_schema(elem, generics))
+                .collect();
+
+            json!({
+                "type": "array",
+                "prefixItems": items,
+                "minItems": tuple.elems.len(),
+                "maxItems": tuple.elems.len(),
+            })
+        }
+        syn::Type::Reference(reference) => rust_to_json_schema(&reference.elem, generics),
+        syn::Type::Slice(slice) => {
+            json!({ "type": "array", "items": rust_to_json_schema(&slice.elem, generics) })
+        }
+        syn::Type::Array(array) => {
+            json!({ "type": "array", "items": rust_to_json_schema(&array.elem, generics) })
+        }
+        syn::Type::Path(type_path) => {
+            let Some(last_segment) = type_path.path.segments.last() else {
+                return json!({});
+            };
+
+            let outer_type = last_segment.ident.to_string();
+
+            let PathArguments::AngleBracketed(args) = &last_segment.arguments else {
+                return ident_to_json_schema(&outer_type, generics);
+            };
+
+            let inner_types: Vec<Value> = args
+                .args
+                .iter()
+                .filter_map(|arg| match arg {
+                    GenericArgument::Type(inner_type) => {
+                        Some(rust_to_json_schema(inner_type, generics))
+                    }
+                    _ => None,
+                })
+                .collect();
+
+            match (outer_type.as_str(), inner_types.as_slice()) {
+                ("Option", [inner]) => json!({ "anyOf": [inner, { "type": "null" }] }),
+                ("Vec" | "VecDeque" | "HashSet" | "BTreeSet", [inner]) => {
+                    json!({ "type": "array", "items": inner })
+                }
+                ("HashMap" | "BTreeMap", [_, value]) => {
+                    json!({ "type": "object", "additionalProperties": value })
+                }
+                ("Box" | "Rc" | "Arc" | "Cow", [.., inner]) => inner.clone(),
+                _ => json!({}),
+            }
+        }
+        _ => json!({}),
+    }
+}
+
+fn generic_names(generics: &syn::Generics) -> Vec<String> {
+    generics
+        .type_params()
+        .map(|param| param.ident.to_string())
+        .collect()
+}
+
+/// Parse the named fields of a struct or an enum variant
+/// into a JSON schema object.
+fn parse_named_fields(
+    fields: &syn::FieldsNamed,
+    rename_option: &RenameSerdeOptions,
+    generics: &[String],
+) -> Value {
+    let mut properties = Map::new();
+    let mut required: Vec<Value> = Vec::new();
+
+    for field in &fields.named {
+        if should_skip_element(&field.attrs) {
+            continue;
+        }
+
+        let field_name = rename_option.transform(get_field_name(field));
+
+        if !is_option(&field.ty) {
+            required.push(Value::String(field_name.clone()));
+        }
+
+        properties.insert(field_name, rust_to_json_schema(&field.ty, generics));
+    }
+
+    let mut schema = Map::new();
+    schema.insert("type".to_string(), json!("object"));
+    schema.insert("properties".to_string(), Value::Object(properties));
+    if !required.is_empty() {
+        schema.insert("required".to_string(), Value::Array(required));
+    }
+
+    Value::Object(schema)
+}
+
+fn parse_unnamed_fields(fields: &syn::FieldsUnnamed, generics: &[String]) -> Value {
+    let items: Vec<Value> = fields
+        .unnamed
+        .iter()
+        .map(|field| rust_to_json_schema(&field.ty, generics))
+        .collect();
+
+    // Newtype structs and variants are serialized by serde as the inner value
+    if let [item] = items.as_slice() {
+        return item.clone();
+    }
+
+    json!({
+        "type": "array",
+        "prefixItems": items,
+        "minItems": fields.unnamed.len(),
+        "maxItems": fields.unnamed.len(),
+    })
+}
+
+/// Parse a rust struct and returns a tuple of the struct name and the
+/// struct compiled to a JSON schema
+pub fn parse_struct_schema(element: &syn::ItemStruct) -> (String, Value) {
+    let struct_name = element.ident.to_string();
+    let generics = generic_names(&element.generics);
+    let rename_option: RenameSerdeOptions = parse_serde_attribute(&element.attrs, "rename_all");
+
+    let schema = match &element.fields {
+        syn::Fields::Named(fields) => parse_named_fields(fields, &rename_option, &generics),
+        syn::Fields::Unnamed(fields) => parse_unnamed_fields(fields, &generics),
+        syn::Fields::Unit => json!({ "type": "null" }),
+    };
+
+    (struct_name, schema)
+}
+
+/// Parse a rust enum and returns a tuple of the enum name and the
+/// enum compiled to a JSON schema.
+///
+/// The output follows the serde default (externally tagged) representation.
+/// The enum `rename_all` applies to the variant names, while the fields of the
+/// struct variants are renamed by the `rename_all` of the variant.
+pub fn parse_enum_schema(element: &syn::ItemEnum) -> (String, Value) {
+    let enum_name = element.ident.to_string();
+    let generics = generic_names(&element.generics);
+    let rename_option: RenameSerdeOptions = parse_serde_attribute(&element.attrs, "rename_all");
+
+    let mut unit_variants: Vec<Value> = Vec::new();
+    let mut complex_variants: Vec<Value> = Vec::new();
+
+    for variant in &element.variants {
+        if should_skip_element(&variant.attrs) {
+            continue;
+        }
+
+        let variant_name = rename_option.transform(variant.ident.to_string());
+
+        let variant_schema = match &variant.fields {
+            syn::Fields::Unit => {
+                unit_variants.push(Value::String(variant_name));
+                continue;
+            }
+            syn::Fields::Named(fields) => {
+                let fields_rename_option: RenameSerdeOptions =
+                    parse_serde_attribute(&variant.attrs, "rename_all");
+                parse_named_fields(fields, &fields_rename_option, &generics)
+            }
+            syn::Fields::Unnamed(fields) => parse_unnamed_fields(fields, &generics),
+        };
+
+        complex_variants.push(json!({
+            "type": "object",
+            "properties": { variant_name.clone(): variant_schema },
+            "required": [variant_name],
+            "additionalProperties": false,
+        }));
+    }
+
+    if complex_variants.is_empty() {
+        return (
+            enum_name,
+            json!({ "type": "string", "enum": unit_variants }),
+        );
+    }
+
+    let mut one_of = Vec::new();
+    if !unit_variants.is_empty() {
+        one_of.push(json!({ "type": "string", "enum": unit_variants }));
+    }
+    one_of.extend(complex_variants);
+
+    (enum_name, json!({ "oneOf": one_of }))
+}
+
+/// Replace the references to the types missing from `known_types`
+/// (i.e. without the `Type` derived trait) with an unconstrained schema.
+pub fn remove_dangling_refs(schema: &mut Value, known_types: &BTreeSet<String>) {
+    match schema {
+        Value::Object(object) => {
+            if let Some(Value::String(reference)) = object.get("$ref")
+                && let Some(type_name) = reference.strip_prefix(COMPONENTS_REF_PREFIX)
+                && !known_types.contains(type_name)
+            {
+                trace!("Type {type_name} is not exported. Falling back to an unconstrained schema");
+                object.clear();
+                return;
+            }
+
+            for value in object.values_mut() {
+                remove_dangling_refs(value, known_types);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                remove_dangling_refs(item, known_types);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_correctly_parses_struct_schema() {
+        let struct_str = r#"
+            #[derive(Type)]
+            #[serde(rename_all = "camelCase")]
+            struct MyStruct<T> {
+                field_one: &str,
+                field_two: i32,
+                field_three: Option<f64>,
+                list: Vec<User>,
+                record: HashMap<String, bool>,
+                generic: T,
+                #[serde(skip)]
+                skipped: String,
+            }
+        "#;
+
+        let parsed_struct = syn::parse_str::<syn::ItemStruct>(struct_str).unwrap();
+        let (struct_name, schema) = parse_struct_schema(&parsed_struct);
+
+        assert_eq!(struct_name, "MyStruct");
+        assert_eq!(
+            schema,
+            json!({
+                "type": "object",
+                "properties": {
+                    "fieldOne": { "type": "string" },
+                    "fieldTwo": { "type": "integer", "format": "int32" },
+                    "fieldThree": { "anyOf": [{ "type": "number", "format": "double" }, { "type": "null" }] },
+                    "list": { "type": "array", "items": { "$ref": "#/components/schemas/User" } },
+                    "record": { "type": "object", "additionalProperties": { "type": "boolean" } },
+                    "generic": {},
+                },
+                "required": ["fieldOne", "fieldTwo", "list", "record", "generic"],
+            })
+        );
+    }
+
+    #[test]
+    fn it_correctly_parses_tuple_fields_schema() {
+        let struct_str = r#"
+            #[derive(Type)]
+            struct MyStruct {
+                tuple: (i32, String),
+            }
+        "#;
+
+        let parsed_struct = syn::parse_str::<syn::ItemStruct>(struct_str).unwrap();
+        let (_, schema) = parse_struct_schema(&parsed_struct);
+
+        assert_eq!(
+            schema["properties"]["tuple"],
+            json!({
+                "type": "array",
+                "prefixItems": [{ "type": "integer", "format": "int32" }, { "type": "string" }],
+                "minItems": 2,
+                "maxItems": 2,
+            })
+        );
+    }
+
+    #[test]
+    fn it_correctly_parses_unit_enum_schema() {
+        let enum_str = r#"
+            #[derive(Type)]
+            #[serde(rename_all = "lowercase")]
+            enum MyEnum {
+                Variant1,
+                Variant2,
+                #[serde(skip)]
+                Skipped,
+            }
+        "#;
+
+        let parsed_enum = syn::parse_str::<syn::ItemEnum>(enum_str).unwrap();
+        let (enum_name, schema) = parse_enum_schema(&parsed_enum);
+
+        assert_eq!(enum_name, "MyEnum");
+        assert_eq!(
+            schema,
+            json!({ "type": "string", "enum": ["variant1", "variant2"] })
+        );
+    }
+
+    #[test]
+    fn it_correctly_parses_complex_enum_schema() {
+        let enum_str = r#"
+            #[derive(Type)]
+            enum MyEnum {
+                Id,
+                User { name: String },
+                Pair(u8, u8),
+            }
+        "#;
+
+        let parsed_enum = syn::parse_str::<syn::ItemEnum>(enum_str).unwrap();
+        let (_, schema) = parse_enum_schema(&parsed_enum);
+
+        assert_eq!(
+            schema,
+            json!({
+                "oneOf": [
+                    { "type": "string", "enum": ["Id"] },
+                    {
+                        "type": "object",
+                        "properties": {
+                            "User": {
+                                "type": "object",
+                                "properties": { "name": { "type": "string" } },
+                                "required": ["name"],
+                            }
+                        },
+                        "required": ["User"],
+                        "additionalProperties": false,
+                    },
+                    {
+                        "type": "object",
+                        "properties": {
+                            "Pair": {
+                                "type": "array",
+                                "prefixItems": [
+                                    { "type": "integer", "format": "int32" },
+                                    { "type": "integer", "format": "int32" }
+                                ],
+                                "minItems": 2,
+                                "maxItems": 2,
+                            }
+                        },
+                        "required": ["Pair"],
+                        "additionalProperties": false,
+                    },
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn it_renames_the_variants_and_their_fields_separately() {
+        let enum_str = r#"
+            #[derive(Type)]
+            #[serde(rename_all = "camelCase")]
+            enum Event {
+                #[serde(rename_all = "UPPERCASE")]
+                UserCreated { user_name: String, created_at: u128 },
+            }
+        "#;
+
+        let parsed_enum = syn::parse_str::<syn::ItemEnum>(enum_str).unwrap();
+        let (_, schema) = parse_enum_schema(&parsed_enum);
+
+        assert_eq!(
+            schema,
+            json!({
+                "oneOf": [{
+                    "type": "object",
+                    "properties": {
+                        "userCreated": {
+                            "type": "object",
+                            "properties": {
+                                "USER_NAME": { "type": "string" },
+                                "CREATED_AT": { "type": "integer" },
+                            },
+                            "required": ["USER_NAME", "CREATED_AT"],
+                        }
+                    },
+                    "required": ["userCreated"],
+                    "additionalProperties": false,
+                }]
+            })
+        );
+    }
+
+    #[test]
+    fn it_removes_the_dangling_refs() {
+        let mut schema = json!({
+            "type": "array",
+            "prefixItems": [
+                { "$ref": "#/components/schemas/User" },
+                { "$ref": "#/components/schemas/Uuid" },
+            ],
+        });
+
+        remove_dangling_refs(&mut schema, &BTreeSet::from(["User".to_string()]));
+
+        assert_eq!(
+            schema,
+            json!({
+                "type": "array",
+                "prefixItems": [{ "$ref": "#/components/schemas/User" }, {}],
+            })
+        );
+    }
+}
@@ -1,6 +1,8 @@
+mod json_schema;
 mod parse_enum;
 mod parse_struct;
 pub mod utils;
 
+pub use json_schema::*;
 pub use parse_enum::*;
 pub use parse_struct::*;
@@ -0,0 +1,164 @@
+use crate::typescript::parser::utils::rust_to_typescript_type;
+use crate::typescript::route_props::{collect_referenced_types, file_route};
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use syn::{GenericArgument, ItemFn, PathArguments, ReturnType, Type};
+use tracing::trace;
+
+/// The type of the events sent by an api route returning `SseResponse<T>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteEvents {
+    /// Rust file where the handler was found
+    pub file_path: PathBuf,
+    /// The api route (e.g. `/api/rooms/[room]`)
+    pub route: String,
+    /// The event type ready to be printed in the typescript file
+    pub type_as_string: String,
+    /// The types referenced by the event type.
+    /// Used to check that all of them are exported in the typescript file.
+    pub referenced_types: Vec<String>,
+}
+
+/// Convert the route file path to the api route.
+/// Returns `None` for files that are not api routes.
+pub fn api_route_from_file_path(file_path: &Path) -> Option<String> {
+    file_route(file_path)
+        .filter(|(_, is_api)| *is_api)
+        .map(|(route, _)| route)
+}
+
+fn is_api_attribute(attr: &syn::Attribute) -> bool {
+    attr.path()
+        .segments
+        .last()
+        .is_some_and(|segment| segment.ident == "api")
+}
+
+/// Read `T` from the `SseResponse<T>` return type
+fn event_type(handler: &ItemFn) -> Option<Type> {
+    let ReturnType::Type(_, return_type) = &handler.sig.output else {
+        return None;
+    };
+    let Type::Path(type_path) = &**return_type else {
+        return None;
+    };
+
+    let segment = type_path.path.segments.last()?;
+    if segment.ident != "SseResponse" {
+        return None;
+    }
+
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+
+    match args.args.first()? {
+        GenericArgument::Type(ty) => Some(ty.clone()),
+        _ => None,
+    }
+}
+
+impl TryFrom<(PathBuf, String)> for RouteEvents {
+    type Error = Box<dyn Error>;
+
+    fn try_from((file_path, file_str): (PathBuf, String)) -> Result<Self, Self::Error> {
+        let route = api_route_from_file_path(&file_path).ok_or("The file is not an api route")?;
+
+        trace!("Looking for the events type in route: {}", route);
+        let file = syn::parse_file(&file_str)?;
+
+        let event_type = file
+            .items
+            .iter()
+            .find_map(|item| match item {
+                syn::Item::Fn(item_fn) if item_fn.attrs.iter().any(is_api_attribute) => {
+                    event_type(item_fn)
+                }
+                _ => None,
+            })
+            .ok_or("No SseResponse handler found in the file")?;
+
+        let mut referenced_types = Vec::new();
+        collect_referenced_types(&event_type, &mut referenced_types);
+
+        Ok(Self {
+            file_path,
+            route,
+            type_as_string: rust_to_typescript_type(&event_type),
+            referenced_types,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn route_events(file_str: &str) -> Option<RouteEvents> {
+        RouteEvents::try_from((
+            PathBuf::from("/app/src/routes/api/rooms/[room].rs"),
+            file_str.to_string(),
+        ))
+        .ok()
+    }
+
+    #[test]
+    fn it_correctly_converts_the_file_path_to_api_route() {
+        let cases = [
+            ("/app/src/routes/api/events.rs", Some("/api/events")),
+            ("/app/src/routes/api/rooms/index.rs", Some("/api/rooms")),
+            (
+                "C:\\app\\src\\routes\\api\\rooms\\[room].rs",
+                Some("/api/rooms/[room]"),
+            ),
+            ("/app/src/routes/api/index.rs", Some("/api")),
+            ("/app/src/routes/api.rs", None),
+            ("/app/src/routes/posts.rs", None),
+        ];
+
+        for (file_path, expected) in cases {
+            assert_eq!(
+                api_route_from_file_path(Path::new(file_path)).as_deref(),
+                expected,
+                "{file_path}"
+            );
+        }
+    }
+
+    #[test]
+    fn it_reads_the_event_type_from_the_sse_response() {
+        let events = route_events(
+            r#"
+            #[tuono_lib::api(POST)]
+            async fn send(req: Request) -> StatusCode {
+                StatusCode::OK
+            }
+
+            #[tuono_lib::api(GET)]
+            async fn events(req: Request) -> tuono_lib::SseResponse<Option<Message>> {
+                SseResponse::new(messages(req.last_event_id()))
+            }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(events.route, "/api/rooms/[room]");
+        assert_eq!(events.type_as_string, "Message | null");
+        assert_eq!(events.referenced_types, vec!["Message"]);
+    }
+
+    #[test]
+    fn it_skips_the_routes_without_sse_response() {
+        assert!(
+            route_events(
+                r#"
+                #[tuono_lib::api(GET)]
+                async fn health_check(req: Request) -> StatusCode {
+                    StatusCode::OK
+                }
+                "#
+            )
+            .is_none()
+        );
+    }
+}
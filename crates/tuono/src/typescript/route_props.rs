@@ -0,0 +1,420 @@
+use crate::typescript::parser::utils::rust_to_typescript_type;
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use syn::visit::{self, Visit};
+use syn::{Expr, GenericArgument, Ident, ItemFn, Local, Pat, PathArguments, Token, Type};
+use tracing::trace;
+
+/// Types that are converted to their typescript counterpart
+/// without requiring a `Type` derived definition.
+const BUILTIN_TYPES: [&str; 22] = [
+    "i8", "i16", "i32", "i64", "i128", "u8", "u16", "u32", "u64", "u128", "f32", "f64", "isize",
+    "usize", "str", "String", "char", "bool", "Option", "Vec", "HashMap", "BTreeMap",
+];
+
+/// Methods that don't change the type carried by the receiver
+/// (e.g. `res.json::<T>().await.unwrap()`).
+const UNWRAP_METHODS: [&str; 5] = ["unwrap", "expect", "unwrap_or_default", "into", "clone"];
+
+/// The props type returned by a page route handler.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteProps {
+    /// Rust file where the handler was found
+    pub file_path: PathBuf,
+    /// The route as defined by the client router (e.g. `/posts/[post]`)
+    pub route: String,
+    /// The props type ready to be printed in the typescript file
+    pub type_as_string: String,
+    /// The types referenced by the props type.
+    /// Used to check that all of them are exported in the typescript file.
+    pub referenced_types: Vec<String>,
+}
+
+/// Convert a file of the `src/routes` folder to its route
+/// (e.g. `/posts/[post]`) and tell whether it is an api route.
+pub(crate) fn file_route(file_path: &Path) -> Option<(String, bool)> {
+    let file_path = file_path.to_string_lossy().replace('\\', "/");
+    let (_, route) = file_path.rsplit_once("src/routes/")?;
+    let route = route.strip_suffix(".rs")?;
+
+    // `src/routes/api.rs` is neither a page nor an api route
+    if route == "api" {
+        return None;
+    }
+
+    if route == "index" {
+        return Some(("/".to_string(), false));
+    }
+
+    Some((
+        format!("/{}", route.trim_end_matches("/index")),
+        route.starts_with("api/"),
+    ))
+}
+
+/// Convert the route file path to the route used by the client router.
+/// Returns `None` for files that are not page routes.
+pub fn route_from_file_path(file_path: &Path) -> Option<String> {
+    file_route(file_path)
+        .filter(|(_, is_api)| !is_api)
+        .map(|(route, _)| route)
+}
+
+fn is_handler_attribute(attr: &syn::Attribute) -> bool {
+    attr.path()
+        .segments
+        .last()
+        .is_some_and(|segment| segment.ident == "handler")
+}
+
+/// Read the `props = Type` argument of the `#[tuono_lib::handler]` attribute
+fn props_from_attribute(attr: &syn::Attribute) -> Option<Type> {
+    let mut props = None;
+
+    attr.parse_args_with(|input: syn::parse::ParseStream| {
+        while !input.is_empty() {
+            let name: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+
+            // Skip the other handler arguments (e.g. `rate_limit = "10/1m"`)
+            if name == "props" {
+                props = Some(input.parse::<Type>()?);
+            } else {
+                input.parse::<Expr>()?;
+            }
+
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        Ok(())
+    })
+    .ok()?;
+
+    props
+}
+
+/// Collects the values passed to `Props::new` and `Props::new_with_status`
+/// and the local bindings of the handler.
+#[derive(Default)]
+struct PropsVisitor<'a> {
+    props_values: Vec<&'a Expr>,
+    typed_bindings: HashMap<String, &'a Type>,
+    bindings: HashMap<String, &'a Expr>,
+}
+
+impl<'a> Visit<'a> for PropsVisitor<'a> {
+    fn visit_expr_call(&mut self, call: &'a syn::ExprCall) {
+        if let Expr::Path(func) = &*call.func {
+            let segments: Vec<String> = func
+                .path
+                .segments
+                .iter()
+                .map(|segment| segment.ident.to_string())
+                .collect();
+
+            if let [.., props, constructor] = segments.as_slice()
+                && props == "Props"
+                && (constructor == "new" || constructor == "new_with_status")
+                && let Some(value) = call.args.first()
+            {
+                self.props_values.push(value);
+            }
+        }
+        visit::visit_expr_call(self, call);
+    }
+
+    fn visit_local(&mut self, local: &'a Local) {
+        match &local.pat {
+            Pat::Type(pat_type) => {
+                if let Pat::Ident(pat_ident) = &*pat_type.pat {
+                    self.typed_bindings
+                        .insert(pat_ident.ident.to_string(), &pat_type.ty);
+                }
+            }
+            Pat::Ident(pat_ident) => {
+                if let Some(init) = &local.init {
+                    self.bindings
+                        .insert(pat_ident.ident.to_string(), &init.expr);
+                }
+            }
+            _ => {}
+        }
+        visit::visit_local(self, local);
+    }
+}
+
+impl PropsVisitor<'_> {
+    /// Infer the type of the expression passed to `Props::new`
+    fn resolve(&self, expr: &Expr, depth: usize) -> Option<Type> {
+        // Avoid looping on shadowed bindings (e.g. `let data = data.unwrap()`)
+        if depth > 8 {
+            return None;
+        }
+
+        match expr {
+            Expr::Struct(expr_struct) => Some(Type::Path(syn::TypePath {
+                qself: None,
+                path: expr_struct.path.clone(),
+            })),
+            Expr::Path(expr_path) => {
+                let name = expr_path.path.get_ident()?.to_string();
+                if let Some(ty) = self.typed_bindings.get(&name) {
+                    return Some((*ty).clone());
+                }
+                self.resolve(self.bindings.get(&name)?, depth + 1)
+            }
+            Expr::Reference(reference) => self.resolve(&reference.expr, depth + 1),
+            Expr::Paren(paren) => self.resolve(&paren.expr, depth + 1),
+            Expr::Await(expr_await) => self.resolve(&expr_await.base, depth + 1),
+            Expr::Try(expr_try) => self.resolve(&expr_try.expr, depth + 1),
+            Expr::MethodCall(method_call) => {
+                if let Some(turbofish) = &method_call.turbofish
+                    && let [GenericArgument::Type(ty)] =
+                        turbofish.args.iter().collect::<Vec<_>>().as_slice()
+                {
+                    return Some((*ty).clone());
+                }
+
+                let method = method_call.method.to_string();
+                if UNWRAP_METHODS.contains(&method.as_str()) {
+                    return self.resolve(&method_call.receiver, depth + 1);
+                }
+                None
+            }
+            // Constructors like `Post::new(...)` or `Post::from(...)`
+            Expr::Call(call) => {
+                let Expr::Path(func) = &*call.func else {
+                    return None;
+                };
+                let mut path = func.path.clone();
+                path.segments.pop()?;
+                path.segments.pop_punct();
+
+                let type_segment = path.segments.last()?;
+                if type_segment.ident == "Self"
+                    || !type_segment
+                        .ident
+                        .to_string()
+                        .starts_with(|c: char| c.is_ascii_uppercase())
+                {
+                    return None;
+                }
+
+                Some(Type::Path(syn::TypePath { qself: None, path }))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Collect the named types referenced by `ty` that need
+/// a `Type` derived definition to exist in the typescript file.
+pub(super) fn collect_referenced_types(ty: &Type, referenced_types: &mut Vec<String>) {
+    match ty {
+        Type::Reference(reference) => collect_referenced_types(&reference.elem, referenced_types),
+        Type::Tuple(tuple) => tuple
+            .elems
+            .iter()
+            .for_each(|elem| collect_referenced_types(elem, referenced_types)),
+        Type::Path(type_path) => {
+            let Some(segment) = type_path.path.segments.last() else {
+                return;
+            };
+            let name = segment.ident.to_string();
+            if !BUILTIN_TYPES.contains(&name.as_str()) {
+                referenced_types.push(name);
+            }
+            if let PathArguments::AngleBracketed(args) = &segment.arguments {
+                for arg in &args.args {
+                    if let GenericArgument::Type(inner_type) = arg {
+                        collect_referenced_types(inner_type, referenced_types);
+                    }
+                }
+            }
+        }
+        // Any other type can't be converted to typescript
+        _ => referenced_types.push(String::from("unknown")),
+    }
+}
+
+/// Detect the props type of the route handler.
+/// The `props = Type` handler argument takes precedence over the
+/// type inferred from the `Props::new` calls.
+fn find_props_type(handler: &ItemFn) -> Option<Type> {
+    if let Some(ty) = handler
+        .attrs
+        .iter()
+        .filter(|attr| is_handler_attribute(attr))
+        .find_map(props_from_attribute)
+    {
+        return Some(ty);
+    }
+
+    let mut visitor = PropsVisitor::default();
+    visitor.visit_block(&handler.block);
+
+    visitor
+        .props_values
+        .iter()
+        .find_map(|value| visitor.resolve(value, 0))
+}
+
+impl TryFrom<(PathBuf, String)> for RouteProps {
+    type Error = Box<dyn Error>;
+
+    fn try_from((file_path, file_str): (PathBuf, String)) -> Result<Self, Self::Error> {
+        let route = route_from_file_path(&file_path).ok_or("The file is not a page route")?;
+
+        trace!("Looking for the props type in route: {}", route);
+        let file = syn::parse_file(&file_str)?;
+
+        let handler = file
+            .items
+            .iter()
+            .find_map(|item| match item {
+                syn::Item::Fn(item_fn) if item_fn.attrs.iter().any(is_handler_attribute) => {
+                    Some(item_fn)
+                }
+                _ => None,
+            })
+            .ok_or("No handler found in the file")?;
+
+        let props_type = find_props_type(handler).ok_or("No props type found in the handler")?;
+
+        let mut referenced_types = Vec::new();
+        collect_referenced_types(&props_type, &mut referenced_types);
+
+        Ok(Self {
+            file_path,
+            route,
+            type_as_string: rust_to_typescript_type(&props_type),
+            referenced_types,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn route_props(file_str: &str) -> Option<RouteProps> {
+        RouteProps::try_from((
+            PathBuf::from("/app/src/routes/posts/[post].rs"),
+            file_str.to_string(),
+        ))
+        .ok()
+    }
+
+    #[test]
+    fn it_correctly_converts_the_file_path_to_route() {
+        let cases = [
+            ("/app/src/routes/index.rs", Some("/")),
+            ("/app/src/routes/about.rs", Some("/about")),
+            ("/app/src/routes/posts/index.rs", Some("/posts")),
+            ("/app/src/routes/posts/[post].rs", Some("/posts/[post]")),
+            (
+                "C:\\app\\src\\routes\\posts\\[post].rs",
+                Some("/posts/[post]"),
+            ),
+            ("/app/src/routes/api/health_check.rs", None),
+            ("/app/src/routes/api.rs", None),
+            ("/app/src/app.rs", None),
+        ];
+
+        for (file_path, expected) in cases {
+            assert_eq!(
+                route_from_file_path(Path::new(file_path)).as_deref(),
+                expected,
+                "{file_path}"
+            );
+        }
+    }
+
+    #[test]
+    fn it_reads_the_props_type_from_the_handler_attribute() {
+        let props = route_props(
+            r#"
+            #[tuono_lib::handler(props = Vec<Post>)]
+            async fn get_posts(req: Request) -> Response {
+                Response::Props(Props::new(load_posts()))
+            }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(props.route, "/posts/[post]");
+        assert_eq!(props.type_as_string, "Post[]");
+        assert_eq!(props.referenced_types, vec!["Post"]);
+    }
+
+    #[test]
+    fn it_skips_the_other_handler_attribute_arguments() {
+        let props = route_props(
+            r#"
+            #[tuono_lib::handler(timeout = "10s", props = Vec<Post>, concurrency = 8)]
+            async fn get_posts(req: Request) -> Response {
+                Response::Props(Props::new(load_posts()))
+            }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(props.type_as_string, "Post[]");
+    }
+
+    #[test]
+    fn it_infers_the_props_type_from_the_props_constructor() {
+        let cases = [
+            (
+                r#"let data = res.json::<Post>().await.unwrap();
+                Response::Props(Props::new(data))"#,
+                "Post",
+            ),
+            (
+                r#"let data: Option<Post> = load();
+                Response::Props(Props::new(data))"#,
+                "Post | null",
+            ),
+            (r#"Response::Props(Props::new(Post { title }))"#, "Post"),
+            (
+                r#"let post = Post::new(title);
+                Response::Props(Props::new(&post))"#,
+                "Post",
+            ),
+            (
+                r#"if failed {
+                    return Response::Props(Props::new_with_status("{}", StatusCode::NOT_FOUND));
+                }
+                Response::Props(Props::new_with_status(Post { title }, StatusCode::OK))"#,
+                "Post",
+            ),
+        ];
+
+        for (body, expected) in cases {
+            let file_str = format!(
+                "#[tuono_lib::handler]\nasync fn get_post(req: Request) -> Response {{ {body} }}"
+            );
+            let props = route_props(&file_str).unwrap();
+            assert_eq!(props.type_as_string, expected, "{body}");
+            assert_eq!(props.referenced_types, vec!["Post"], "{body}");
+        }
+    }
+
+    #[test]
+    fn it_skips_files_without_a_detectable_props_type() {
+        assert!(route_props("fn main() {}").is_none());
+        assert!(
+            route_props(
+                r#"
+                #[tuono_lib::handler]
+                async fn get_post(req: Request) -> Response {
+                    Response::Props(Props::new(fetch_post().await))
+                }
+                "#
+            )
+            .is_none()
+        );
+    }
+}
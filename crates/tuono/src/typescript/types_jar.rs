@@ -1,7 +1,10 @@
 use crate::symbols::TYPE_TRAIT;
-use crate::typescript::FileTypes;
+use crate::typescript::{
+    FileTypes, RouteEvents, RouteProps, api_route_from_file_path, route_from_file_path,
+};
 use glob::glob;
-use std::collections::HashMap;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::env;
 use std::fs::read_to_string;
 use std::path::{Path, PathBuf};
@@ -11,6 +14,8 @@ use tuono_internal::tuono_println;
 #[derive(Debug, Clone, Default)]
 pub struct TypesJar {
     types: Vec<FileTypes>,
+    routes_props: Vec<RouteProps>,
+    routes_events: Vec<RouteEvents>,
     should_generate_typescript_file: bool,
 }
 
@@ -18,6 +23,8 @@ impl TypesJar {
     pub fn new() -> Self {
         Self {
             types: Vec::new(),
+            routes_props: Vec::new(),
+            routes_events: Vec::new(),
             should_generate_typescript_file: true,
         }
     }
@@ -30,10 +37,20 @@ impl TypesJar {
     pub fn remove_file(&mut self, file_path: PathBuf) {
         self.should_generate_typescript_file = true;
         self.types.retain(|ttype| ttype.file_path != file_path);
+        self.routes_props
+            .retain(|route_props| route_props.file_path != file_path);
+        self.routes_events
+            .retain(|route_events| route_events.file_path != file_path);
     }
 
     pub fn refresh_file(&mut self, path: PathBuf) {
         if let Ok(file_str) = read_to_string(&path) {
+            if refresh_route_types(&mut self.routes_props, &path, &file_str)
+                | refresh_route_types(&mut self.routes_events, &path, &file_str)
+            {
+                self.should_generate_typescript_file = true;
+            }
+
             if file_str.contains(*TYPE_TRAIT) {
                 if let Ok(ttype) = FileTypes::try_from((path.clone(), file_str)) {
                     if Some(&ttype) == self.types.iter().find(|t| t.file_path == path) {
@@ -44,7 +61,7 @@ impl TypesJar {
                     trace!("Refreshing: {:?} type", ttype.types);
 
                     self.should_generate_typescript_file = true;
-                    self.remove_file(path);
+                    self.types.retain(|ttype| ttype.file_path != path);
                     self.types.push(ttype);
                 } else {
                     error!("Failed to parse file: {:?}", path);
@@ -53,7 +70,10 @@ impl TypesJar {
                 // Check if the file exist. In case it is it means that the user
                 // removed the "Type" derived trait. Hence we have to remove it from
                 // the jar
-                self.remove_file(path.clone());
+                if self.types.iter().any(|ttype| ttype.file_path == path) {
+                    self.should_generate_typescript_file = true;
+                    self.types.retain(|ttype| ttype.file_path != path);
+                }
             }
         } else {
             error!("Failed to read file: {:?}", path);
@@ -121,10 +141,52 @@ impl TypesJar {
             typescript.push_str(&ttype.types_as_string);
             typescript.push_str(&format!("// END [{}]\n", ttype.file_path.to_string_lossy()));
         }
+        typescript.push_str(&self.generate_routes_props());
+        typescript.push_str(&self.generate_routes_events());
         typescript.push_str("}\n");
         typescript
     }
 
+    /// The names of the types with the `Type` derived trait
+    fn known_types(&self) -> HashSet<&String> {
+        self.types.iter().flat_map(|ttype| &ttype.types).collect()
+    }
+
+    /// Generate the `RoutesProps` interface mapping each page route
+    /// to the props type returned by its handler.
+    fn generate_routes_props(&self) -> String {
+        let mut typescript = String::from("import type { TuonoRouteProps } from \"tuono\";\n");
+        typescript.push_str(&routes_interface(
+            "RoutesProps",
+            &self.routes_props,
+            &self.known_types(),
+        ));
+        typescript.push_str(
+            "export type RouteProps<TPath extends keyof RoutesProps> = TuonoRouteProps<RoutesProps[TPath]>;\n",
+        );
+        typescript
+    }
+
+    /// Generate the `RoutesEvents` interface mapping each api route
+    /// to the type of the events sent by its `SseResponse`.
+    fn generate_routes_events(&self) -> String {
+        let mut typescript =
+            routes_interface("RoutesEvents", &self.routes_events, &self.known_types());
+        typescript.push_str(
+            "export type RouteEvent<TPath extends keyof RoutesEvents> = RoutesEvents[TPath];\n",
+        );
+        typescript
+    }
+
+    /// Collect the JSON schemas of all the types found in the jar.
+    /// The types are keyed by name, ready to be used as OpenAPI components.
+    pub fn json_schemas(&self) -> BTreeMap<String, Value> {
+        self.types
+            .iter()
+            .flat_map(|ttype| ttype.schemas.clone())
+            .collect()
+    }
+
     pub fn generate_typescript_file(&mut self, base_path: &Path) -> std::io::Result<()> {
         if !self.should_generate_typescript_file {
             trace!("No need to create typescript module file");
@@ -141,6 +203,136 @@ impl TypesJar {
     }
 }
 
+/// A type generated for each route from its handler
+/// (the page props or the api events).
+trait RouteType: TryFrom<(PathBuf, String)> + PartialEq {
+    /// What the type describes, used in the logs
+    const KIND: &'static str;
+
+    fn route_from_file_path(file_path: &Path) -> Option<String>;
+    fn file_path(&self) -> &Path;
+    fn route(&self) -> &str;
+    fn type_as_string(&self) -> &str;
+    fn referenced_types(&self) -> &[String];
+
+    /// Returns `None` when the file is not a route of this kind
+    /// or its handler doesn't define the type.
+    fn parse(file_path: &Path, file_str: &str) -> Option<Self> {
+        Self::route_from_file_path(file_path)?;
+        Self::try_from((file_path.to_path_buf(), file_str.to_string())).ok()
+    }
+}
+
+impl RouteType for RouteProps {
+    const KIND: &'static str = "props";
+
+    fn route_from_file_path(file_path: &Path) -> Option<String> {
+        route_from_file_path(file_path)
+    }
+    fn file_path(&self) -> &Path {
+        &self.file_path
+    }
+    fn route(&self) -> &str {
+        &self.route
+    }
+    fn type_as_string(&self) -> &str {
+        &self.type_as_string
+    }
+    fn referenced_types(&self) -> &[String] {
+        &self.referenced_types
+    }
+}
+
+impl RouteType for RouteEvents {
+    const KIND: &'static str = "events";
+
+    fn route_from_file_path(file_path: &Path) -> Option<String> {
+        api_route_from_file_path(file_path)
+    }
+    fn file_path(&self) -> &Path {
+        &self.file_path
+    }
+    fn route(&self) -> &str {
+        &self.route
+    }
+    fn type_as_string(&self) -> &str {
+        &self.type_as_string
+    }
+    fn referenced_types(&self) -> &[String] {
+        &self.referenced_types
+    }
+}
+
+/// Update the type of the route defined in `file_path`.
+/// Returns whether the routes changed.
+fn refresh_route_types<T: RouteType>(
+    routes: &mut Vec<T>,
+    file_path: &Path,
+    file_str: &str,
+) -> bool {
+    let route_type = T::parse(file_path, file_str);
+    let current = routes
+        .iter()
+        .position(|route_type| route_type.file_path() == file_path);
+
+    match (route_type, current) {
+        (Some(route_type), Some(index)) => {
+            if routes[index] == route_type {
+                return false;
+            }
+            trace!("Refreshing: {} route {}", route_type.route(), T::KIND);
+            routes[index] = route_type;
+        }
+        (Some(route_type), None) => routes.push(route_type),
+        (None, Some(index)) => {
+            routes.remove(index);
+        }
+        (None, None) => return false,
+    }
+    true
+}
+
+/// Generate the `name` interface mapping each route to its type.
+///
+/// Types referencing a type without the `Type` derived trait
+/// fall back to `unknown`.
+fn routes_interface<T: RouteType>(
+    name: &str,
+    routes: &[T],
+    known_types: &HashSet<&String>,
+) -> String {
+    let mut routes: Vec<&T> = routes.iter().collect();
+    routes.sort_by(|a, b| a.route().cmp(b.route()));
+
+    let mut typescript = format!("export interface {name} {{\n");
+    for route_type in routes {
+        let is_exported = route_type
+            .referenced_types()
+            .iter()
+            .all(|referenced_type| known_types.contains(referenced_type));
+
+        if !is_exported {
+            trace!(
+                "The {} type of route {} is not exported. Falling back to unknown",
+                T::KIND,
+                route_type.route()
+            );
+        }
+
+        typescript.push_str(&format!(
+            "  \"{}\": {};\n",
+            route_type.route(),
+            if is_exported {
+                route_type.type_as_string()
+            } else {
+                "unknown"
+            }
+        ));
+    }
+    typescript.push_str("}\n");
+    typescript
+}
+
 impl From<&PathBuf> for TypesJar {
     /// Fill the TypesJar with all the Rust files found within
     /// the provided `base_path`.
@@ -152,6 +344,10 @@ impl From<&PathBuf> for TypesJar {
                 files.for_each(|path| {
                     let file_path = path.unwrap_or_default();
                     if let Ok(file_str) = read_to_string(&file_path) {
+                        jar.routes_props
+                            .extend(RouteProps::parse(&file_path, &file_str));
+                        jar.routes_events
+                            .extend(RouteEvents::parse(&file_path, &file_str));
                         if !file_str.contains(*TYPE_TRAIT) {
                             return;
                         }
@@ -186,6 +382,7 @@ mod tests {
                 String::from("Type2"),
                 String::from("Type3"),
             ],
+            schemas: BTreeMap::new(),
         };
         let file_type2 = FileTypes {
             file_path: PathBuf::from("src/types2.rs"),
@@ -195,12 +392,14 @@ mod tests {
                 String::from("Type2"),
                 String::from("Type4"),
             ],
+            schemas: BTreeMap::new(),
         };
 
         let file_type3 = FileTypes {
             file_path: PathBuf::from("src/types2.rs"),
             types_as_string: String::from("type1"),
             types: vec![String::from("Type3")],
+            schemas: BTreeMap::new(),
         };
 
         let mut jar = TypesJar::new();
@@ -245,6 +444,7 @@ mod tests {
             file_path: file_path.clone(),
             types_as_string: String::from("type1"),
             types: vec![String::from("Type1")],
+            schemas: BTreeMap::new(),
         };
         assert!(jar.should_generate_typescript_file);
         //Force file generation to false
@@ -256,4 +456,63 @@ mod tests {
         assert!(jar.should_generate_typescript_file);
         assert_eq!(jar.types.len(), 0);
     }
+
+    #[test]
+    fn it_correctly_generates_the_routes_props() {
+        let mut jar = TypesJar::new();
+        jar.types.push(FileTypes {
+            file_path: PathBuf::from("src/types.rs"),
+            types_as_string: String::from("type1"),
+            types: vec![String::from("Post")],
+            schemas: BTreeMap::new(),
+        });
+        jar.routes_props.push(RouteProps {
+            file_path: PathBuf::from("src/routes/posts/[post].rs"),
+            route: String::from("/posts/[post]"),
+            type_as_string: String::from("Post"),
+            referenced_types: vec![String::from("Post")],
+        });
+        jar.routes_props.push(RouteProps {
+            file_path: PathBuf::from("src/routes/index.rs"),
+            route: String::from("/"),
+            type_as_string: String::from("Pokemon[]"),
+            referenced_types: vec![String::from("Pokemon")],
+        });
+
+        assert_eq!(
+            jar.generate_routes_props(),
+            "import type { TuonoRouteProps } from \"tuono\";\n\
+            export interface RoutesProps {\n  \"/\": unknown;\n  \"/posts/[post]\": Post;\n}\n\
+            export type RouteProps<TPath extends keyof RoutesProps> = TuonoRouteProps<RoutesProps[TPath]>;\n"
+        );
+    }
+
+    #[test]
+    fn it_correctly_generates_the_routes_events() {
+        let mut jar = TypesJar::new();
+        jar.types.push(FileTypes {
+            file_path: PathBuf::from("src/types.rs"),
+            types_as_string: String::from("type1"),
+            types: vec![String::from("Notification")],
+            schemas: BTreeMap::new(),
+        });
+        jar.routes_events.push(RouteEvents {
+            file_path: PathBuf::from("src/routes/api/notifications.rs"),
+            route: String::from("/api/notifications"),
+            type_as_string: String::from("Notification"),
+            referenced_types: vec![String::from("Notification")],
+        });
+        jar.routes_events.push(RouteEvents {
+            file_path: PathBuf::from("src/routes/api/logs.rs"),
+            route: String::from("/api/logs"),
+            type_as_string: String::from("Log"),
+            referenced_types: vec![String::from("Log")],
+        });
+
+        assert_eq!(
+            jar.generate_routes_events(),
+            "export interface RoutesEvents {\n  \"/api/logs\": unknown;\n  \"/api/notifications\": Notification;\n}\n\
+            export type RouteEvent<TPath extends keyof RoutesEvents> = RoutesEvents[TPath];\n"
+        );
+    }
 }
@@ -19,11 +19,13 @@ async fn main() {
     }
 
     //MAIN_FILE_DEFINITION//
+    //JOBS_DEFINITION//
+    //HEALTH_CHECKS_DEFINITION//
 
     let router = Router::new()
         // ROUTE_BUILDER
         //MAIN_FILE_USAGE//;
 
-    Server::init(router, MODE).await.start().await
+    Server::init(router, MODE).await//AUTHENTICATOR_USAGE////JOBS_USAGE////HEALTH_CHECKS_USAGE//.start().await
 }
 
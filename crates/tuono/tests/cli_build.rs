@@ -42,7 +42,7 @@ fn it_successfully_create_the_index_route() {
     assert!(temp_main_rs_content.contains("mod index;"));
 
     assert!(temp_main_rs_content
-        .contains(r#".route("/", get(index::tuono_internal_route)).route("/__tuono/data/", get(index::tuono_internal_api))"#));
+        .contains(r#".route("/", get(index::tuono_internal_route).layer(index::tuono_internal_limits())).route("/__tuono/data/", get(index::tuono_internal_api).layer(index::tuono_internal_limits()))"#));
 }
 
 #[test]
@@ -68,10 +68,38 @@ fn it_successfully_create_an_api_route() {
     assert!(temp_main_rs_content.contains("mod api_health_check;"));
 
     assert!(temp_main_rs_content.contains(
-        r#".route("/api/health_check", post(api_health_check::post_tuono_internal_api))"#
+        r#".route("/api/health_check", post(api_health_check::post_tuono_internal_api).layer(api_health_check::post_tuono_internal_limits()))"#
     ));
 }
 
+#[test]
+#[serial]
+fn it_successfully_create_a_websocket_route() {
+    let temp_tuono_project = TempTuonoProject::new();
+
+    temp_tuono_project.add_file_with_content(
+        "./src/routes/chat/[room].rs",
+        r#"#[tuono_lib::ws(auth = "required")]"#,
+    );
+
+    let mut test_tuono_build = Command::cargo_bin("tuono").unwrap();
+    test_tuono_build
+        .arg("build")
+        .arg("--no-js-emit")
+        .assert()
+        .success();
+
+    let temp_main_rs_path = temp_tuono_project.path().join(".tuono/main.rs");
+
+    let temp_main_rs_content =
+        fs::read_to_string(&temp_main_rs_path).expect("Failed to read '.tuono/main.rs' content.");
+
+    assert!(temp_main_rs_content.contains(
+        r#".route("/chat/{room}", get(chat_dyn_room::tuono_internal_ws).layer(chat_dyn_room::tuono_internal_limits()))"#
+    ));
+    assert!(!temp_main_rs_content.contains("chat_dyn_room::tuono_internal_route"));
+}
+
 #[test]
 #[serial]
 fn it_successfully_create_multiple_api_for_the_same_file() {
@@ -98,11 +126,11 @@ fn it_successfully_create_multiple_api_for_the_same_file() {
     assert!(temp_main_rs_content.contains("mod api_health_check;"));
 
     assert!(temp_main_rs_content.contains(
-        r#".route("/api/health_check", post(api_health_check::post_tuono_internal_api))"#
+        r#".route("/api/health_check", post(api_health_check::post_tuono_internal_api).layer(api_health_check::post_tuono_internal_limits()))"#
     ));
     assert!(
         temp_main_rs_content.contains(
-            r#".route("/api/health_check", get(api_health_check::get_tuono_internal_api))"#
+            r#".route("/api/health_check", get(api_health_check::get_tuono_internal_api).layer(api_health_check::get_tuono_internal_limits()))"#
         )
     );
 }
@@ -170,19 +198,19 @@ fn it_successfully_create_catch_all_routes() {
     assert!(temp_main_rs_content.contains("mod dyn_catch_all_all_routes;"));
 
     assert!(temp_main_rs_content.contains(
-        r#".route("/api/{*all_apis}", post(api_dyn_catch_all_all_apis::post_tuono_internal_api))"#
+        r#".route("/api/{*all_apis}", post(api_dyn_catch_all_all_apis::post_tuono_internal_api).layer(api_dyn_catch_all_all_apis::post_tuono_internal_limits()))"#
     ));
 
     assert!(temp_main_rs_content.contains(
-        r#".route("/{*all_routes}", get(dyn_catch_all_all_routes::tuono_internal_route))"#
+        r#".route("/{*all_routes}", get(dyn_catch_all_all_routes::tuono_internal_route).layer(dyn_catch_all_all_routes::tuono_internal_limits()))"#
     ));
 
     assert!(temp_main_rs_content.contains(
-        r#".route("/{*all_routes}", get(dyn_catch_all_all_routes::tuono_internal_route))"#
+        r#".route("/{*all_routes}", get(dyn_catch_all_all_routes::tuono_internal_route).layer(dyn_catch_all_all_routes::tuono_internal_limits()))"#
     ));
 
     assert!(temp_main_rs_content.contains(
-        r#".route("/__tuono/data/{*all_routes}", get(dyn_catch_all_all_routes::tuono_internal_api))"#
+        r#".route("/__tuono/data/{*all_routes}", get(dyn_catch_all_all_routes::tuono_internal_api).layer(dyn_catch_all_all_routes::tuono_internal_limits()))"#
     ));
 }
 
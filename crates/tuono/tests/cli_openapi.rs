@@ -0,0 +1,74 @@
+use assert_cmd::Command;
+use serial_test::serial;
+use std::fs;
+
+mod utils;
+use utils::temp_tuono_project::TempTuonoProject;
+
+const USERS_API_FILE: &str = r#"
+use serde::{Deserialize, Serialize};
+use tuono_lib::{Request, Type, axum::Json};
+
+#[derive(Serialize, Type)]
+struct User {
+    name: String,
+    age: Option<u8>,
+}
+
+#[derive(Deserialize, Type)]
+struct NewUser {
+    name: String,
+}
+
+/// Create a new user
+#[tuono_lib::api(POST)]
+async fn create_user(req: Request) -> Json<User> {
+    let payload = req.body::<NewUser>().unwrap();
+    Json(User { name: payload.name, age: None })
+}
+"#;
+
+#[test]
+#[serial]
+fn it_generates_the_openapi_document() {
+    let temp_tuono_project = TempTuonoProject::new();
+
+    temp_tuono_project.add_file_with_content("./src/routes/api/users/[id].rs", USERS_API_FILE);
+    temp_tuono_project.add_file("./src/routes/index.rs");
+
+    let mut test_tuono_openapi = Command::cargo_bin("tuono").unwrap();
+    test_tuono_openapi
+        .arg("openapi")
+        .arg("--output")
+        .arg("openapi.json")
+        .assert()
+        .success();
+
+    let document = fs::read_to_string(temp_tuono_project.path().join("openapi.json"))
+        .expect("Failed to read 'openapi.json' content.");
+
+    let document: serde_json::Value =
+        serde_json::from_str(&document).expect("Invalid OpenAPI document");
+
+    assert_eq!(document["openapi"], "3.1.0");
+
+    let paths = document["paths"].as_object().unwrap();
+    assert_eq!(paths.len(), 1);
+
+    let operation = &document["paths"]["/api/users/{id}"]["post"];
+    assert_eq!(operation["summary"], "Create a new user");
+    assert_eq!(operation["parameters"][0]["name"], "id");
+    assert_eq!(
+        operation["requestBody"]["content"]["application/json"]["schema"]["$ref"],
+        "#/components/schemas/NewUser"
+    );
+    assert_eq!(
+        operation["responses"]["200"]["content"]["application/json"]["schema"]["$ref"],
+        "#/components/schemas/User"
+    );
+
+    assert_eq!(
+        document["components"]["schemas"]["User"]["required"],
+        serde_json::json!(["name"])
+    );
+}
@@ -1,13 +1,32 @@
+use crate::env::read_env_files;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::fs::read_to_string;
 use std::io;
 use std::path::PathBuf;
 
+/// The prefix of the environment variables overriding the config options
+pub const ENV_PREFIX: &str = "TUONO_";
+
+/// Separates the nested options in the environment variable names
+/// (e.g. `TUONO_SERVER__PORT` overrides `server.port`)
+const ENV_SEPARATOR: &str = "__";
+
 #[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct ServerConfig {
     pub host: String,
     pub origin: Option<String>,
     pub port: u16,
+    #[serde(default)]
+    pub tls: Option<TlsConfig>,
+    /// Listen on a Unix domain socket instead of `host:port`
+    #[serde(default)]
+    pub unix_socket: Option<PathBuf>,
+    /// Listen on the socket inherited from the service manager
+    /// (i.e. systemd `LISTEN_FDS`) instead of `host:port`
+    #[serde(default)]
+    pub socket_activation: bool,
 }
 
 impl Default for ServerConfig {
@@ -16,21 +35,760 @@ impl Default for ServerConfig {
             host: "localhost".to_string(),
             origin: None,
             port: 3000,
+            tls: None,
+            unix_socket: None,
+            socket_activation: false,
+        }
+    }
+}
+
+impl ServerConfig {
+    /// The server binds `host:port` by itself
+    pub fn binds_address(&self) -> bool {
+        self.unix_socket.is_none() && !self.socket_activation
+    }
+
+    /// The URL of the server listener
+    pub fn base_url(&self) -> String {
+        let scheme = if self.tls.is_some() { "https" } else { "http" };
+        format!("{scheme}://{}:{}", self.host, self.port)
+    }
+}
+
+/// Serve HTTPS and HTTP/2 with the PEM encoded certificate chain and private key
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct TlsConfig {
+    pub cert: PathBuf,
+    pub key: PathBuf,
+    /// The port of a plain HTTP listener redirecting to HTTPS
+    #[serde(default)]
+    pub redirect_port: Option<u16>,
+}
+
+/// Redirect the requests matching `source` to `destination`.
+///
+/// Both paths accept the router path parameters syntax
+/// (e.g. `/blog/{slug}` or `/docs/{*path}`).
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct RedirectConfig {
+    pub source: String,
+    pub destination: String,
+    /// Use a permanent (308) redirect instead of a temporary (307) one
+    #[serde(default)]
+    pub permanent: bool,
+}
+
+/// Serve the `destination` route when the `source` path is requested
+/// without changing the URL displayed by the browser.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct RewriteConfig {
+    pub source: String,
+    pub destination: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionStoreKind {
+    #[default]
+    Memory,
+    /// Persist the sessions on the file system so that they
+    /// survive the server restarts
+    File,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SameSiteConfig {
+    #[default]
+    Lax,
+    Strict,
+    None,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SessionConfig {
+    pub store: SessionStoreKind,
+    /// Folder used by the file store
+    pub directory: String,
+    pub cookie_name: String,
+    /// Seconds of inactivity after which the session expires
+    pub idle_timeout: u64,
+    /// Seconds after the session creation after which it always expires
+    pub absolute_timeout: u64,
+    /// Defaults to `true` in production
+    pub secure: Option<bool>,
+    pub same_site: SameSiteConfig,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        SessionConfig {
+            store: SessionStoreKind::Memory,
+            directory: String::from(".tuono/sessions"),
+            cookie_name: String::from("tuono_session"),
+            idle_timeout: 60 * 60 * 24,
+            absolute_timeout: 60 * 60 * 24 * 7,
+            secure: None,
+            same_site: SameSiteConfig::Lax,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct CsrfConfig {
+    pub enabled: bool,
+    pub cookie_name: String,
+    /// Header read on `fetch` calls
+    pub header_name: String,
+    /// Field read on url encoded form submissions
+    pub field_name: String,
+    /// Paths excluded from the check (e.g. webhooks).
+    /// They accept the router parameters syntax.
+    pub exempt: Vec<String>,
+    /// Defaults to `true` in production
+    pub secure: Option<bool>,
+}
+
+impl Default for CsrfConfig {
+    fn default() -> Self {
+        CsrfConfig {
+            enabled: true,
+            cookie_name: String::from("tuono_csrf"),
+            header_name: String::from("x-csrf-token"),
+            field_name: String::from("_csrf"),
+            exempt: Vec::new(),
+            secure: None,
+        }
+    }
+}
+
+fn default_cors_methods() -> Vec<String> {
+    ["GET", "HEAD", "POST", "PUT", "PATCH", "DELETE"]
+        .into_iter()
+        .map(String::from)
+        .collect()
+}
+
+/// Cross-origin resource sharing. Disabled until `origins` is defined.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct CorsConfig {
+    /// Allowed origins. `*` allows any origin.
+    pub origins: Vec<String>,
+    pub methods: Vec<String>,
+    /// Allowed request headers. When empty the headers
+    /// requested by the preflight are allowed.
+    pub headers: Vec<String>,
+    pub expose_headers: Vec<String>,
+    pub credentials: bool,
+    /// Seconds the preflight response can be cached
+    pub max_age: Option<u64>,
+    /// Per-route overrides. The first route matching the request path is used.
+    pub routes: Vec<CorsRouteConfig>,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        CorsConfig {
+            origins: Vec::new(),
+            methods: default_cors_methods(),
+            headers: Vec::new(),
+            expose_headers: Vec::new(),
+            credentials: false,
+            max_age: None,
+            routes: Vec::new(),
+        }
+    }
+}
+
+/// The options not defined fallback to the top level `cors` ones
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CorsRouteConfig {
+    /// Accepts the router parameters syntax (e.g. `/api/{*path}`)
+    pub path: String,
+    pub origins: Option<Vec<String>>,
+    pub methods: Option<Vec<String>>,
+    pub headers: Option<Vec<String>>,
+    pub expose_headers: Option<Vec<String>>,
+    pub credentials: Option<bool>,
+    pub max_age: Option<u64>,
+}
+
+/// Traffic controls applied to every request
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct LimitsConfig {
+    /// Token bucket rate limit (e.g. `100/1m`)
+    pub rate_limit: Option<String>,
+    /// `ip` (default) or `header:<name>`
+    pub rate_limit_key: Option<String>,
+    /// Max number of requests handled at the same time
+    pub concurrency: Option<usize>,
+    /// Max duration of a request (e.g. `30s`)
+    pub timeout: Option<String>,
+    /// Number of reverse proxies in front of the server.
+    /// The client IP is read from the headers they set only when not zero.
+    pub trusted_proxies: usize,
+    /// Additional limits for the matching paths.
+    /// Only the first route matching the request path is applied.
+    pub routes: Vec<RouteLimitsConfig>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteLimitsConfig {
+    /// Accepts the router parameters syntax (e.g. `/api/{*path}`)
+    pub path: String,
+    pub rate_limit: Option<String>,
+    pub rate_limit_key: Option<String>,
+    pub concurrency: Option<usize>,
+    pub timeout: Option<String>,
+}
+
+/// Where the guarded routes send the unauthenticated visitors
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct AuthConfig {
+    pub login_url: String,
+    /// Query parameter holding the path requested before the login
+    pub return_param: String,
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        AuthConfig {
+            login_url: String::from("/login"),
+            return_param: String::from("redirect"),
+        }
+    }
+}
+
+/// The liveness and readiness endpoints polled by the orchestrators
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct HealthConfig {
+    pub enabled: bool,
+    pub liveness_path: String,
+    pub readiness_path: String,
+    /// Seconds after which a readiness check is considered failed
+    pub timeout: u64,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        HealthConfig {
+            enabled: true,
+            liveness_path: String::from("/__tuono/health"),
+            readiness_path: String::from("/__tuono/ready"),
+            timeout: 5,
         }
     }
 }
 
+/// The Prometheus metrics endpoint
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct MetricsConfig {
+    pub enabled: bool,
+    pub path: String,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        MetricsConfig {
+            enabled: false,
+            path: String::from("/metrics"),
+        }
+    }
+}
+
+/// The OpenTelemetry traces exported to an OTLP/HTTP collector
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TelemetryConfig {
+    pub enabled: bool,
+    /// The collector base URL. The traces are sent to `{endpoint}/v1/traces`.
+    pub endpoint: String,
+    pub service_name: String,
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        TelemetryConfig {
+            enabled: false,
+            endpoint: String::from("http://localhost:4318"),
+            service_name: String::from("tuono"),
+        }
+    }
+}
+
+/// A domain serving a single locale without the path prefix
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct I18nDomainConfig {
+    /// The host name without the port (e.g. `tuono.it`)
+    pub domain: String,
+    pub locale: String,
+}
+
+/// The internationalized routing
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct I18nConfig {
+    /// The supported locales (e.g. `["en", "it"]`).
+    /// The i18n routing is disabled when empty.
+    pub locales: Vec<String>,
+    /// The locale of the paths without prefix.
+    /// Defaults to the first of `locales`.
+    pub default_locale: Option<String>,
+    /// Prefix also the paths of the default locale (e.g. `/en/about`)
+    pub prefix_default_locale: bool,
+    pub domains: Vec<I18nDomainConfig>,
+    /// Redirect the application root to the locale preferred by the client
+    pub locale_detection: bool,
+    /// The cookie storing the locale chosen by the user
+    pub cookie_name: String,
+}
+
+impl Default for I18nConfig {
+    fn default() -> Self {
+        I18nConfig {
+            locales: Vec::new(),
+            default_locale: None,
+            prefix_default_locale: false,
+            domains: Vec::new(),
+            locale_detection: true,
+            cookie_name: String::from("TUONO_LOCALE"),
+        }
+    }
+}
+
+impl I18nConfig {
+    fn validate(&self) -> Result<(), String> {
+        for (index, locale) in self.locales.iter().enumerate() {
+            if locale.is_empty() || locale.contains('/') {
+                return Err(format!("i18n.locales: `{locale}` is not a valid locale"));
+            }
+
+            if self.locales[..index].contains(locale) {
+                return Err(format!("i18n.locales: `{locale}` is listed twice"));
+            }
+        }
+
+        let locales = self
+            .default_locale
+            .iter()
+            .map(|locale| ("i18n.defaultLocale", locale))
+            .chain(
+                self.domains
+                    .iter()
+                    .map(|domain| ("i18n.domains", &domain.locale)),
+            );
+
+        for (option, locale) in locales {
+            if !self.locales.contains(locale) {
+                return Err(format!(
+                    "{option}: `{locale}` is not one of the i18n.locales"
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.locales.is_empty()
+    }
+
+    pub fn default_locale(&self) -> Option<&str> {
+        self.default_locale
+            .as_deref()
+            .or_else(|| self.locales.first().map(String::as_str))
+    }
+
+    /// The path prefix of the `locale` (e.g. `/it`).
+    /// Empty for the default locale unless `prefix_default_locale` is set.
+    pub fn path_prefix(&self, locale: &str) -> String {
+        if !self.prefix_default_locale && self.default_locale() == Some(locale) {
+            String::new()
+        } else {
+            format!("/{locale}")
+        }
+    }
+
+    /// The path prefixes of all the locales.
+    /// A single empty prefix when the i18n routing is disabled.
+    pub fn path_prefixes(&self) -> Vec<String> {
+        if !self.is_enabled() {
+            return vec![String::new()];
+        }
+
+        self.locales
+            .iter()
+            .map(|locale| self.path_prefix(locale))
+            .collect()
+    }
+
+    /// The locale served by the `host` domain
+    pub fn domain_locale(&self, host: &str) -> Option<&str> {
+        self.domains
+            .iter()
+            .find(|domain| domain.domain.eq_ignore_ascii_case(host))
+            .map(|domain| domain.locale.as_str())
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone, Default)]
 pub struct Config {
     pub server: ServerConfig,
+    /// Either empty or starting with `/` without the trailing one
+    #[serde(
+        default,
+        rename = "basePath",
+        deserialize_with = "deserialize_base_path"
+    )]
+    pub base_path: String,
+    #[serde(default)]
+    pub redirects: Vec<RedirectConfig>,
+    #[serde(default)]
+    pub rewrites: Vec<RewriteConfig>,
+    #[serde(default)]
+    pub session: SessionConfig,
+    #[serde(default)]
+    pub csrf: CsrfConfig,
+    #[serde(default)]
+    pub cors: CorsConfig,
+    #[serde(default)]
+    pub limits: LimitsConfig,
+    #[serde(default)]
+    pub auth: AuthConfig,
+    #[serde(default)]
+    pub health: HealthConfig,
+    #[serde(default)]
+    pub metrics: MetricsConfig,
+    #[serde(default)]
+    pub telemetry: TelemetryConfig,
+    #[serde(default)]
+    pub i18n: I18nConfig,
+}
+
+/// Normalize the base path to either an empty string (root)
+/// or a path starting with `/` without the trailing one (e.g. `/shop`)
+fn deserialize_base_path<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let base_path = String::deserialize(deserializer)?;
+    let segments = base_path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<&str>>();
+
+    if segments.is_empty() {
+        Ok(String::new())
+    } else {
+        Ok(format!("/{}", segments.join("/")))
+    }
+}
+
+/// Convert a `SCREAMING_SNAKE_CASE` segment of an environment variable
+/// name to the option name (e.g. `UNIX_SOCKET` to `unixSocket`)
+fn to_camel_case(segment: &str) -> String {
+    segment
+        .split('_')
+        .filter(|word| !word.is_empty())
+        .enumerate()
+        .map(|(index, word)| {
+            let word = word.to_ascii_lowercase();
+            let mut chars = word.chars();
+            match (index, chars.next()) {
+                (0, _) | (_, None) => word,
+                (_, Some(first)) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+            }
+        })
+        .collect()
+}
+
+/// The path of the option overridden by the environment variable `name`
+/// (e.g. `TUONO_SERVER__UNIX_SOCKET` to `["server", "unixSocket"]`)
+fn env_option_path(name: &str) -> Option<Vec<String>> {
+    let path = name
+        .strip_prefix(ENV_PREFIX)?
+        .split(ENV_SEPARATOR)
+        .map(to_camel_case)
+        .collect::<Vec<String>>();
+
+    (!path.iter().any(String::is_empty)).then_some(path)
+}
+
+/// The option at `path` of the JSON config
+fn option_at<'a>(config: &'a Value, path: &[String]) -> Option<&'a Value> {
+    path.iter().try_fold(config, |value, key| value.get(key))
+}
+
+/// Parse the environment variable `value` as the type of the `current` option value.
+///
+/// The options without a value accept any JSON value or a plain string.
+fn parse_env_value(name: &str, value: &str, current: Option<&Value>) -> Result<Value, String> {
+    match current {
+        Some(Value::String(_)) => Ok(Value::String(value.to_string())),
+        Some(Value::Bool(_)) => match value.to_ascii_lowercase().as_str() {
+            "true" | "1" => Ok(Value::Bool(true)),
+            "false" | "0" => Ok(Value::Bool(false)),
+            _ => Err(format!(
+                "{name} must be either `true` or `false`, found `{value}`"
+            )),
+        },
+        Some(Value::Number(_)) => serde_json::from_str(value)
+            .map(Value::Number)
+            .map_err(|_| format!("{name} must be a number, found `{value}`")),
+        // Either a JSON array or a comma separated list (e.g. `en,it`)
+        Some(Value::Array(_)) => match serde_json::from_str(value) {
+            Ok(Value::Array(items)) => Ok(Value::Array(items)),
+            _ => Ok(Value::Array(
+                value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|item| !item.is_empty())
+                    .map(|item| Value::String(item.to_string()))
+                    .collect(),
+            )),
+        },
+        Some(Value::Object(_)) => match serde_json::from_str(value) {
+            Ok(Value::Object(options)) => Ok(Value::Object(options)),
+            _ => Err(format!("{name} must be a JSON object, found `{value}`")),
+        },
+        Some(Value::Null) | None => {
+            Ok(serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.to_string())))
+        }
+    }
+}
+
+/// Set the option at `path` creating the missing parent objects
+fn set_option(config: &mut Value, path: &[String], value: Value) {
+    let mut option = config;
+    for key in path {
+        if !option.is_object() {
+            *option = Value::Object(Default::default());
+        }
+        let Value::Object(options) = option else {
+            unreachable!()
+        };
+        option = options.entry(key.as_str()).or_insert(Value::Null);
+    }
+    *option = value;
+}
+
+/// A config option overridden by a `TUONO_*` environment variable
+#[derive(Debug)]
+struct EnvOverride {
+    name: String,
+    path: Vec<String>,
+    value: Value,
+}
+
+/// The `TUONO_*` environment variable overrides of the JSON config.
+///
+/// The environment variables not matching a config section
+/// (e.g. `TUONO_COOKIE_SECRET`) are ignored.
+fn env_overrides(
+    config: &Value,
+    vars: impl IntoIterator<Item = (String, String)>,
+) -> Result<Vec<EnvOverride>, String> {
+    let defaults = serde_json::to_value(Config::default()).map_err(|e| e.to_string())?;
+
+    let mut vars = vars
+        .into_iter()
+        .filter_map(|(name, value)| Some((env_option_path(&name)?, name, value)))
+        .filter(|(path, _, _)| defaults.get(&path[0]).is_some())
+        .collect::<Vec<(Vec<String>, String, String)>>();
+
+    // The sections are overridden before their options
+    vars.sort();
+
+    vars.into_iter()
+        .map(|(path, name, value)| {
+            let current = option_at(config, &path).or_else(|| option_at(&defaults, &path));
+
+            if current.is_none() {
+                let parent = &path[..path.len() - 1];
+                let known_parent =
+                    option_at(config, parent).or_else(|| option_at(&defaults, parent));
+
+                // The options of the unset sections (e.g. `server.tls`) can't be checked
+                if known_parent.is_some_and(Value::is_object) {
+                    return Err(format!("{name} doesn't match any config option"));
+                }
+            }
+
+            let value = parse_env_value(&name, &value, current)?;
+            Ok(EnvOverride { name, path, value })
+        })
+        .collect()
+}
+
+/// The JSON config with the `overrides` applied
+fn with_overrides<'a>(config: &Value, overrides: impl Iterator<Item = &'a EnvOverride>) -> Value {
+    let mut config = config.clone();
+    for env_override in overrides {
+        set_option(&mut config, &env_override.path, env_override.value.clone());
+    }
+    config
+}
+
+/// The error of the invalid overridden config.
+/// It names the environment variable making the config invalid when possible.
+fn invalid_override_error(
+    config: &Value,
+    overrides: &[EnvOverride],
+    error: serde_json::Error,
+) -> String {
+    let invalid_override = overrides.iter().enumerate().find(|(index, _)| {
+        let others = overrides
+            .iter()
+            .enumerate()
+            .filter(|(other_index, _)| other_index != index)
+            .map(|(_, env_override)| env_override);
+
+        serde_json::from_value::<Config>(with_overrides(config, others)).is_ok()
+    });
+
+    match invalid_override {
+        Some((_, env_override)) => format!("Invalid {}: {error}", env_override.name),
+        None => format!("Invalid config: {error}"),
+    }
+}
+
+/// The environment variables with a valid UTF-8 name and value
+fn env_vars() -> impl Iterator<Item = (String, String)> {
+    std::env::vars_os()
+        .filter_map(|(name, value)| Some((name.into_string().ok()?, value.into_string().ok()?)))
 }
 
 impl Config {
+    /// Read the config generated from `tuono.config.ts`.
+    ///
+    /// Every option can be overridden at runtime with a `TUONO_*` environment
+    /// variable: the section and the option names are written in
+    /// `SCREAMING_SNAKE_CASE` and separated by a double underscore
+    /// (e.g. `TUONO_SERVER__PORT=8080`, `TUONO_SERVER__TLS__CERT=cert.pem`,
+    /// `TUONO_BASE_PATH=/shop`, `TUONO_I18N__LOCALES=en,it`).
     pub fn get() -> io::Result<Config> {
+        Config::read(env_vars())
+    }
+
+    /// Read the config applying also the overrides defined by the `.env` files
+    /// of the `mode` (`development` or `production`), without loading them
+    /// in the process env.
+    pub fn get_with_env_files(mode: &str) -> io::Result<Config> {
+        let (env_file_vars, _) = read_env_files(mode);
+        Config::read(env_vars().chain(env_file_vars))
+    }
+
+    fn read(vars: impl IntoIterator<Item = (String, String)>) -> io::Result<Config> {
         let config_file = read_to_string(PathBuf::from_iter([".tuono", "config", "config.json"]))?;
 
-        serde_json::from_str(&config_file)
-            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+        Config::parse(&config_file, vars).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Parse the JSON config applying the `TUONO_*` overrides found in `vars`
+    pub fn parse(
+        json: &str,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<Config, String> {
+        let json = serde_json::from_str::<Value>(json)
+            .map_err(|e| format!("The config file is not valid JSON: {e}"))?;
+
+        serde_json::from_value::<Config>(json.clone())
+            .map_err(|e| format!("Invalid config: {e}"))?;
+
+        let overrides = env_overrides(&json, vars)?;
+
+        let config = serde_json::from_value::<Config>(with_overrides(&json, overrides.iter()))
+            .map_err(|e| invalid_override_error(&json, &overrides, e))?;
+
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    /// Check the options whose valid values can't be expressed by their types
+    pub fn validate(&self) -> Result<(), String> {
+        let server = &self.server;
+
+        if server.host.trim().is_empty() {
+            return Err("server.host can't be empty".into());
+        }
+
+        if let Some(origin) = &server.origin
+            && !origin.starts_with("http://")
+            && !origin.starts_with("https://")
+        {
+            return Err(format!(
+                "server.origin must start with `http://` or `https://`, found `{origin}`"
+            ));
+        }
+
+        if server.unix_socket.is_some() && server.socket_activation {
+            return Err(
+                "server.unixSocket and server.socketActivation can't be used together".into(),
+            );
+        }
+
+        if let Some(tls) = &server.tls
+            && tls.redirect_port == Some(server.port)
+        {
+            return Err(format!(
+                "server.tls.redirectPort must differ from server.port ({})",
+                server.port
+            ));
+        }
+
+        let paths = self
+            .redirects
+            .iter()
+            .map(|redirect| ("redirects", &redirect.source))
+            .chain(
+                self.rewrites
+                    .iter()
+                    .map(|rewrite| ("rewrites", &rewrite.source)),
+            )
+            .chain([
+                ("health.livenessPath", &self.health.liveness_path),
+                ("health.readinessPath", &self.health.readiness_path),
+                ("metrics.path", &self.metrics.path),
+            ]);
+
+        for (option, path) in paths {
+            if !path.starts_with('/') {
+                return Err(format!("{option}: `{path}` must start with `/`"));
+            }
+        }
+
+        let telemetry_endpoint = &self.telemetry.endpoint;
+        if !telemetry_endpoint.starts_with("http://") && !telemetry_endpoint.starts_with("https://")
+        {
+            return Err(format!(
+                "telemetry.endpoint must start with `http://` or `https://`, found `{telemetry_endpoint}`"
+            ));
+        }
+
+        self.i18n.validate()
+    }
+
+    /// Prefix the application `path` with the base path.
+    /// The root of the application is the base path itself.
+    pub fn with_base_path(&self, path: &str) -> String {
+        if self.base_path.is_empty() {
+            path.to_string()
+        } else if path == "/" {
+            self.base_path.clone()
+        } else {
+            format!("{}{path}", self.base_path)
+        }
     }
 }
 
@@ -45,5 +803,369 @@ mod tests {
         assert_eq!(config.server.host, "localhost".to_string());
         assert_eq!(config.server.origin, None);
         assert_eq!(config.server.port, 3000);
+        assert_eq!(config.server.tls, None);
+        assert!(config.server.binds_address());
+        assert_eq!(config.server.base_url(), "http://localhost:3000");
+        assert_eq!(config.base_path, "");
+        assert_eq!(config.with_base_path("/"), "/");
+        assert!(config.redirects.is_empty());
+        assert!(config.rewrites.is_empty());
+        assert_eq!(config.session, SessionConfig::default());
+        assert!(config.csrf.enabled);
+        assert!(config.cors.origins.is_empty());
+        assert_eq!(config.limits, LimitsConfig::default());
+        assert_eq!(config.auth.login_url, "/login");
+        assert_eq!(config.auth.return_param, "redirect");
+        assert!(config.health.enabled);
+        assert_eq!(config.health.liveness_path, "/__tuono/health");
+        assert_eq!(config.health.readiness_path, "/__tuono/ready");
+        assert!(!config.metrics.enabled);
+        assert_eq!(config.metrics.path, "/metrics");
+        assert!(!config.telemetry.enabled);
+        assert_eq!(config.telemetry.endpoint, "http://localhost:4318");
+        assert_eq!(config.telemetry.service_name, "tuono");
+        assert!(!config.i18n.is_enabled());
+        assert_eq!(config.i18n.path_prefixes(), vec![String::new()]);
+        assert_eq!(config.i18n.cookie_name, "TUONO_LOCALE");
+    }
+
+    #[test]
+    fn test_config_cors() {
+        let config: Config = serde_json::from_str(
+            r#"{
+                "server": {"host": "localhost", "origin": null, "port": 3000},
+                "cors": {
+                    "origins": ["https://m.tuono.dev"],
+                    "maxAge": 600,
+                    "routes": [{"path": "/api/{*path}", "credentials": true}]
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.cors.origins, vec!["https://m.tuono.dev"]);
+        assert_eq!(config.cors.methods, default_cors_methods());
+        assert_eq!(config.cors.max_age, Some(600));
+        assert_eq!(
+            config.cors.routes,
+            vec![CorsRouteConfig {
+                path: "/api/{*path}".to_string(),
+                credentials: Some(true),
+                ..CorsRouteConfig::default()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_config_tls() {
+        let config: Config = serde_json::from_str(
+            r#"{
+                "server": {
+                    "host": "localhost",
+                    "origin": null,
+                    "port": 3443,
+                    "tls": {"cert": "certs/cert.pem", "key": "certs/key.pem", "redirectPort": 3000}
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.server.tls,
+            Some(TlsConfig {
+                cert: PathBuf::from("certs/cert.pem"),
+                key: PathBuf::from("certs/key.pem"),
+                redirect_port: Some(3000),
+            })
+        );
+        assert_eq!(config.server.base_url(), "https://localhost:3443");
+    }
+
+    #[test]
+    fn test_config_unix_socket() {
+        let config: Config = serde_json::from_str(
+            r#"{
+                "server": {
+                    "host": "localhost",
+                    "origin": null,
+                    "port": 3000,
+                    "unixSocket": "/run/tuono/app.sock"
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.server.unix_socket,
+            Some(PathBuf::from("/run/tuono/app.sock"))
+        );
+        assert!(!config.server.socket_activation);
+        assert!(!config.server.binds_address());
+    }
+
+    #[test]
+    fn test_config_base_path() {
+        let config: Config = serde_json::from_str(
+            r#"{
+                "server": {"host": "localhost", "origin": null, "port": 3000},
+                "basePath": "shop/"
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.base_path, "/shop");
+        assert_eq!(config.with_base_path("/"), "/shop");
+        assert_eq!(config.with_base_path("/about"), "/shop/about");
+
+        let config: Config = serde_json::from_str(
+            r#"{
+                "server": {"host": "localhost", "origin": null, "port": 3000},
+                "basePath": "/"
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.base_path, "");
+        assert_eq!(config.with_base_path("/about"), "/about");
+    }
+
+    #[test]
+    fn test_config_i18n() {
+        let config: Config = serde_json::from_str(
+            r#"{
+                "server": {"host": "localhost", "origin": null, "port": 3000},
+                "i18n": {
+                    "locales": ["en", "it", "fr"],
+                    "domains": [{"domain": "tuono.fr", "locale": "fr"}]
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert!(config.i18n.is_enabled());
+        assert_eq!(config.i18n.default_locale(), Some("en"));
+        assert!(config.i18n.locale_detection);
+        assert_eq!(config.i18n.path_prefix("en"), "");
+        assert_eq!(config.i18n.path_prefix("it"), "/it");
+        assert_eq!(config.i18n.path_prefixes(), vec!["", "/it", "/fr"]);
+        assert_eq!(config.i18n.domain_locale("Tuono.fr"), Some("fr"));
+        assert_eq!(config.i18n.domain_locale("tuono.dev"), None);
+
+        let config: Config = serde_json::from_str(
+            r#"{
+                "server": {"host": "localhost", "origin": null, "port": 3000},
+                "i18n": {"locales": ["en", "it"], "defaultLocale": "it", "prefixDefaultLocale": true}
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.i18n.default_locale(), Some("it"));
+        assert_eq!(config.i18n.path_prefixes(), vec!["/en", "/it"]);
+    }
+
+    #[test]
+    fn test_config_session() {
+        let config: Config = serde_json::from_str(
+            r#"{
+                "server": {"host": "localhost", "origin": null, "port": 3000},
+                "session": {"store": "file", "cookieName": "sid", "idleTimeout": 60, "sameSite": "strict"}
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.session,
+            SessionConfig {
+                store: SessionStoreKind::File,
+                cookie_name: "sid".to_string(),
+                idle_timeout: 60,
+                same_site: SameSiteConfig::Strict,
+                ..SessionConfig::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_config_limits() {
+        let config: Config = serde_json::from_str(
+            r#"{
+                "server": {"host": "localhost", "origin": null, "port": 3000},
+                "limits": {"rateLimit": "100/1m", "trustedProxies": 1}
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.limits,
+            LimitsConfig {
+                rate_limit: Some("100/1m".to_string()),
+                trusted_proxies: 1,
+                ..LimitsConfig::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_config_redirects_and_rewrites() {
+        let config: Config = serde_json::from_str(
+            r#"{
+                "server": {"host": "localhost", "origin": null, "port": 3000},
+                "redirects": [
+                    {"source": "/old", "destination": "/new"},
+                    {"source": "/blog/{slug}", "destination": "/posts/{slug}", "permanent": true}
+                ],
+                "rewrites": [{"source": "/about", "destination": "/company/about"}]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.redirects,
+            vec![
+                RedirectConfig {
+                    source: "/old".to_string(),
+                    destination: "/new".to_string(),
+                    permanent: false,
+                },
+                RedirectConfig {
+                    source: "/blog/{slug}".to_string(),
+                    destination: "/posts/{slug}".to_string(),
+                    permanent: true,
+                },
+            ]
+        );
+        assert_eq!(
+            config.rewrites,
+            vec![RewriteConfig {
+                source: "/about".to_string(),
+                destination: "/company/about".to_string(),
+            }]
+        );
+    }
+
+    fn vars(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+        vars.iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    const SERVER_CONFIG: &str =
+        r#"{"server": {"host": "localhost", "origin": null, "port": 3000}}"#;
+
+    #[test]
+    fn test_config_env_option_path() {
+        assert_eq!(
+            env_option_path("TUONO_SERVER__UNIX_SOCKET"),
+            Some(vec!["server".to_string(), "unixSocket".to_string()])
+        );
+        assert_eq!(
+            env_option_path("TUONO_BASE_PATH"),
+            Some(vec!["basePath".to_string()])
+        );
+        assert_eq!(env_option_path("TUONO_SERVER__"), None);
+        assert_eq!(env_option_path("PORT"), None);
+    }
+
+    #[test]
+    fn test_config_env_overrides() {
+        let config = Config::parse(
+            SERVER_CONFIG,
+            vars(&[
+                ("TUONO_SERVER__PORT", "8080"),
+                ("TUONO_SERVER__HOST", "0.0.0.0"),
+                ("TUONO_SERVER__ORIGIN", "https://tuono.dev"),
+                ("TUONO_SERVER__TLS__CERT", "cert.pem"),
+                ("TUONO_SERVER__TLS__KEY", "key.pem"),
+                ("TUONO_BASE_PATH", "/shop/"),
+                ("TUONO_CSRF__ENABLED", "false"),
+                ("TUONO_I18N__LOCALES", "en, it"),
+                ("TUONO_CORS__ORIGINS", r#"["https://m.tuono.dev"]"#),
+                // Not config options
+                ("TUONO_COOKIE_SECRET", "secret"),
+                ("TUONO_PUBLIC_API_URL", "https://api.tuono.dev"),
+                ("PORT", "4000"),
+            ]),
+        )
+        .unwrap();
+
+        assert_eq!(config.server.port, 8080);
+        assert_eq!(config.server.host, "0.0.0.0");
+        assert_eq!(config.server.origin.as_deref(), Some("https://tuono.dev"));
+        assert_eq!(
+            config.server.tls,
+            Some(TlsConfig {
+                cert: PathBuf::from("cert.pem"),
+                key: PathBuf::from("key.pem"),
+                redirect_port: None,
+            })
+        );
+        assert_eq!(config.base_path, "/shop");
+        assert!(!config.csrf.enabled);
+        assert_eq!(config.i18n.locales, vec!["en", "it"]);
+        assert_eq!(config.cors.origins, vec!["https://m.tuono.dev"]);
+    }
+
+    #[test]
+    fn test_config_invalid_env_overrides() {
+        let error = |name: &str, value: &str| {
+            Config::parse(SERVER_CONFIG, vars(&[(name, value)]))
+                .err()
+                .unwrap()
+        };
+
+        assert_eq!(
+            error("TUONO_SERVER__PORT", "http"),
+            "TUONO_SERVER__PORT must be a number, found `http`"
+        );
+        assert_eq!(
+            error("TUONO_SERVER__PORT", "70000"),
+            "Invalid TUONO_SERVER__PORT: invalid value: integer `70000`, expected u16"
+        );
+        assert_eq!(
+            error("TUONO_CSRF__ENABLED", "no"),
+            "TUONO_CSRF__ENABLED must be either `true` or `false`, found `no`"
+        );
+        assert_eq!(
+            error("TUONO_SERVER__PROT", "8080"),
+            "TUONO_SERVER__PROT doesn't match any config option"
+        );
+    }
+
+    #[test]
+    fn test_config_validation() {
+        let error = |json: &str| Config::parse(json, Vec::new()).err().unwrap();
+
+        assert_eq!(
+            error("INVALID JSON"),
+            "The config file is not valid JSON: expected value at line 1 column 1"
+        );
+        assert_eq!(
+            error(r#"{"server": {"host": "localhost", "origin": "tuono.dev", "port": 3000}}"#),
+            "server.origin must start with `http://` or `https://`, found `tuono.dev`"
+        );
+        assert_eq!(
+            error(
+                r#"{
+                    "server": {"host": "localhost", "origin": null, "port": 3000},
+                    "redirects": [{"source": "old", "destination": "/new"}]
+                }"#
+            ),
+            "redirects: `old` must start with `/`"
+        );
+        assert_eq!(
+            error(
+                r#"{
+                    "server": {"host": "localhost", "origin": null, "port": 3000},
+                    "i18n": {"locales": ["en", "it"], "defaultLocale": "fr"}
+                }"#
+            ),
+            "i18n.defaultLocale: `fr` is not one of the i18n.locales"
+        );
+        assert_eq!(
+            error(
+                r#"{"server": {"host": "localhost", "origin": null, "port": 3000, "unixSocket": "t.sock", "socketActivation": true}}"#
+            ),
+            "server.unixSocket and server.socketActivation can't be used together"
+        );
     }
 }
@@ -0,0 +1,989 @@
+use crate::tuono_println;
+use colored::Colorize;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+
+/// A part of a dotenv value
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Text(String),
+    /// `$NAME`, `${NAME}`, `${NAME-fallback}` or `${NAME:-fallback}`
+    Variable {
+        name: String,
+        fallback: Option<Fallback>,
+    },
+}
+
+/// The value used when the referenced variable is not defined
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Fallback {
+    /// `:-` replaces also the empty values
+    if_empty: bool,
+    parts: Vec<Part>,
+}
+
+/// A `KEY=value` entry of a dotenv file
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Entry {
+    key: String,
+    value: Vec<Part>,
+    line: usize,
+}
+
+/// A malformed entry of a dotenv file
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Warning {
+    line: usize,
+    message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Quoting {
+    None,
+    Double,
+}
+
+fn is_name_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_'
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Parse the dotenv syntax:
+///
+/// - `# comments`, empty lines and the `export` prefix
+/// - unquoted values, trimmed and ending at the inline ` # comment`
+/// - `'single quoted'` literal values
+/// - `"double quoted"` values with the `\n`, `\r`, `\t`, `\"`, `\\` and `\$` escapes
+/// - quoted values spanning multiple lines
+/// - `$VAR`, `${VAR}`, `${VAR-fallback}` and `${VAR:-fallback}` references
+///   in the unquoted and double quoted values
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+    line: usize,
+}
+
+impl Parser {
+    fn new(contents: &str) -> Self {
+        Parser {
+            chars: contents.chars().collect(),
+            position: 0,
+            line: 1,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn peek_next(&self) -> Option<char> {
+        self.chars.get(self.position + 1).copied()
+    }
+
+    fn advance(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += 1;
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    fn skip_blanks(&mut self) {
+        while self
+            .peek()
+            .is_some_and(|c| c == ' ' || c == '\t' || c == '\r')
+        {
+            self.advance();
+        }
+    }
+
+    fn skip_line(&mut self) {
+        while self.peek().is_some_and(|c| c != '\n') {
+            self.advance();
+        }
+    }
+
+    fn take_name(&mut self) -> String {
+        let mut name = String::new();
+        if self.peek().is_some_and(is_name_start) {
+            while let Some(c) = self.peek().filter(|c| is_name_char(*c)) {
+                name.push(c);
+                self.advance();
+            }
+        }
+        name
+    }
+
+    fn parse(mut self) -> (Vec<Entry>, Vec<Warning>) {
+        let mut entries = Vec::new();
+        let mut warnings = Vec::new();
+
+        loop {
+            while self.peek().is_some_and(char::is_whitespace) {
+                self.advance();
+            }
+
+            match self.peek() {
+                None => break,
+                Some('#') => self.skip_line(),
+                Some(_) => {
+                    let line = self.line;
+                    match self.parse_entry() {
+                        Ok(entry) => entries.push(entry),
+                        Err(message) => {
+                            warnings.push(Warning { line, message });
+                            self.skip_line();
+                        }
+                    }
+                }
+            }
+        }
+
+        (entries, warnings)
+    }
+
+    fn parse_entry(&mut self) -> Result<Entry, String> {
+        let line = self.line;
+        let mut key = self.take_name();
+
+        if key == "export" && self.peek().is_some_and(|c| c == ' ' || c == '\t') {
+            self.skip_blanks();
+            key = self.take_name();
+        }
+
+        if key.is_empty() || self.peek().is_some_and(|c| !c.is_whitespace() && c != '=') {
+            return Err("Invalid variable name".to_string());
+        }
+
+        self.skip_blanks();
+        if self.peek() != Some('=') {
+            return Err(format!("Missing `=` after {key}"));
+        }
+        self.advance();
+        self.skip_blanks();
+
+        let value = match self.peek() {
+            Some('\'') => {
+                self.advance();
+                let mut text = String::new();
+                loop {
+                    match self.advance() {
+                        Some('\'') => break,
+                        Some(c) => text.push(c),
+                        None => return Err(format!("Unterminated single quoted value of {key}")),
+                    }
+                }
+                self.end_of_quoted_value(&key)?;
+                vec![Part::Text(text)]
+            }
+            Some('"') => {
+                self.advance();
+                let value = self
+                    .parse_parts(Quoting::Double, false)
+                    .map_err(|message| format!("{message} in the value of {key}"))?;
+                self.end_of_quoted_value(&key)?;
+                value
+            }
+            _ => self
+                .parse_parts(Quoting::None, false)
+                .map_err(|message| format!("{message} in the value of {key}"))?,
+        };
+
+        Ok(Entry { key, value, line })
+    }
+
+    /// Only an inline comment can follow the closing quote
+    fn end_of_quoted_value(&mut self, key: &str) -> Result<(), String> {
+        self.skip_blanks();
+        match self.peek() {
+            None | Some('\n') => Ok(()),
+            Some('#') => {
+                self.skip_line();
+                Ok(())
+            }
+            Some(_) => Err(format!("Unexpected characters after the value of {key}")),
+        }
+    }
+
+    /// Parse the value until its end: the closing double quote, the end of
+    /// the line of the unquoted values or the closing brace of a fallback.
+    fn parse_parts(&mut self, quoting: Quoting, fallback: bool) -> Result<Vec<Part>, String> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+
+        loop {
+            match self.peek() {
+                None | Some('\n') if quoting == Quoting::None && !fallback => break,
+                None => {
+                    return Err(if fallback {
+                        "Unterminated `${`".to_string()
+                    } else {
+                        "Unterminated double quoted value".to_string()
+                    });
+                }
+                Some('\n') if quoting == Quoting::None => {
+                    return Err("Unterminated `${`".to_string());
+                }
+                Some('}') if fallback => {
+                    self.advance();
+                    break;
+                }
+                Some('"') if quoting == Quoting::Double => {
+                    if fallback {
+                        return Err("Unterminated `${`".to_string());
+                    }
+                    self.advance();
+                    break;
+                }
+                // Inline comments need a leading whitespace (e.g. `KEY=value # comment`)
+                Some('#')
+                    if quoting == Quoting::None
+                        && !fallback
+                        && (parts.is_empty() && text.is_empty() || text.ends_with([' ', '\t'])) =>
+                {
+                    self.skip_line();
+                    break;
+                }
+                Some('\\') => {
+                    self.advance();
+                    let escaped = match (quoting, self.peek()) {
+                        (_, Some('$')) => Some('$'),
+                        (Quoting::Double, Some('n')) => Some('\n'),
+                        (Quoting::Double, Some('r')) => Some('\r'),
+                        (Quoting::Double, Some('t')) => Some('\t'),
+                        (Quoting::Double, Some(c @ ('"' | '\\'))) => Some(c),
+                        _ => None,
+                    };
+                    match escaped {
+                        Some(c) => {
+                            self.advance();
+                            text.push(c);
+                        }
+                        None => text.push('\\'),
+                    }
+                }
+                Some('$') => {
+                    self.advance();
+                    match self.parse_variable(quoting)? {
+                        Some(variable) => {
+                            if !text.is_empty() {
+                                parts.push(Part::Text(std::mem::take(&mut text)));
+                            }
+                            parts.push(variable);
+                        }
+                        None => text.push('$'),
+                    }
+                }
+                Some(c) => {
+                    self.advance();
+                    text.push(c);
+                }
+            }
+        }
+
+        // The unquoted values are trimmed
+        if quoting == Quoting::None && !fallback {
+            text.truncate(text.trim_end().len());
+        }
+
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+
+        Ok(parts)
+    }
+
+    /// Parse the variable reference following a `$`.
+    /// `None` when the `$` is a literal one.
+    fn parse_variable(&mut self, quoting: Quoting) -> Result<Option<Part>, String> {
+        if self.peek() != Some('{') {
+            let name = self.take_name();
+            return Ok((!name.is_empty()).then_some(Part::Variable {
+                name,
+                fallback: None,
+            }));
+        }
+
+        self.advance();
+        let name = self.take_name();
+        if name.is_empty() {
+            return Err("Invalid variable name after `${`".to_string());
+        }
+
+        let if_empty = match (self.peek(), self.peek_next()) {
+            (Some('}'), _) => {
+                self.advance();
+                return Ok(Some(Part::Variable {
+                    name,
+                    fallback: None,
+                }));
+            }
+            (Some(':'), Some('-')) => {
+                self.advance();
+                true
+            }
+            (Some('-'), _) => false,
+            _ => return Err(format!("Unterminated `${{{name}`")),
+        };
+        self.advance();
+
+        let parts = self.parse_parts(quoting, true)?;
+
+        Ok(Some(Part::Variable {
+            name,
+            fallback: Some(Fallback { if_empty, parts }),
+        }))
+    }
+}
+
+/// A value defined by a dotenv file
+struct Definition<'a> {
+    file: &'a str,
+    entry: Entry,
+}
+
+/// Expand the variable references of the dotenv values.
+///
+/// The references are resolved with the system env first, then with the
+/// values of the files with the highest precedence, regardless of the file
+/// defining them. A variable referencing itself gets the value of its previous
+/// definition (e.g. `API=${API}/v2` in `.env.local` extends the `.env` one).
+struct Resolver<'a> {
+    system: &'a HashMap<String, String>,
+    /// The definitions of each variable sorted by increasing precedence
+    definitions: &'a HashMap<String, Vec<Definition<'a>>>,
+    resolved: HashMap<(String, usize), String>,
+    /// The definitions being expanded
+    resolving: Vec<(String, usize)>,
+    warnings: Vec<String>,
+}
+
+impl Resolver<'_> {
+    fn lookup(&mut self, name: &str) -> Option<String> {
+        if let Some(value) = self.system.get(name) {
+            return Some(value.clone());
+        }
+
+        let definitions = self.definitions.get(name)?;
+
+        let expanding = self
+            .resolving
+            .iter()
+            .filter(|(resolving, _)| resolving == name)
+            .map(|(_, index)| *index)
+            .min();
+
+        let index = match expanding {
+            None => definitions.len() - 1,
+            Some(0) => {
+                let definition = &definitions[0];
+                self.warnings.push(format!(
+                    "{}:{} {name} references itself",
+                    definition.file, definition.entry.line
+                ));
+                return None;
+            }
+            Some(index) => index - 1,
+        };
+
+        let key = (name.to_string(), index);
+        if let Some(value) = self.resolved.get(&key) {
+            return Some(value.clone());
+        }
+
+        self.resolving.push(key.clone());
+        let value = self.expand(&definitions[index].entry.value);
+        self.resolving.pop();
+
+        self.resolved.insert(key, value.clone());
+        Some(value)
+    }
+
+    fn expand(&mut self, parts: &[Part]) -> String {
+        let mut value = String::new();
+
+        for part in parts {
+            match part {
+                Part::Text(text) => value.push_str(text),
+                Part::Variable { name, fallback } => match (self.lookup(name), fallback) {
+                    (Some(variable), Some(fallback))
+                        if fallback.if_empty && variable.is_empty() =>
+                    {
+                        value.push_str(&self.expand(&fallback.parts))
+                    }
+                    (Some(variable), _) => value.push_str(&variable),
+                    (None, Some(fallback)) => value.push_str(&self.expand(&fallback.parts)),
+                    (None, None) => {}
+                },
+            }
+        }
+
+        value
+    }
+}
+
+/// The variables defined by the dotenv `files` sorted by increasing precedence
+/// and not already defined in the `system` env, with the warnings
+/// of the malformed entries.
+fn resolve_env_files(
+    files: &[(String, String)],
+    system: &HashMap<String, String>,
+) -> (Vec<(String, String)>, Vec<String>) {
+    let mut warnings = Vec::new();
+    let mut definitions: HashMap<String, Vec<Definition>> = HashMap::new();
+    let mut keys: Vec<String> = Vec::new();
+
+    for (file, contents) in files {
+        let (entries, file_warnings) = Parser::new(contents).parse();
+
+        warnings.extend(
+            file_warnings
+                .into_iter()
+                .map(|warning| format!("{file}:{} {}", warning.line, warning.message)),
+        );
+
+        for entry in entries {
+            // Skip if key exists in system env
+            if system.contains_key(&entry.key) {
+                continue;
+            }
+            if !keys.contains(&entry.key) {
+                keys.push(entry.key.clone());
+            }
+            definitions
+                .entry(entry.key.clone())
+                .or_default()
+                .push(Definition { file, entry });
+        }
+    }
+
+    let mut resolver = Resolver {
+        system,
+        definitions: &definitions,
+        resolved: HashMap::new(),
+        resolving: Vec::new(),
+        warnings: Vec::new(),
+    };
+
+    let vars = keys
+        .into_iter()
+        .map(|key| {
+            let value = resolver.lookup(&key).unwrap_or_default();
+            (key, value)
+        })
+        .collect();
+
+    warnings.extend(resolver.warnings);
+
+    (vars, warnings)
+}
+
+/// The env files of the `mode` (`development` or `production`)
+/// sorted by increasing precedence
+fn env_files(mode: &str) -> Vec<String> {
+    vec![
+        String::from(".env"),
+        format!(".env.{mode}"),
+        String::from(".env.local"),
+        format!(".env.{mode}.local"),
+    ]
+}
+
+/// Read the variables defined by the .env files of the `mode`
+/// and not already defined in the OS env, with the warnings
+/// of the malformed entries.
+pub fn read_env_files(mode: &str) -> (Vec<(String, String)>, Vec<String>) {
+    let files = env_files(mode)
+        .into_iter()
+        .filter_map(|file| Some((file.clone(), fs::read_to_string(file).ok()?)))
+        .collect::<Vec<(String, String)>>();
+
+    let system = env::vars().collect::<HashMap<String, String>>();
+
+    resolve_env_files(&files, &system)
+}
+
+/// Read the env variables from the .env files
+/// and set them in the OS env.
+///
+/// The variables already defined in the OS env are not overridden.
+/// The malformed entries are reported and skipped.
+///
+/// # Safety
+///
+/// This function modifies the OS env variables: it must be called
+/// in a single-threaded context.
+pub unsafe fn load_env_vars(mode: &str) {
+    let (vars, warnings) = read_env_files(mode);
+
+    for warning in warnings {
+        tuono_println!("{}", format!("[ENV] {warning}").yellow());
+    }
+
+    for (key, value) in vars {
+        unsafe {
+            env::set_var(key, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+    use std::collections::HashMap;
+    use std::env;
+    use std::fs;
+
+    struct MockEnv {
+        files: Vec<String>,
+        vars: HashMap<String, String>,
+    }
+
+    impl MockEnv {
+        fn new() -> Self {
+            Self {
+                files: Vec::new(),
+                vars: HashMap::new(),
+            }
+        }
+
+        fn add_system_var(&mut self, k: &str, v: &str) {
+            self.vars.insert(k.to_string(), v.to_string());
+            unsafe {
+                env::set_var(k, v);
+            }
+        }
+
+        pub fn setup_env_file(&mut self, file_name: &str, contents: &str) {
+            self.files.push(file_name.to_string());
+            fs::write(file_name, contents).expect("Failed to write test .env file");
+        }
+
+        pub fn capture_keys(&mut self, keys: &[&str]) {
+            for key in keys {
+                if let Ok(val) = env::var(key) {
+                    self.vars.insert(key.to_string(), val);
+                }
+            }
+        }
+    }
+
+    impl Drop for MockEnv {
+        fn drop(&mut self) {
+            for file in self.files.iter() {
+                let _ = fs::remove_file(file.as_str());
+            }
+            for key in self.vars.keys() {
+                unsafe {
+                    env::remove_var(key);
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_system_env_var_precedence() {
+        let mut mock_env = MockEnv::new();
+
+        mock_env.add_system_var("TEST_KEY", "system_value");
+        mock_env.setup_env_file(".env", "TEST_KEY=file_value");
+
+        unsafe {
+            load_env_vars("development");
+        }
+
+        mock_env.capture_keys(&["TEST_KEY"]);
+
+        assert_eq!(env::var("TEST_KEY").unwrap(), "system_value");
+    }
+
+    #[test]
+    #[serial]
+    fn test_mode_specific_env_var_precedence_dev() {
+        let mut mock_env = MockEnv::new();
+
+        mock_env.setup_env_file(".env", "TEST_KEY=base_value");
+        mock_env.setup_env_file(".env.development", "TEST_KEY=development_value");
+
+        unsafe {
+            load_env_vars("development");
+        }
+
+        mock_env.capture_keys(&["TEST_KEY"]);
+
+        assert_eq!(env::var("TEST_KEY").unwrap(), "development_value");
+    }
+
+    #[test]
+    #[serial]
+    fn test_mode_specific_env_var_precedence_prod() {
+        let mut mock_env = MockEnv::new();
+
+        mock_env.setup_env_file(".env", "TEST_KEY=base_value");
+        mock_env.setup_env_file(".env.production", "TEST_KEY=production_value");
+
+        unsafe {
+            load_env_vars("production");
+        }
+
+        mock_env.capture_keys(&["TEST_KEY"]);
+
+        assert_eq!(env::var("TEST_KEY").unwrap(), "production_value");
+    }
+
+    #[test]
+    #[serial]
+    fn test_local_env_var_precedence() {
+        let mut mock_env = MockEnv::new();
+
+        mock_env.setup_env_file(".env", "TEST_KEY=base_value");
+        mock_env.setup_env_file(".env.local", "TEST_KEY=local_value");
+
+        unsafe {
+            load_env_vars("development");
+        }
+
+        mock_env.capture_keys(&["TEST_KEY"]);
+
+        assert_eq!(env::var("TEST_KEY").unwrap(), "local_value");
+    }
+
+    #[test]
+    #[serial]
+    fn test_mode_local_env_var_precedence_dev() {
+        let mut mock_env = MockEnv::new();
+
+        mock_env.setup_env_file(".env", "TEST_KEY=base_value");
+        mock_env.setup_env_file(".env.development", "TEST_KEY=development_value");
+        mock_env.setup_env_file(".env.development.local", "TEST_KEY=local_dev_value");
+
+        unsafe {
+            load_env_vars("development");
+        }
+
+        mock_env.capture_keys(&["TEST_KEY"]);
+
+        assert_eq!(env::var("TEST_KEY").unwrap(), "local_dev_value");
+    }
+
+    #[test]
+    #[serial]
+    fn test_mode_local_env_var_precedence_prod() {
+        let mut mock_env = MockEnv::new();
+
+        mock_env.setup_env_file(".env", "TEST_KEY=base_value");
+        mock_env.setup_env_file(".env.production", "TEST_KEY=production_value");
+        mock_env.setup_env_file(".env.production.local", "TEST_KEY=local_prod_value");
+
+        unsafe {
+            load_env_vars("production");
+        }
+
+        mock_env.capture_keys(&["TEST_KEY"]);
+
+        assert_eq!(env::var("TEST_KEY").unwrap(), "local_prod_value");
+    }
+
+    #[test]
+    #[serial]
+    fn test_ignores_files_from_other_mode() {
+        let mut mock_env = MockEnv::new();
+
+        mock_env.setup_env_file(".env.development", "TEST_KEY=development_value");
+        mock_env.setup_env_file(".env.production", "TEST_KEY=production_value");
+
+        unsafe {
+            load_env_vars("production");
+        }
+
+        mock_env.capture_keys(&["TEST_KEY"]);
+
+        assert_eq!(env::var("TEST_KEY").unwrap(), "production_value");
+    }
+
+    #[test]
+    #[serial]
+    fn test_empty_env_file() {
+        let mut mock_env = MockEnv::new();
+
+        mock_env.setup_env_file(".env", "");
+
+        unsafe {
+            load_env_vars("development");
+        }
+
+        assert!(env::var("NON_EXISTENT_KEY").is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn test_malformed_env_entries() {
+        let mut mock_env = MockEnv::new();
+
+        mock_env.setup_env_file(".env", "INVALID_LINE\nMISSING_EQUALS_SIGN");
+        unsafe {
+            load_env_vars("development");
+        }
+
+        mock_env.capture_keys(&["INVALID_LINE", "MISSING_EQUALS_SIGN"]);
+
+        assert!(env::var("INVALID_LINE").is_err());
+        assert!(env::var("MISSING_EQUALS_SIGN").is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn test_quoted_values_parsing() {
+        let mut mock_env = MockEnv::new();
+
+        mock_env.setup_env_file(".env", r#"TEST_KEY="quoted_value""#);
+
+        unsafe {
+            load_env_vars("development");
+        }
+
+        mock_env.capture_keys(&["TEST_KEY"]);
+
+        assert_eq!(env::var("TEST_KEY").unwrap(), "quoted_value");
+    }
+
+    #[test]
+    #[serial]
+    fn test_non_existent_env_file() {
+        let mut mock_env = MockEnv::new();
+        unsafe {
+            load_env_vars("development");
+        }
+
+        mock_env.capture_keys(&["NON_EXISTENT_KEY"]);
+
+        assert!(env::var("NON_EXISTENT_KEY").is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn test_multiple_env_vars() {
+        let mut mock_env = MockEnv::new();
+
+        mock_env.setup_env_file(".env", "KEY1=value1\nKEY2=value2");
+
+        unsafe {
+            load_env_vars("development");
+        }
+
+        mock_env.capture_keys(&["KEY1", "KEY2"]);
+
+        assert_eq!(env::var("KEY1").unwrap(), "value1");
+        assert_eq!(env::var("KEY2").unwrap(), "value2");
+    }
+
+    fn resolve(files: &[(&str, &str)]) -> (HashMap<String, String>, Vec<String>) {
+        resolve_with_system(files, &[])
+    }
+
+    fn resolve_with_system(
+        files: &[(&str, &str)],
+        system: &[(&str, &str)],
+    ) -> (HashMap<String, String>, Vec<String>) {
+        let files = files
+            .iter()
+            .map(|(file, contents)| (file.to_string(), contents.to_string()))
+            .collect::<Vec<(String, String)>>();
+        let system = system
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+
+        let (vars, warnings) = resolve_env_files(&files, &system);
+        (vars.into_iter().collect(), warnings)
+    }
+
+    fn parse(contents: &str) -> HashMap<String, String> {
+        let (vars, warnings) = resolve(&[(".env", contents)]);
+        assert!(warnings.is_empty(), "{warnings:?}");
+        vars
+    }
+
+    #[test]
+    fn test_env_files_precedence_order() {
+        assert_eq!(
+            env_files("development"),
+            vec![
+                ".env",
+                ".env.development",
+                ".env.local",
+                ".env.development.local"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_comments_and_empty_lines() {
+        let vars = parse("# comment\n\n   # indented comment\nKEY=value\n\n");
+
+        assert_eq!(vars.len(), 1);
+        assert_eq!(vars["KEY"], "value");
+    }
+
+    #[test]
+    fn test_parse_export_prefix() {
+        let vars = parse("export KEY=value\nexport\tTAB=tab\nexport=not_a_prefix");
+
+        assert_eq!(vars["KEY"], "value");
+        assert_eq!(vars["TAB"], "tab");
+        assert_eq!(vars["export"], "not_a_prefix");
+    }
+
+    #[test]
+    fn test_parse_unquoted_values() {
+        let vars = parse("KEY =  spaced value  \r\nEMPTY=\nEQUALS=a=b\nHASH=a#b");
+
+        assert_eq!(vars["KEY"], "spaced value");
+        assert_eq!(vars["EMPTY"], "");
+        assert_eq!(vars["EQUALS"], "a=b");
+        assert_eq!(vars["HASH"], "a#b");
+    }
+
+    #[test]
+    fn test_parse_inline_comments() {
+        let vars = parse(
+            "UNQUOTED=value # comment\nEMPTY= # comment\nDOUBLE=\"a # b\" # comment\nSINGLE='a # b'\t# comment",
+        );
+
+        assert_eq!(vars["UNQUOTED"], "value");
+        assert_eq!(vars["EMPTY"], "");
+        assert_eq!(vars["DOUBLE"], "a # b");
+        assert_eq!(vars["SINGLE"], "a # b");
+    }
+
+    #[test]
+    fn test_parse_single_quoted_values() {
+        let vars = parse(r"KEY='  literal $HOME \n ${USER} '");
+
+        assert_eq!(vars["KEY"], r"  literal $HOME \n ${USER} ");
+    }
+
+    #[test]
+    fn test_parse_double_quoted_escapes() {
+        let vars = parse(r#"KEY="line\nnext\ttab \"quoted\" \\ \$HOME \q""#);
+
+        assert_eq!(vars["KEY"], "line\nnext\ttab \"quoted\" \\ $HOME \\q");
+    }
+
+    #[test]
+    fn test_parse_multiline_values() {
+        let vars = parse(
+            "DOUBLE=\"first\nsecond\"\nSINGLE='-----BEGIN KEY-----\nabc\n-----END KEY-----'\nNEXT=next",
+        );
+
+        assert_eq!(vars["DOUBLE"], "first\nsecond");
+        assert_eq!(
+            vars["SINGLE"],
+            "-----BEGIN KEY-----\nabc\n-----END KEY-----"
+        );
+        assert_eq!(vars["NEXT"], "next");
+    }
+
+    #[test]
+    fn test_parse_variable_expansion() {
+        let vars = parse(
+            "HOST=localhost\nPORT=3000\nURL=http://${HOST}:$PORT/api\nQUOTED=\"$HOST\"\nLITERAL=\\$HOST\nPRICE=5$",
+        );
+
+        assert_eq!(vars["URL"], "http://localhost:3000/api");
+        assert_eq!(vars["QUOTED"], "localhost");
+        assert_eq!(vars["LITERAL"], "$HOST");
+        assert_eq!(vars["PRICE"], "5$");
+    }
+
+    #[test]
+    fn test_parse_variable_fallbacks() {
+        let vars = parse(
+            "EMPTY=\nUNSET=${MISSING}\nDASH=${MISSING-fallback}\nCOLON=${EMPTY:-fallback}\nKEEP_EMPTY=${EMPTY-fallback}\nNESTED=${MISSING:-${UNSET:-nested}}",
+        );
+
+        assert_eq!(vars["UNSET"], "");
+        assert_eq!(vars["DASH"], "fallback");
+        assert_eq!(vars["COLON"], "fallback");
+        assert_eq!(vars["KEEP_EMPTY"], "");
+        assert_eq!(vars["NESTED"], "nested");
+    }
+
+    #[test]
+    fn test_expansion_across_files() {
+        let (vars, _) = resolve_with_system(
+            &[
+                (
+                    ".env",
+                    "URL=http://${HOST}:${PORT}\nHOST=localhost\nPORT=3000",
+                ),
+                (".env.local", "PORT=4000\nHOME_DIR=$HOME"),
+            ],
+            &[("HOME", "/home/tuono")],
+        );
+
+        // The references use the values with the highest precedence
+        assert_eq!(vars["URL"], "http://localhost:4000");
+        assert_eq!(vars["PORT"], "4000");
+        // The system env is available to the references
+        assert_eq!(vars["HOME_DIR"], "/home/tuono");
+        assert!(!vars.contains_key("HOME"));
+    }
+
+    #[test]
+    fn test_self_referencing_variables() {
+        let (vars, warnings) = resolve(&[(".env", "A=$B\nB=${A}b")]);
+
+        assert_eq!(vars["A"], "b");
+        assert_eq!(vars["B"], "b");
+        assert_eq!(warnings, vec![".env:1 A references itself"]);
+    }
+
+    #[test]
+    fn test_self_reference_to_the_lower_precedence_definition() {
+        let (vars, warnings) = resolve(&[
+            (".env", "API=https://tuono.dev\nAPI=${API}/api"),
+            (".env.local", "API=${API}/v2\nDOCS=${API}/docs"),
+        ]);
+
+        assert_eq!(vars["API"], "https://tuono.dev/api/v2");
+        assert_eq!(vars["DOCS"], "https://tuono.dev/api/v2/docs");
+        assert!(warnings.is_empty(), "{warnings:?}");
+
+        // Without a previous definition the reference is a cycle
+        let (vars, warnings) = resolve(&[(".env.local", "A=${A}x")]);
+
+        assert_eq!(vars["A"], "x");
+        assert_eq!(warnings, vec![".env.local:1 A references itself"]);
+    }
+
+    #[test]
+    fn test_malformed_entries_warnings() {
+        let (vars, warnings) = resolve(&[(
+            ".env.local",
+            "VALID=1\nMISSING_EQUALS\n1INVALID=1\nBAD KEY=1\nTRAILING=\"a\" b\nBRACE=${OPEN\nNEXT=2\nUNTERMINATED=\"a\nb",
+        )]);
+
+        assert_eq!(vars.len(), 2);
+        assert_eq!(vars["VALID"], "1");
+        assert_eq!(vars["NEXT"], "2");
+        assert_eq!(
+            warnings,
+            vec![
+                ".env.local:2 Missing `=` after MISSING_EQUALS",
+                ".env.local:3 Invalid variable name",
+                ".env.local:4 Missing `=` after BAD",
+                ".env.local:5 Unexpected characters after the value of TRAILING",
+                ".env.local:6 Unterminated `${OPEN` in the value of BRACE",
+                ".env.local:8 Unterminated double quoted value in the value of UNTERMINATED",
+            ]
+        );
+    }
+}
@@ -1,2 +1,3 @@
 pub mod config;
+pub mod env;
 pub mod tuono_println;
@@ -72,3 +72,67 @@ fn should_fail_if_the_file_is_not_json() {
 
     assert_eq!(config.err().unwrap().kind(), ErrorKind::InvalidData);
 }
+
+#[test]
+#[serial]
+fn should_override_the_config_with_the_env_vars() {
+    let folder = TempTuonoProject::new();
+
+    folder.add_file_with_content(
+        "./.tuono/config/config.json",
+        r#"{ "server": {"host": "localhost", "port": 3000}}"#,
+    );
+
+    // SAFETY: the tests reading the environment run serially
+    unsafe { std::env::set_var("TUONO_SERVER__PORT", "8080") };
+    let config = Config::get();
+    unsafe { std::env::remove_var("TUONO_SERVER__PORT") };
+
+    assert_eq!(config.unwrap().server.port, 8080);
+}
+
+#[test]
+#[serial]
+fn should_fail_if_an_env_var_is_invalid() {
+    let folder = TempTuonoProject::new();
+
+    folder.add_file_with_content(
+        "./.tuono/config/config.json",
+        r#"{ "server": {"host": "localhost", "port": 3000}}"#,
+    );
+
+    // SAFETY: the tests reading the environment run serially
+    unsafe { std::env::set_var("TUONO_SERVER__PORT", "http") };
+    let config = Config::get();
+    unsafe { std::env::remove_var("TUONO_SERVER__PORT") };
+
+    let error = config.err().unwrap();
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+    assert_eq!(
+        error.to_string(),
+        "TUONO_SERVER__PORT must be a number, found `http`"
+    );
+}
+
+#[test]
+#[serial]
+fn should_apply_the_env_files_overrides_without_loading_them() {
+    let folder = TempTuonoProject::new();
+
+    folder.add_file_with_content(
+        "./.tuono/config/config.json",
+        r#"{ "server": {"host": "localhost", "port": 3000}}"#,
+    );
+    folder.add_file_with_content("./.env", "TUONO_SERVER__PORT=8080");
+    folder.add_file_with_content("./.env.production", "TUONO_SERVER__HOST=0.0.0.0");
+
+    let config = Config::get_with_env_files("production").unwrap();
+
+    assert_eq!(config.server.port, 8080);
+    assert_eq!(config.server.host, "0.0.0.0");
+    assert!(std::env::var("TUONO_SERVER__PORT").is_err());
+
+    let config = Config::get_with_env_files("development").unwrap();
+
+    assert_eq!(config.server.host, "localhost");
+}
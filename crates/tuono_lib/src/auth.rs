@@ -0,0 +1,190 @@
+use crate::Request;
+use crate::Response;
+use crate::config::GLOBAL_CONFIG;
+use crate::services::path_pattern::{app_path, split_data_route, with_base_path};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Redirect, Response as AxumResponse};
+use futures_util::future::BoxFuture;
+use std::any::Any;
+use std::sync::Arc;
+use tuono_internal::config::AuthConfig;
+
+/// The user resolved by the authentication hook.
+/// Read it with `Request::user`.
+#[derive(Clone)]
+pub(crate) struct AuthenticatedUser(pub Arc<dyn Any + Send + Sync>);
+
+type AuthenticateFn =
+    dyn Fn(Request) -> BoxFuture<'static, Option<AuthenticatedUser>> + Send + Sync;
+
+/// The `authenticate` hook defined in `src/app.rs`
+#[derive(Clone)]
+pub(crate) struct Authenticator(Arc<AuthenticateFn>);
+
+impl Authenticator {
+    pub fn new<F, Fut, U>(authenticate: F) -> Self
+    where
+        F: Fn(Request) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Option<U>> + Send + 'static,
+        U: Send + Sync + 'static,
+    {
+        Authenticator(Arc::new(move |req| {
+            let user = authenticate(req);
+            Box::pin(async move {
+                user.await
+                    .map(|user| AuthenticatedUser(Arc::new(user) as Arc<dyn Any + Send + Sync>))
+            })
+        }))
+    }
+
+    pub async fn authenticate(&self, req: Request) -> Option<AuthenticatedUser> {
+        (self.0)(req).await
+    }
+}
+
+/// The route kinds protected by `auth = "required"`
+#[doc(hidden)]
+pub enum AuthGuard {
+    /// Server side rendered page
+    Page,
+    /// Data fetched by the client side navigation
+    Data,
+    Api,
+}
+
+/// The login url with the path requested by the visitor.
+/// The data routes are replaced by their page.
+fn login_location(config: &AuthConfig, base_path: &str, req: &Request) -> String {
+    let path = req.uri.path();
+    let path = match app_path(base_path, path).map(split_data_route) {
+        Some((true, page)) => with_base_path(base_path, page),
+        _ => path.to_string(),
+    };
+    let path = match req.uri.query() {
+        Some(query) => format!("{path}?{query}"),
+        None => path,
+    };
+
+    let query = serde_urlencoded::to_string([(config.return_param.as_str(), path.as_str())])
+        .unwrap_or_default();
+
+    let separator = if config.login_url.contains('?') {
+        '&'
+    } else {
+        '?'
+    };
+
+    format!("{}{separator}{query}", config.login_url)
+}
+
+/// Returns the response sent instead of the route when the user
+/// is not authenticated.
+#[doc(hidden)]
+pub fn tuono_internal_guard(req: &Request, guard: AuthGuard) -> Option<AxumResponse> {
+    if req.is_authenticated() {
+        return None;
+    }
+
+    let config = GLOBAL_CONFIG
+        .get()
+        .map(|config| config.auth.clone())
+        .unwrap_or_default();
+    let base_path = crate::config::base_path();
+
+    let response = match guard {
+        AuthGuard::Page => {
+            Redirect::temporary(&login_location(&config, base_path, req)).into_response()
+        }
+        AuthGuard::Data => Response::TemporaryRedirect(login_location(&config, base_path, req))
+            .json()
+            .into_response(),
+        AuthGuard::Api => (StatusCode::UNAUTHORIZED, "Unauthorized").into_response(),
+    };
+
+    Some(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::Extensions;
+    use axum::http::header::LOCATION;
+    use std::collections::HashMap;
+
+    fn request(uri: &str) -> Request {
+        Request::new(
+            uri.parse().unwrap(),
+            Default::default(),
+            HashMap::new(),
+            None,
+        )
+    }
+
+    #[tokio::test]
+    async fn it_exposes_the_resolved_user() {
+        let authenticator = Authenticator::new(|req: Request| async move {
+            req.headers
+                .get("authorization")
+                .map(|value| value.to_str().unwrap().to_string())
+        });
+
+        let mut req = request("/");
+        req.headers
+            .insert("authorization", "Bearer token".parse().unwrap());
+
+        let mut extensions = Extensions::new();
+        if let Some(user) = authenticator.authenticate(req.clone()).await {
+            extensions.insert(user);
+        }
+        let req = req.with_extensions(extensions);
+
+        assert!(req.is_authenticated());
+        assert_eq!(req.user::<String>(), Some("Bearer token".to_string()));
+        assert_eq!(req.user::<u32>(), None);
+
+        assert!(authenticator.authenticate(request("/")).await.is_none());
+    }
+
+    #[test]
+    fn it_redirects_the_pages_to_the_login() {
+        let response = tuono_internal_guard(&request("/posts?page=2"), AuthGuard::Page).unwrap();
+
+        assert_eq!(response.status(), StatusCode::TEMPORARY_REDIRECT);
+        assert_eq!(
+            response.headers()[LOCATION],
+            "/login?redirect=%2Fposts%3Fpage%3D2"
+        );
+
+        let response =
+            tuono_internal_guard(&request("/__tuono/data/posts"), AuthGuard::Data).unwrap();
+        assert_eq!(response.status(), StatusCode::TEMPORARY_REDIRECT);
+    }
+
+    #[test]
+    fn it_rejects_the_api_requests() {
+        let response = tuono_internal_guard(&request("/api/posts"), AuthGuard::Api).unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn it_appends_the_return_path_to_the_login_query() {
+        let config = AuthConfig {
+            login_url: "/auth?provider=github".to_string(),
+            ..AuthConfig::default()
+        };
+
+        assert_eq!(
+            login_location(&config, "", &request("/__tuono/data/posts")),
+            "/auth?provider=github&redirect=%2Fposts"
+        );
+        assert_eq!(
+            login_location(
+                &config,
+                "/shop",
+                &request("/shop/__tuono/data/posts?page=2")
+            ),
+            "/auth?provider=github&redirect=%2Fshop%2Fposts%3Fpage%3D2"
+        );
+    }
+}
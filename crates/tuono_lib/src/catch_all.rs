@@ -10,7 +10,8 @@ pub async fn catch_all(
     let pathname = request.uri();
     let headers = request.headers();
 
-    let req = crate::Request::new(pathname.to_owned(), headers.to_owned(), params, None);
+    let req = crate::Request::new(pathname.to_owned(), headers.to_owned(), params, None)
+        .with_extensions(request.extensions().to_owned());
 
     // TODO: remove unwrap
     let payload = Payload::new(&req, &"").client_payload().unwrap();
@@ -2,3 +2,19 @@ use once_cell::sync::OnceCell;
 use tuono_internal::config::Config;
 
 pub static GLOBAL_CONFIG: OnceCell<Config> = OnceCell::new();
+
+/// The application base path (empty when the app is served from the root)
+pub(crate) fn base_path() -> &'static str {
+    GLOBAL_CONFIG
+        .get()
+        .map(|config| config.base_path.as_str())
+        .unwrap_or_default()
+}
+
+/// Number of reverse proxies whose forwarded headers identify the client
+pub(crate) fn trusted_proxies() -> usize {
+    GLOBAL_CONFIG
+        .get()
+        .map(|config| config.limits.trusted_proxies)
+        .unwrap_or_default()
+}
@@ -0,0 +1,95 @@
+use crate::mode::Mode;
+use axum_extra::extract::cookie::{Cookie, Key};
+use colored::Colorize;
+use once_cell::sync::OnceCell;
+use std::env;
+use tuono_internal::tuono_println;
+
+/// The env variable containing the secret used to sign
+/// and encrypt the cookies. It must be at least 64 bytes long.
+pub const COOKIE_SECRET_ENV_NAME: &str = "TUONO_COOKIE_SECRET";
+
+static COOKIE_KEY: OnceCell<Key> = OnceCell::new();
+
+/// Load the signed and private cookies key from the env.
+///
+/// Must be called after the `.env` files have been loaded.
+/// When the secret is missing a random key is generated hence the
+/// cookies can't be read anymore after a server restart.
+pub fn load_cookie_key(mode: Mode) {
+    let key = match env::var(COOKIE_SECRET_ENV_NAME) {
+        Ok(secret) => Key::try_from(secret.as_bytes()).unwrap_or_else(|_| {
+            panic!("[SERVER] {COOKIE_SECRET_ENV_NAME} must be at least 64 bytes long")
+        }),
+        Err(_) => {
+            if mode == Mode::Prod {
+                tuono_println!(
+                    "{} is not defined: signed and private cookies won't survive a server restart",
+                    COOKIE_SECRET_ENV_NAME.yellow()
+                );
+            }
+            Key::generate()
+        }
+    };
+
+    let _ = COOKIE_KEY.set(key);
+}
+
+pub(crate) fn cookie_key() -> &'static Key {
+    COOKIE_KEY.get_or_init(Key::generate)
+}
+
+/// Sign the cookie value so that it can't be tampered by the client
+pub(crate) fn sign_cookie(cookie: Cookie<'static>) -> Cookie<'static> {
+    let name = cookie.name().to_string();
+    let mut jar = cookie::CookieJar::new();
+    jar.signed_mut(cookie_key()).add(cookie);
+    jar.get(&name)
+        .cloned()
+        .expect("The signed cookie was just added")
+}
+
+/// Encrypt the cookie value so that it can't be read or tampered by the client
+pub(crate) fn encrypt_cookie(cookie: Cookie<'static>) -> Cookie<'static> {
+    let name = cookie.name().to_string();
+    let mut jar = cookie::CookieJar::new();
+    jar.private_mut(cookie_key()).add(cookie);
+    jar.get(&name)
+        .cloned()
+        .expect("The private cookie was just added")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_signs_the_cookie_value() {
+        let signed = sign_cookie(Cookie::new("session", "value"));
+
+        assert_eq!(signed.name(), "session");
+        assert_ne!(signed.value(), "value");
+        assert!(signed.value().ends_with("value"));
+
+        let mut jar = cookie::CookieJar::new();
+        jar.add_original(signed);
+        assert_eq!(
+            jar.signed(cookie_key()).get("session").unwrap().value(),
+            "value"
+        );
+    }
+
+    #[test]
+    fn it_encrypts_the_cookie_value() {
+        let encrypted = encrypt_cookie(Cookie::new("session", "value"));
+
+        assert!(!encrypted.value().contains("value"));
+
+        let mut jar = cookie::CookieJar::new();
+        jar.add_original(encrypted);
+        assert_eq!(
+            jar.private(cookie_key()).get("session").unwrap().value(),
+            "value"
+        );
+    }
+}
@@ -0,0 +1,219 @@
+const MINUTES_PER_DAY: u64 = 24 * 60;
+/// Leap years included, any valid expression matches within this window
+const SEARCH_WINDOW_MINUTES: u64 = 5 * 366 * MINUTES_PER_DAY;
+
+/// The values allowed by a cron field, one bit per value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Field {
+    bits: u64,
+    /// The field is `*` (used by the day of month/day of week rule)
+    any: bool,
+}
+
+impl Field {
+    fn parse(name: &str, value: &str, min: u32, max: u32) -> Result<Self, String> {
+        let invalid = || format!("Invalid {name} field `{value}`");
+        let mut bits = 0u64;
+
+        for part in value.split(',') {
+            let (range, step) = match part.split_once('/') {
+                Some((range, step)) => (range, step.parse::<u32>().map_err(|_| invalid())?),
+                None => (part, 1),
+            };
+
+            if step == 0 {
+                return Err(invalid());
+            }
+
+            let (start, end) = if range == "*" {
+                (min, max)
+            } else if let Some((start, end)) = range.split_once('-') {
+                (
+                    start.parse::<u32>().map_err(|_| invalid())?,
+                    end.parse::<u32>().map_err(|_| invalid())?,
+                )
+            } else {
+                let start = range.parse::<u32>().map_err(|_| invalid())?;
+                // `5/15` means from 5 to the end of the range
+                (start, if part.contains('/') { max } else { start })
+            };
+
+            if start < min || end > max || start > end {
+                return Err(invalid());
+            }
+
+            for value in (start..=end).step_by(step as usize) {
+                bits |= 1 << value;
+            }
+        }
+
+        Ok(Field {
+            bits,
+            any: value == "*",
+        })
+    }
+
+    fn contains(&self, value: u64) -> bool {
+        self.bits & (1 << value) != 0
+    }
+}
+
+/// A standard five fields cron expression
+/// (`minute hour day-of-month month day-of-week`) evaluated in UTC.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Cron {
+    minutes: Field,
+    hours: Field,
+    days_of_month: Field,
+    months: Field,
+    days_of_week: Field,
+}
+
+/// Convert the days since the unix epoch to the (year, month, day) date.
+/// See http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+
+    (year, month, day)
+}
+
+impl Cron {
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+
+        let [minutes, hours, days_of_month, months, days_of_week] = fields[..] else {
+            return Err(format!(
+                "Expected 5 fields in the cron expression `{expression}`"
+            ));
+        };
+
+        let mut days_of_week = Field::parse("day of week", days_of_week, 0, 7)?;
+        // Both 0 and 7 are Sunday
+        if days_of_week.contains(7) {
+            days_of_week.bits |= 1;
+        }
+
+        Ok(Cron {
+            minutes: Field::parse("minute", minutes, 0, 59)?,
+            hours: Field::parse("hour", hours, 0, 23)?,
+            days_of_month: Field::parse("day of month", days_of_month, 1, 31)?,
+            months: Field::parse("month", months, 1, 12)?,
+            days_of_week,
+        })
+    }
+
+    fn matches_day(&self, day_of_month: u64, day_of_week: u64) -> bool {
+        let day_of_month = self.days_of_month.contains(day_of_month);
+        let day_of_week = self.days_of_week.contains(day_of_week);
+
+        // When both fields are restricted the day matches either of them
+        match (self.days_of_month.any, self.days_of_week.any) {
+            (false, false) => day_of_month || day_of_week,
+            _ => day_of_month && day_of_week,
+        }
+    }
+
+    /// The first matching time after `timestamp` (seconds since the unix epoch).
+    /// `None` when the expression never matches (e.g. `0 0 30 2 *`).
+    pub fn next_after(&self, timestamp: u64) -> Option<u64> {
+        let mut minute = timestamp / 60 + 1;
+        let limit = minute + SEARCH_WINDOW_MINUTES;
+
+        while minute < limit {
+            let days = minute / MINUTES_PER_DAY;
+            let (_, month, day_of_month) = civil_from_days(days);
+            // The unix epoch was a Thursday
+            let day_of_week = (days + 4) % 7;
+
+            if !self.months.contains(month) || !self.matches_day(day_of_month, day_of_week) {
+                minute = (days + 1) * MINUTES_PER_DAY;
+                continue;
+            }
+
+            if !self.hours.contains(minute % MINUTES_PER_DAY / 60) {
+                minute = (minute / 60 + 1) * 60;
+                continue;
+            }
+
+            if self.minutes.contains(minute % 60) {
+                return Some(minute * 60);
+            }
+
+            minute += 1;
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2025-01-15T10:30:20Z, a Wednesday
+    const NOW: u64 = 1_736_937_020;
+
+    fn next(expression: &str) -> Option<u64> {
+        Cron::parse(expression).unwrap().next_after(NOW)
+    }
+
+    #[test]
+    fn it_converts_the_days_to_dates() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(NOW / 86_400), (2025, 1, 15));
+        assert_eq!(civil_from_days(19_782), (2024, 2, 29));
+    }
+
+    #[test]
+    fn it_finds_the_next_matching_time() {
+        let cases = [
+            // 2025-01-15T10:31:00Z
+            ("* * * * *", 1_736_937_060),
+            // 2025-01-15T10:45:00Z
+            ("*/15 * * * *", 1_736_937_900),
+            // 2025-01-16T03:00:00Z
+            ("0 3 * * *", 1_736_996_400),
+            // 2025-01-19T00:00:00Z, Sunday
+            ("0 0 * * 7", 1_737_244_800),
+            // 2025-02-01T00:00:00Z
+            ("0 0 1 * *", 1_738_368_000),
+            // 2025-01-17T09:00:00Z, the 1st of February is later
+            ("0 9 1 * 5", 1_737_104_400),
+            // 2028-02-29T12:00:00Z
+            ("0 12 29 2 *", 1_835_438_400),
+        ];
+
+        for (expression, expected) in cases {
+            assert_eq!(next(expression), Some(expected), "{expression}");
+        }
+    }
+
+    #[test]
+    fn it_never_matches_the_impossible_dates() {
+        assert_eq!(next("0 0 30 2 *"), None);
+    }
+
+    #[test]
+    fn it_rejects_the_invalid_expressions() {
+        for expression in [
+            "* * * *",
+            "60 * * * *",
+            "* 24 * * *",
+            "* * 0 * *",
+            "* * * 13 *",
+            "*/0 * * * *",
+            "5-1 * * * *",
+            "@daily",
+        ] {
+            assert!(Cron::parse(expression).is_err(), "{expression}");
+        }
+    }
+}
@@ -0,0 +1,227 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+
+/// A `<meta>` tag identified either by `name` or by `property` (Open Graph)
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Meta {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    property: Option<String>,
+    content: String,
+}
+
+/// The document head metadata defined by the route handler.
+///
+/// It is injected in the server side rendered HTML and
+/// applied by the client after each client side navigation.
+///
+/// ```ignore
+/// let mut props = Props::new(post);
+/// props.head(
+///     Head::new()
+///         .title(&post.title)
+///         .description(&post.summary)
+///         .canonical(format!("https://tuono.dev/blog/{}", post.slug))
+///         .open_graph("image", &post.cover),
+/// );
+/// ```
+#[derive(Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct Head {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    canonical: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    meta: Vec<Meta>,
+}
+
+static TITLE_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?is)<title[^>]*>.*?</title>").expect("Invalid title regex"));
+
+static DESCRIPTION_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?is)<meta[^>]*name=["']description["'][^>]*/?>"#)
+        .expect("Invalid meta description regex")
+});
+
+static CANONICAL_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?is)<link[^>]*rel=["']canonical["'][^>]*/?>"#)
+        .expect("Invalid canonical link regex")
+});
+
+fn escape_html(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#x27;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+impl Head {
+    pub fn new() -> Self {
+        Head::default()
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn canonical(mut self, url: impl Into<String>) -> Self {
+        self.canonical = Some(url.into());
+        self
+    }
+
+    /// Add a `<meta name="..." content="...">` tag
+    pub fn meta(mut self, name: impl Into<String>, content: impl Into<String>) -> Self {
+        self.meta.push(Meta {
+            name: Some(name.into()),
+            property: None,
+            content: content.into(),
+        });
+        self
+    }
+
+    /// Add an Open Graph `<meta property="og:..." content="...">` tag.
+    /// The `og:` prefix is added when missing.
+    pub fn open_graph(mut self, property: impl Into<String>, content: impl Into<String>) -> Self {
+        let property = property.into();
+        let property = if property.starts_with("og:") {
+            property
+        } else {
+            format!("og:{property}")
+        };
+
+        self.meta.push(Meta {
+            name: None,
+            property: Some(property),
+            content: content.into(),
+        });
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self == &Head::default()
+    }
+
+    fn to_html(&self) -> String {
+        let mut html = String::new();
+
+        if let Some(title) = &self.title {
+            html.push_str(&format!("<title>{}</title>", escape_html(title)));
+        }
+        if let Some(description) = &self.description {
+            html.push_str(&format!(
+                "<meta name=\"description\" content=\"{}\"/>",
+                escape_html(description)
+            ));
+        }
+        if let Some(canonical) = &self.canonical {
+            html.push_str(&format!(
+                "<link rel=\"canonical\" href=\"{}\"/>",
+                escape_html(canonical)
+            ));
+        }
+        for meta in &self.meta {
+            let (attribute, key) = match (&meta.name, &meta.property) {
+                (Some(name), _) => ("name", name),
+                (None, Some(property)) => ("property", property),
+                (None, None) => continue,
+            };
+            html.push_str(&format!(
+                "<meta {attribute}=\"{}\" content=\"{}\"/>",
+                escape_html(key),
+                escape_html(&meta.content)
+            ));
+        }
+
+        html
+    }
+
+    /// Inject the metadata at the end of the document `<head>`.
+    ///
+    /// The title, description and canonical tags rendered by react
+    /// are replaced since browsers and crawlers only read the first one.
+    pub(crate) fn inject(&self, html: String) -> String {
+        if self.is_empty() {
+            return html;
+        }
+
+        let Some(head_end) = html.find("</head>") else {
+            return html;
+        };
+
+        let (head, body) = html.split_at(head_end);
+        let mut head = head.to_string();
+
+        if self.title.is_some() {
+            head = TITLE_RE.replace_all(&head, "").to_string();
+        }
+        if self.description.is_some() {
+            head = DESCRIPTION_RE.replace_all(&head, "").to_string();
+        }
+        if self.canonical.is_some() {
+            head = CANONICAL_RE.replace_all(&head, "").to_string();
+        }
+
+        format!("{head}{}{body}", self.to_html())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HTML: &str = "<!DOCTYPE html><html><head><meta charSet=\"UTF-8\"/><title>Tuono</title><meta name=\"description\" content=\"Default\"/></head><body><title>Not in head</title></body></html>";
+
+    #[test]
+    fn should_not_change_the_html_without_metadata() {
+        assert_eq!(Head::new().inject(HTML.to_string()), HTML);
+    }
+
+    #[test]
+    fn should_replace_the_react_title_and_description() {
+        let head = Head::new()
+            .title("My <post>")
+            .description("A \"quoted\" summary")
+            .canonical("https://tuono.dev/blog/post")
+            .open_graph("title", "My post")
+            .meta("twitter:card", "summary");
+
+        assert_eq!(
+            head.inject(HTML.to_string()),
+            "<!DOCTYPE html><html><head><meta charSet=\"UTF-8\"/>\
+            <title>My &lt;post&gt;</title>\
+            <meta name=\"description\" content=\"A &quot;quoted&quot; summary\"/>\
+            <link rel=\"canonical\" href=\"https://tuono.dev/blog/post\"/>\
+            <meta property=\"og:title\" content=\"My post\"/>\
+            <meta name=\"twitter:card\" content=\"summary\"/>\
+            </head><body><title>Not in head</title></body></html>"
+        );
+    }
+
+    #[test]
+    fn should_serialize_only_the_defined_fields() {
+        let head = Head::new().title("Title").open_graph("og:type", "article");
+
+        assert_eq!(
+            serde_json::to_string(&head).unwrap(),
+            "{\"title\":\"Title\",\"meta\":[{\"property\":\"og:type\",\"content\":\"article\"}]}"
+        );
+    }
+}
@@ -0,0 +1,287 @@
+use crate::manifest::MANIFEST;
+use crate::mode::Mode;
+use crate::ssr::Js;
+use axum::Json;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response as AxumResponse};
+use axum::routing::{Router, get};
+use futures_util::future::{BoxFuture, join_all};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
+use tuono_internal::config::HealthConfig;
+
+type CheckFn = dyn Fn() -> BoxFuture<'static, Result<(), String>> + Send + Sync;
+
+/// The readiness checks registered with the `health_checks` function
+/// of `src/app.rs`. It receives the `ApplicationState` when `main` is defined:
+///
+/// ```ignore
+/// pub fn health_checks(state: &ApplicationState) -> HealthChecks {
+///     let db = state.db.clone();
+///
+///     HealthChecks::new().check("database", move || {
+///         let db = db.clone();
+///         async move { db.ping().await }
+///     })
+/// }
+/// ```
+#[derive(Clone, Default)]
+pub struct HealthChecks {
+    checks: Vec<(String, Arc<CheckFn>)>,
+}
+
+impl HealthChecks {
+    pub fn new() -> Self {
+        HealthChecks::default()
+    }
+
+    /// The server is not ready while `check` fails
+    pub fn check<F, Fut, E>(mut self, name: &str, check: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+        E: Display,
+    {
+        self.checks.push((
+            name.to_string(),
+            Arc::new(move || {
+                let result = check();
+                Box::pin(async move { result.await.map_err(|err| err.to_string()) })
+            }),
+        ));
+        self
+    }
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum CheckStatus {
+    Ok,
+    Error,
+}
+
+#[derive(Serialize, Debug)]
+struct CheckReport {
+    status: CheckStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    duration_ms: u128,
+}
+
+impl CheckReport {
+    fn new(result: Result<(), String>, start: Instant) -> Self {
+        CheckReport {
+            status: if result.is_ok() {
+                CheckStatus::Ok
+            } else {
+                CheckStatus::Error
+            },
+            error: result.err(),
+            duration_ms: start.elapsed().as_millis(),
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "lowercase")]
+enum ReadinessStatus {
+    Ready,
+    Unavailable,
+}
+
+#[derive(Serialize, Debug)]
+struct ReadinessReport {
+    status: ReadinessStatus,
+    checks: BTreeMap<String, CheckReport>,
+}
+
+/// The liveness and readiness endpoints
+#[derive(Clone)]
+pub(crate) struct Health {
+    config: HealthConfig,
+    mode: Mode,
+    checks: HealthChecks,
+}
+
+impl std::fmt::Debug for Health {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Health")
+            .field("config", &self.config)
+            .field(
+                "checks",
+                &self
+                    .checks
+                    .checks
+                    .iter()
+                    .map(|(name, _)| name)
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl Health {
+    pub fn new(config: &HealthConfig, mode: Mode) -> Self {
+        Health {
+            config: config.clone(),
+            mode,
+            checks: HealthChecks::default(),
+        }
+    }
+
+    pub fn with_checks(&mut self, checks: HealthChecks) {
+        self.checks = checks;
+    }
+
+    /// Add the endpoints to the router, outside of the route layers
+    /// so that the probes are never limited or logged.
+    pub fn routes(self, router: Router) -> Router {
+        if !self.config.enabled {
+            return router;
+        }
+
+        let liveness_path = self.config.liveness_path.clone();
+        let readiness_path = self.config.readiness_path.clone();
+
+        router.route(&liveness_path, get(liveness)).route(
+            &readiness_path,
+            get(move || {
+                let health = self.clone();
+                async move { health.readiness().await }
+            }),
+        )
+    }
+
+    /// The checks that don't depend on the application
+    fn builtin_checks(&self) -> Vec<(&'static str, Result<(), String>)> {
+        let mut checks = vec![(
+            "ssr",
+            if Js::is_bundle_available(self.mode) {
+                Ok(())
+            } else {
+                Err("The server bundle is not available".to_string())
+            },
+        )];
+
+        // The manifest is only loaded by the production server
+        if self.mode == Mode::Prod {
+            checks.push((
+                "manifest",
+                MANIFEST
+                    .get()
+                    .map(|_| ())
+                    .ok_or_else(|| "The Vite manifest is not loaded".to_string()),
+            ));
+        }
+
+        checks
+    }
+
+    async fn readiness(&self) -> AxumResponse {
+        let timeout = Duration::from_secs(self.config.timeout);
+        let start = Instant::now();
+
+        let mut checks: BTreeMap<String, CheckReport> = self
+            .builtin_checks()
+            .into_iter()
+            .map(|(name, result)| (name.to_string(), CheckReport::new(result, start)))
+            .collect();
+
+        let user_checks = self.checks.checks.iter().map(|(name, check)| async move {
+            let start = Instant::now();
+            let result = match tokio::time::timeout(timeout, check()).await {
+                Ok(result) => result,
+                Err(_) => Err(format!("Timed out after {}s", timeout.as_secs())),
+            };
+            (name.clone(), CheckReport::new(result, start))
+        });
+
+        checks.extend(join_all(user_checks).await);
+
+        readiness_response(checks)
+    }
+}
+
+fn readiness_response(checks: BTreeMap<String, CheckReport>) -> AxumResponse {
+    let is_ready = checks.values().all(|check| check.status == CheckStatus::Ok);
+
+    let (status_code, status) = if is_ready {
+        (StatusCode::OK, ReadinessStatus::Ready)
+    } else {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            ReadinessStatus::Unavailable,
+        )
+    };
+
+    (status_code, Json(ReadinessReport { status, checks })).into_response()
+}
+
+async fn liveness() -> impl IntoResponse {
+    Json(serde_json::json!({ "status": "ok" }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn body(response: AxumResponse) -> serde_json::Value {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_reports_the_failing_checks() {
+        let mut health = Health::new(&HealthConfig::default(), Mode::Dev);
+        health.with_checks(
+            HealthChecks::new()
+                .check("cache", || async { Ok::<(), String>(()) })
+                .check("database", || async { Err("Connection refused") }),
+        );
+
+        let response = health.readiness().await;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let report = body(response).await;
+        assert_eq!(report["status"], "unavailable");
+        assert_eq!(report["checks"]["cache"]["status"], "ok");
+        assert_eq!(report["checks"]["database"]["status"], "error");
+        assert_eq!(report["checks"]["database"]["error"], "Connection refused");
+        assert!(report["checks"]["ssr"].is_object());
+        assert!(report["checks"].get("manifest").is_none());
+    }
+
+    #[tokio::test]
+    async fn it_fails_the_checks_exceeding_the_timeout() {
+        let config = HealthConfig {
+            timeout: 0,
+            ..HealthConfig::default()
+        };
+        let mut health = Health::new(&config, Mode::Dev);
+        health.with_checks(HealthChecks::new().check("slow", || async {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            Ok::<(), String>(())
+        }));
+
+        let report = body(health.readiness().await).await;
+        assert_eq!(report["checks"]["slow"]["error"], "Timed out after 0s");
+    }
+
+    #[tokio::test]
+    async fn it_is_ready_when_all_the_checks_pass() {
+        let checks = BTreeMap::from([(
+            "database".to_string(),
+            CheckReport::new(Ok(()), Instant::now()),
+        )]);
+
+        let response = readiness_response(checks);
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body(response).await["status"], "ready");
+    }
+}
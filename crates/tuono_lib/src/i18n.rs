@@ -0,0 +1,129 @@
+use axum::http::Uri;
+use serde::Serialize;
+use tuono_internal::config::I18nConfig;
+
+/// The locale resolved for the request
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Locale {
+    /// The locale code (e.g. `it`)
+    pub code: String,
+    /// The path prefix the locale was requested with (e.g. `/it`).
+    /// Empty for the default locale and the domain locales.
+    #[serde(rename(serialize = "pathPrefix"))]
+    pub path_prefix: String,
+}
+
+/// The URI requested by the client, including the locale prefix
+/// removed before the route matching.
+#[derive(Debug, Clone)]
+pub(crate) struct LocalizedUri(pub Uri);
+
+/// The language tags of the `Accept-Language` header sorted by preference
+fn accepted_languages(header: &str) -> Vec<&str> {
+    let mut languages = header
+        .split(',')
+        .filter_map(|entry| {
+            let mut params = entry.split(';');
+            let tag = params.next()?.trim();
+
+            let quality = params
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .map_or(1.0, |quality| quality.trim().parse::<f32>().unwrap_or(0.0));
+
+            (!tag.is_empty() && quality > 0.0).then_some((tag, quality))
+        })
+        .collect::<Vec<(&str, f32)>>();
+
+    // The stable sort keeps the header order for the same quality
+    languages.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    languages.into_iter().map(|(tag, _)| tag).collect()
+}
+
+/// The primary language subtag (e.g. `it` for `it-CH`)
+fn primary_language(tag: &str) -> &str {
+    tag.split(['-', '_']).next().unwrap_or(tag)
+}
+
+/// The supported locale matching the language `tag`:
+/// the same tag first, then the same primary language.
+fn match_locale<'a>(locales: &'a [String], tag: &str) -> Option<&'a str> {
+    locales
+        .iter()
+        .find(|locale| locale.eq_ignore_ascii_case(tag))
+        .or_else(|| {
+            locales
+                .iter()
+                .find(|locale| primary_language(locale).eq_ignore_ascii_case(primary_language(tag)))
+        })
+        .map(String::as_str)
+}
+
+/// The locale preferred by the client: the one stored in the locale
+/// cookie, then the best match of the `Accept-Language` header.
+/// Defaults to the default locale.
+pub(crate) fn negotiate<'a>(
+    config: &'a I18nConfig,
+    cookie: Option<&str>,
+    accept_language: Option<&str>,
+) -> Option<&'a str> {
+    if let Some(locale) = cookie.and_then(|cookie| {
+        config
+            .locales
+            .iter()
+            .find(|locale| locale.as_str() == cookie)
+    }) {
+        return Some(locale);
+    }
+
+    accept_language
+        .map(accepted_languages)
+        .unwrap_or_default()
+        .into_iter()
+        .find_map(|tag| match_locale(&config.locales, tag))
+        .or_else(|| config.default_locale())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> I18nConfig {
+        I18nConfig {
+            locales: vec!["en-US".to_string(), "it".to_string(), "fr".to_string()],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn it_sorts_the_accepted_languages() {
+        assert_eq!(
+            accepted_languages("fr;q=0.5, it-CH, en;q=0.8, de;q=0"),
+            vec!["it-CH", "en", "fr"]
+        );
+        assert_eq!(accepted_languages("it, fr"), vec!["it", "fr"]);
+        assert!(accepted_languages("").is_empty());
+    }
+
+    #[test]
+    fn it_matches_the_primary_language() {
+        let config = config();
+
+        assert_eq!(match_locale(&config.locales, "IT"), Some("it"));
+        assert_eq!(match_locale(&config.locales, "it-CH"), Some("it"));
+        assert_eq!(match_locale(&config.locales, "en"), Some("en-US"));
+        assert_eq!(match_locale(&config.locales, "de"), None);
+    }
+
+    #[test]
+    fn it_negotiates_the_locale() {
+        let config = config();
+
+        assert_eq!(negotiate(&config, None, Some("de, fr;q=0.9")), Some("fr"));
+        assert_eq!(negotiate(&config, Some("it"), Some("fr")), Some("it"));
+        // Unsupported cookie values are ignored
+        assert_eq!(negotiate(&config, Some("de"), Some("fr")), Some("fr"));
+        assert_eq!(negotiate(&config, None, Some("de")), Some("en-US"));
+        assert_eq!(negotiate(&config, None, None), Some("en-US"));
+    }
+}
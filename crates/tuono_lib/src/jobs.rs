@@ -0,0 +1,400 @@
+use crate::cron::Cron;
+use futures_util::future::BoxFuture;
+use std::fmt::Display;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::task::{JoinError, JoinSet};
+use tokio::time::{Instant, Interval, MissedTickBehavior};
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, warn};
+
+type TaskFn = dyn Fn() -> BoxFuture<'static, Result<(), String>> + Send + Sync;
+type WorkerFn = Box<dyn FnOnce(Shutdown) -> BoxFuture<'static, Result<(), String>> + Send>;
+
+enum Schedule {
+    Every(Duration),
+    Cron(Cron),
+}
+
+struct ScheduledJob {
+    name: String,
+    schedule: Schedule,
+    task: Arc<TaskFn>,
+}
+
+struct Worker {
+    name: String,
+    task: WorkerFn,
+}
+
+/// Notifies the workers that the server is shutting down
+#[derive(Clone)]
+pub struct Shutdown(CancellationToken);
+
+impl Shutdown {
+    /// Resolves once the server started the graceful shutdown
+    pub async fn requested(&self) {
+        self.0.cancelled().await
+    }
+
+    pub fn is_requested(&self) -> bool {
+        self.0.is_cancelled()
+    }
+}
+
+/// The background jobs started by the server alongside the HTTP listener.
+///
+/// Define them with the `jobs` function of `src/app.rs`. It receives
+/// the `ApplicationState` when `main` is defined:
+///
+/// ```ignore
+/// pub fn jobs(state: &ApplicationState) -> Jobs {
+///     let cache = state.cache.clone();
+///
+///     Jobs::new()
+///         .every("refresh_cache", Duration::from_secs(300), move || {
+///             let cache = cache.clone();
+///             async move { cache.refresh().await }
+///         })
+///         .cron("cleanup", "0 3 * * *", || async { cleanup().await })
+/// }
+/// ```
+///
+/// The runs of a job never overlap. A failing or panicking run is logged
+/// and doesn't stop the next ones. On shutdown no new run is started and
+/// the running ones are awaited for a few seconds.
+///
+/// The runs are reported as `tracing` events with the `job`, `duration_ms`
+/// and `error` fields.
+#[derive(Default)]
+pub struct Jobs {
+    scheduled: Vec<ScheduledJob>,
+    workers: Vec<Worker>,
+}
+
+impl std::fmt::Debug for Jobs {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Jobs")
+            .field(
+                "scheduled",
+                &self
+                    .scheduled
+                    .iter()
+                    .map(|job| &job.name)
+                    .collect::<Vec<_>>(),
+            )
+            .field(
+                "workers",
+                &self.workers.iter().map(|job| &job.name).collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+fn task_fn<F, Fut, E>(task: F) -> Arc<TaskFn>
+where
+    F: Fn() -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<(), E>> + Send + 'static,
+    E: Display,
+{
+    Arc::new(move || {
+        let run = task();
+        Box::pin(async move { run.await.map_err(|err| err.to_string()) })
+    })
+}
+
+impl Jobs {
+    pub fn new() -> Self {
+        Jobs::default()
+    }
+
+    /// Run `task` when the server starts and then every `interval`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `interval` is zero.
+    pub fn every<F, Fut, E>(mut self, name: &str, interval: Duration, task: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+        E: Display,
+    {
+        if interval.is_zero() {
+            panic!("Failed to schedule the job {name}: the interval must be greater than zero");
+        }
+
+        self.scheduled.push(ScheduledJob {
+            name: name.to_string(),
+            schedule: Schedule::Every(interval),
+            task: task_fn(task),
+        });
+        self
+    }
+
+    /// Run `task` on the cron schedule
+    /// (`minute hour day-of-month month day-of-week`, in UTC).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `expression` is not a valid cron expression.
+    pub fn cron<F, Fut, E>(mut self, name: &str, expression: &str, task: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+        E: Display,
+    {
+        let cron = Cron::parse(expression)
+            .unwrap_or_else(|err| panic!("Failed to schedule the job {name}: {err}"));
+
+        self.scheduled.push(ScheduledJob {
+            name: name.to_string(),
+            schedule: Schedule::Cron(cron),
+            task: task_fn(task),
+        });
+        self
+    }
+
+    /// Run a long-lived `task` (e.g. a queue consumer) for the whole
+    /// server lifetime. It should return once the shutdown is requested.
+    pub fn worker<F, Fut, E>(mut self, name: &str, task: F) -> Self
+    where
+        F: FnOnce(Shutdown) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+        E: Display,
+    {
+        self.workers.push(Worker {
+            name: name.to_string(),
+            task: Box::new(move |shutdown| {
+                let run = task(shutdown);
+                Box::pin(async move { run.await.map_err(|err| err.to_string()) })
+            }),
+        });
+        self
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.scheduled.is_empty() && self.workers.is_empty()
+    }
+
+    pub(crate) fn start(self, shutdown: &CancellationToken) -> RunningJobs {
+        let mut tasks = JoinSet::new();
+
+        for job in self.scheduled {
+            tasks.spawn(run_scheduled(job, shutdown.clone()));
+        }
+
+        for worker in self.workers {
+            let shutdown = Shutdown(shutdown.clone());
+            tasks.spawn(async move {
+                let start = Instant::now();
+                let result = spawn_run((worker.task)(shutdown)).await;
+                log_run(&worker.name, start, result);
+            });
+        }
+
+        RunningJobs(tasks)
+    }
+}
+
+/// Run a job in its own task so that a panic doesn't stop the schedule.
+/// The run is aborted as well when the returned future is dropped
+/// (i.e. the job is aborted on shutdown).
+async fn spawn_run(
+    run: BoxFuture<'static, Result<(), String>>,
+) -> Result<Result<(), String>, JoinError> {
+    let mut runs = JoinSet::new();
+    runs.spawn(run);
+    runs.join_next()
+        .await
+        .expect("The job run was spawned in the set")
+}
+
+fn log_run(name: &str, start: Instant, result: Result<Result<(), String>, JoinError>) {
+    let duration_ms = start.elapsed().as_millis() as u64;
+
+    match result {
+        Ok(Ok(())) => info!(job = name, duration_ms, "Job done"),
+        Ok(Err(err)) => error!(job = name, duration_ms, error = %err, "Job failed"),
+        Err(err) if err.is_panic() => error!(job = name, duration_ms, "Job panicked"),
+        Err(_) => warn!(job = name, duration_ms, "Job aborted"),
+    }
+}
+
+/// The time left before the next cron match
+fn until_next(cron: &Cron) -> Option<Duration> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?;
+    let next = cron.next_after(now.as_secs())?;
+
+    Some(Duration::from_secs(next).saturating_sub(now))
+}
+
+/// Waits for the next run of a scheduled job
+enum Ticker {
+    Interval(Interval),
+    Cron(Cron),
+}
+
+impl Ticker {
+    fn new(schedule: Schedule) -> Self {
+        match schedule {
+            Schedule::Every(period) => {
+                let mut interval = tokio::time::interval(period);
+                interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+                Ticker::Interval(interval)
+            }
+            Schedule::Cron(cron) => Ticker::Cron(cron),
+        }
+    }
+
+    /// Returns `false` when the job never runs again
+    async fn tick(&mut self) -> bool {
+        match self {
+            Ticker::Interval(interval) => {
+                interval.tick().await;
+                true
+            }
+            Ticker::Cron(cron) => match until_next(cron) {
+                Some(delay) => {
+                    tokio::time::sleep(delay).await;
+                    true
+                }
+                None => false,
+            },
+        }
+    }
+}
+
+async fn run_scheduled(job: ScheduledJob, shutdown: CancellationToken) {
+    let mut ticker = Ticker::new(job.schedule);
+
+    loop {
+        tokio::select! {
+            _ = shutdown.cancelled() => return,
+            scheduled = ticker.tick() => if !scheduled {
+                warn!(job = job.name.as_str(), "Job never matches its cron expression");
+                return;
+            },
+        }
+
+        let start = Instant::now();
+        let result = spawn_run((job.task)()).await;
+        log_run(&job.name, start, result);
+    }
+}
+
+/// The jobs started by the server
+pub(crate) struct RunningJobs(JoinSet<()>);
+
+impl RunningJobs {
+    /// Wait for the running jobs after the shutdown was requested.
+    /// The jobs still running after `timeout` are aborted.
+    pub async fn stop(mut self, timeout: Duration) {
+        let all_done = async { while self.0.join_next().await.is_some() {} };
+
+        if tokio::time::timeout(timeout, all_done).await.is_err() {
+            warn!(
+                timeout_s = timeout.as_secs(),
+                "Aborting the jobs still running"
+            );
+            self.0.abort_all();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn it_keeps_running_the_job_after_a_panic() {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&runs);
+
+        let jobs = Jobs::new().every("flaky", Duration::from_millis(20), move || {
+            let run = counter.fetch_add(1, Ordering::SeqCst);
+            async move {
+                if run == 0 {
+                    panic!("first run failed");
+                }
+                Ok::<(), String>(())
+            }
+        });
+
+        let shutdown = CancellationToken::new();
+        let running = jobs.start(&shutdown);
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(runs.load(Ordering::SeqCst) >= 2);
+
+        shutdown.cancel();
+        running.stop(Duration::from_secs(10)).await;
+
+        let stopped_runs = runs.load(Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(runs.load(Ordering::SeqCst), stopped_runs);
+    }
+
+    #[tokio::test]
+    async fn it_stops_the_workers_on_shutdown() {
+        let jobs = Jobs::new().worker("consumer", |shutdown: Shutdown| async move {
+            shutdown.requested().await;
+            Ok::<(), String>(())
+        });
+
+        let shutdown = CancellationToken::new();
+        let running = jobs.start(&shutdown);
+        shutdown.cancel();
+
+        tokio::time::timeout(
+            Duration::from_secs(1),
+            running.stop(Duration::from_secs(10)),
+        )
+        .await
+        .expect("The worker did not stop");
+    }
+
+    #[tokio::test]
+    async fn it_aborts_the_running_jobs_after_the_timeout() {
+        struct Aborted(Arc<AtomicUsize>);
+
+        impl Drop for Aborted {
+            fn drop(&mut self) {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        let aborted = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&aborted);
+
+        let jobs = Jobs::new().every("stuck", Duration::from_secs(60), move || {
+            let guard = Aborted(Arc::clone(&counter));
+            async move {
+                let _guard = guard;
+                std::future::pending::<Result<(), String>>().await
+            }
+        });
+
+        let shutdown = CancellationToken::new();
+        let running = jobs.start(&shutdown);
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        shutdown.cancel();
+        running.stop(Duration::from_millis(20)).await;
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(aborted.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "the interval must be greater than zero")]
+    fn it_rejects_the_zero_interval() {
+        let _ = Jobs::new().every("refresh", Duration::ZERO, || async { Ok::<(), String>(()) });
+    }
+
+    #[test]
+    #[should_panic(expected = "Failed to schedule the job cleanup")]
+    fn it_rejects_the_invalid_cron_expressions() {
+        let _ = Jobs::new().cron("cleanup", "every day", || async { Ok::<(), String>(()) });
+    }
+}
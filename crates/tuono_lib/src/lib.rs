@@ -3,26 +3,52 @@
 //!
 //! You can find the full documentation at [tuono.dev](https://tuono.dev/)
 
+mod auth;
 mod catch_all;
 mod config;
-mod env;
+mod cookies;
+mod cron;
+mod head;
+mod health;
+mod i18n;
+mod jobs;
 mod manifest;
+pub mod metrics;
 mod mode;
+mod openapi;
 mod payload;
 mod request;
 mod response;
+mod scoped;
 mod server;
 mod services;
+pub mod session;
+mod socket;
+mod sse;
 mod ssr;
+mod telemetry;
+mod tls;
 mod vite_reverse_proxy;
 mod vite_websocket_proxy;
+mod ws;
 
+pub use auth::{AuthGuard, tuono_internal_guard};
+pub use head::Head;
+pub use health::HealthChecks;
+pub use i18n::Locale;
+pub use jobs::{Jobs, Shutdown};
 pub use mode::Mode;
 pub use payload::Payload;
 pub use request::Request;
-pub use response::{Props, Response};
-pub use server::{Server, tuono_internal_init_v8_platform};
-pub use tuono_lib_macros::{Type, api, handler};
+pub use response::{FileResponse, Props, Response, StreamResponse};
+pub use scoped::{Outcome, RequestScoped, Scoped};
+pub use server::{Server, tuono_internal_app_state_failed, tuono_internal_init_v8_platform};
+pub use services::limits::{LimitsLayer, RouteLimits};
+pub use socket::Socket;
+pub use sse::{SseEvent, SseResponse};
+pub use telemetry::{current_traceparent, tuono_internal_trace_handler};
+pub use tuono_lib_macros::{Type, api, handler, ws};
+pub use ws::tuono_internal_ws_origin_guard;
 
 // Re-exports
 pub use axum;
@@ -1,3 +1,4 @@
+use crate::config::GLOBAL_CONFIG;
 use once_cell::sync::Lazy;
 use once_cell::sync::OnceCell;
 use regex::Regex;
@@ -48,6 +49,10 @@ pub struct RouteBundle {
 pub struct Manifest {
     /// The mapping between the route and the bundle
     bundles: HashMap<String, RouteBundle>,
+    /// The application base path (i.e. `/shop`)
+    base_path: String,
+    /// The locales prefixing the pathnames (i.e. `/it/about`)
+    locales: Vec<String>,
 }
 
 fn clean_route_path(path: String) -> String {
@@ -153,11 +158,69 @@ impl From<ViteManifest> for Manifest {
             }
         }
 
-        Manifest { bundles }
+        Manifest {
+            bundles,
+            base_path: String::new(),
+            locales: Vec::new(),
+        }
     }
 }
 
 impl Manifest {
+    /// Serve the application under the `base_path`:
+    /// the pathnames are matched without it and the
+    /// bundle files are prefixed with it.
+    pub fn with_base_path(mut self, base_path: &str) -> Manifest {
+        let prefix = base_path.trim_start_matches('/');
+
+        if !prefix.is_empty() {
+            for bundle in self.bundles.values_mut() {
+                for file in bundle.css_files.iter_mut().chain(&mut bundle.js_files) {
+                    *file = format!("{prefix}/{file}");
+                }
+            }
+        }
+
+        self.base_path = base_path.to_string();
+        self
+    }
+
+    /// Match also the pathnames prefixed by one of the `locales`
+    pub fn with_locales(mut self, locales: &[String]) -> Manifest {
+        self.locales = locales.to_vec();
+        self
+    }
+
+    /// The pathname relative to the application base path
+    fn strip_base_path<'a>(&self, pathname: &'a str) -> &'a str {
+        if self.base_path.is_empty() {
+            return pathname;
+        }
+
+        match pathname.strip_prefix(self.base_path.as_str()) {
+            Some("") => "/",
+            Some(path) if path.starts_with('/') => path,
+            _ => pathname,
+        }
+    }
+
+    /// The pathname without the locale prefix
+    fn strip_locale<'a>(&self, pathname: &'a str) -> &'a str {
+        let segment = pathname
+            .strip_prefix('/')
+            .and_then(|path| path.split('/').next());
+
+        match segment {
+            Some(segment) if self.locales.iter().any(|locale| locale == segment) => {
+                match &pathname[segment.len() + 1..] {
+                    "" => "/",
+                    path => path,
+                }
+            }
+            _ => pathname,
+        }
+    }
+
     /// This method adds the route specific bundles to the server
     /// side rendered HTML.
     ///
@@ -166,6 +229,8 @@ impl Manifest {
     ///
     /// Optimizations should occour on both.
     pub fn get_bundle_from_pathname(&self, pathname: &str) -> RouteBundle {
+        let pathname = self.strip_locale(self.strip_base_path(pathname));
+
         // Exact match
         if let Some(bundle) = self.bundles.get(pathname) {
             return bundle.clone();
@@ -247,8 +312,12 @@ pub fn load_manifest() -> std::io::Result<()> {
     let file = File::open(PathBuf::from(VITE_MANIFEST_PATH))?;
     let reader = BufReader::new(file);
     let manifest: ViteManifest = serde_json::from_reader(reader)?;
+    let config = GLOBAL_CONFIG.get();
+    let manifest = Manifest::from(manifest)
+        .with_base_path(config.map_or("", |config| config.base_path.as_str()))
+        .with_locales(config.map_or(&[], |config| config.i18n.locales.as_slice()));
     MANIFEST
-        .set(Manifest::from(manifest))
+        .set(manifest)
         .map_err(|_| std::io::Error::other("Failed to set the manifest"))?;
     Ok(())
 }
@@ -518,4 +587,49 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn should_match_the_pathnames_under_the_base_path() {
+        let parsed_manifest = serde_json::from_str::<ViteManifest>(MANIFEST_EXAMPLE).unwrap();
+
+        let manifest = Manifest::from(parsed_manifest).with_base_path("/shop");
+
+        let index_route = manifest.get_bundle_from_pathname("/shop");
+        assert_eq!(
+            index_route.js_files,
+            vec![
+                "shop/assets/index-B3tnHOzi.js",
+                "shop/assets/client-main-DOdr9gvl.js"
+            ]
+        );
+
+        let route = manifest.get_bundle_from_pathname("/shop/pokemons/charizard/fire");
+        assert_eq!(route.js_files[0], "shop/assets/_type_-B-sJOcVJ.js");
+        assert_eq!(route.css_files[0], "shop/assets/_type_-B8vgxybx.css");
+
+        // Not a sub path of the base path
+        let route = manifest.get_bundle_from_pathname("/shopping");
+        assert_eq!(route.js_files, vec!["shop/assets/client-main-DOdr9gvl.js"]);
+    }
+
+    #[test]
+    fn should_match_the_pathnames_prefixed_by_a_locale() {
+        let parsed_manifest = serde_json::from_str::<ViteManifest>(MANIFEST_EXAMPLE).unwrap();
+
+        let manifest = Manifest::from(parsed_manifest)
+            .with_base_path("/shop")
+            .with_locales(&["en".to_string(), "it".to_string()]);
+
+        let index_route = manifest.get_bundle_from_pathname("/shop/it");
+        assert_eq!(index_route.js_files[0], "shop/assets/index-B3tnHOzi.js");
+
+        let route = manifest.get_bundle_from_pathname("/shop/it/about");
+        assert_eq!(route.js_files[0], "shop/assets/about-C3UqHfGb.js");
+
+        let route = manifest.get_bundle_from_pathname("/shop/about");
+        assert_eq!(route.js_files[0], "shop/assets/about-C3UqHfGb.js");
+
+        let route = manifest.get_bundle_from_pathname("/shop/it/pokemons/ditto");
+        assert_eq!(route.js_files[0], "shop/assets/index-ByRBj7WK.js");
+    }
 }
@@ -0,0 +1,537 @@
+//! Prometheus metrics exposed by the `/metrics` endpoint
+//! (enabled with the `metrics.enabled` option).
+//!
+//! Besides the built-in HTTP and SSR metrics, the application can
+//! register its own:
+//!
+//! ```ignore
+//! use tuono_lib::metrics;
+//!
+//! metrics::counter("orders_total", "Orders placed").inc(&[("plan", "pro")]);
+//! ```
+
+use crate::services::path_pattern::{app_path, split_data_route};
+use axum::http::{HeaderValue, StatusCode, header};
+use axum::response::IntoResponse;
+use axum::routing::{Router, get};
+use once_cell::sync::Lazy;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tuono_internal::config::MetricsConfig;
+
+/// The Prometheus client libraries default buckets (in seconds)
+pub const DEFAULT_BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+type Labels = Vec<(String, String)>;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Kind {
+    Counter,
+    Gauge,
+    Histogram(Vec<f64>),
+}
+
+impl Kind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Kind::Counter => "counter",
+            Kind::Gauge => "gauge",
+            Kind::Histogram(_) => "histogram",
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Series {
+    Value(f64),
+    Histogram {
+        /// Not cumulative, summed when rendered
+        buckets: Vec<u64>,
+        sum: f64,
+        count: u64,
+    },
+}
+
+#[derive(Debug)]
+struct Family {
+    name: String,
+    help: String,
+    kind: Kind,
+    series: Mutex<BTreeMap<Labels, Series>>,
+}
+
+impl Family {
+    fn update(&self, labels: &[(&str, &str)], update: impl FnOnce(&mut Series)) {
+        let labels = labels
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+
+        let mut series = self.series.lock().unwrap_or_else(|err| err.into_inner());
+        let series = series.entry(labels).or_insert_with(|| match &self.kind {
+            Kind::Histogram(buckets) => Series::Histogram {
+                buckets: vec![0; buckets.len()],
+                sum: 0.0,
+                count: 0,
+            },
+            _ => Series::Value(0.0),
+        });
+
+        update(series);
+    }
+
+    fn render(&self, output: &mut String) {
+        let _ = writeln!(output, "# HELP {} {}", self.name, escape(&self.help, false));
+        let _ = writeln!(output, "# TYPE {} {}", self.name, self.kind.as_str());
+
+        let series = self.series.lock().unwrap_or_else(|err| err.into_inner());
+
+        for (labels, series) in series.iter() {
+            match series {
+                Series::Value(value) => {
+                    let _ = writeln!(
+                        output,
+                        "{}{} {}",
+                        self.name,
+                        format_labels(labels, None),
+                        value
+                    );
+                }
+                Series::Histogram {
+                    buckets,
+                    sum,
+                    count,
+                } => {
+                    let Kind::Histogram(bounds) = &self.kind else {
+                        continue;
+                    };
+
+                    let mut cumulative = 0;
+                    for (bound, bucket) in bounds.iter().zip(buckets) {
+                        cumulative += bucket;
+                        let _ = writeln!(
+                            output,
+                            "{}_bucket{} {}",
+                            self.name,
+                            format_labels(labels, Some(&bound.to_string())),
+                            cumulative
+                        );
+                    }
+                    let _ = writeln!(
+                        output,
+                        "{}_bucket{} {}",
+                        self.name,
+                        format_labels(labels, Some("+Inf")),
+                        count
+                    );
+                    let _ = writeln!(
+                        output,
+                        "{}_sum{} {}",
+                        self.name,
+                        format_labels(labels, None),
+                        sum
+                    );
+                    let _ = writeln!(
+                        output,
+                        "{}_count{} {}",
+                        self.name,
+                        format_labels(labels, None),
+                        count
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn escape(value: &str, quotes: bool) -> String {
+    let value = value.replace('\\', "\\\\").replace('\n', "\\n");
+
+    if quotes {
+        value.replace('"', "\\\"")
+    } else {
+        value
+    }
+}
+
+fn format_labels(labels: &Labels, le: Option<&str>) -> String {
+    let mut labels: Vec<String> = labels
+        .iter()
+        .map(|(name, value)| format!("{name}=\"{}\"", escape(value, true)))
+        .collect();
+
+    if let Some(le) = le {
+        labels.push(format!("le=\"{le}\""));
+    }
+
+    if labels.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", labels.join(","))
+    }
+}
+
+static REGISTRY: Lazy<Mutex<Vec<Arc<Family>>>> = Lazy::new(|| Mutex::new(Vec::new()));
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Get the metric registered with `name` or register it.
+///
+/// # Panics
+///
+/// Panics if `name` is already registered with a different kind.
+fn family(name: &str, help: &str, kind: Kind) -> Arc<Family> {
+    let mut registry = REGISTRY.lock().unwrap_or_else(|err| err.into_inner());
+
+    if let Some(family) = registry.iter().find(|family| family.name == name) {
+        assert!(
+            family.kind == kind,
+            "The metric {name} is already registered as {}",
+            family.kind.as_str()
+        );
+        return Arc::clone(family);
+    }
+
+    let family = Arc::new(Family {
+        name: name.to_string(),
+        help: help.to_string(),
+        kind,
+        series: Mutex::new(BTreeMap::new()),
+    });
+    registry.push(Arc::clone(&family));
+    family
+}
+
+#[derive(Debug, Clone)]
+pub struct Counter(Arc<Family>);
+
+impl Counter {
+    pub fn inc(&self, labels: &[(&str, &str)]) {
+        self.inc_by(labels, 1.0);
+    }
+
+    pub fn inc_by(&self, labels: &[(&str, &str)], value: f64) {
+        self.0.update(labels, |series| {
+            if let Series::Value(current) = series {
+                *current += value;
+            }
+        });
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Gauge(Arc<Family>);
+
+impl Gauge {
+    pub fn set(&self, labels: &[(&str, &str)], value: f64) {
+        self.0.update(labels, |series| {
+            if let Series::Value(current) = series {
+                *current = value;
+            }
+        });
+    }
+
+    pub fn add(&self, labels: &[(&str, &str)], value: f64) {
+        self.0.update(labels, |series| {
+            if let Series::Value(current) = series {
+                *current += value;
+            }
+        });
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Histogram(Arc<Family>);
+
+impl Histogram {
+    pub fn observe(&self, labels: &[(&str, &str)], value: f64) {
+        let Kind::Histogram(bounds) = &self.0.kind else {
+            return;
+        };
+
+        self.0.update(labels, |series| {
+            if let Series::Histogram {
+                buckets,
+                sum,
+                count,
+            } = series
+            {
+                if let Some(index) = bounds.iter().position(|bound| value <= *bound) {
+                    buckets[index] += 1;
+                }
+                *sum += value;
+                *count += 1;
+            }
+        });
+    }
+
+    pub fn observe_duration(&self, labels: &[(&str, &str)], duration: Duration) {
+        self.observe(labels, duration.as_secs_f64());
+    }
+}
+
+/// Register a counter or get the one already registered with `name`
+pub fn counter(name: &str, help: &str) -> Counter {
+    Counter(family(name, help, Kind::Counter))
+}
+
+/// Register a gauge or get the one already registered with `name`
+pub fn gauge(name: &str, help: &str) -> Gauge {
+    Gauge(family(name, help, Kind::Gauge))
+}
+
+/// Register a histogram with the `DEFAULT_BUCKETS`
+/// or get the one already registered with `name`
+pub fn histogram(name: &str, help: &str) -> Histogram {
+    histogram_with_buckets(name, help, &DEFAULT_BUCKETS)
+}
+
+/// Register a histogram or get the one already registered with `name`.
+/// The `buckets` upper bounds must be sorted.
+pub fn histogram_with_buckets(name: &str, help: &str, buckets: &[f64]) -> Histogram {
+    Histogram(family(name, help, Kind::Histogram(buckets.to_vec())))
+}
+
+/// All the registered metrics in the Prometheus text format
+pub fn render() -> String {
+    let registry = REGISTRY.lock().unwrap_or_else(|err| err.into_inner());
+    let mut output = String::new();
+
+    for family in registry.iter() {
+        family.render(&mut output);
+    }
+
+    output
+}
+
+/// Whether the built-in metrics are recorded
+pub(crate) fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+static HTTP_REQUESTS: Lazy<Counter> =
+    Lazy::new(|| counter("tuono_http_requests_total", "Handled HTTP requests"));
+static HTTP_DURATION: Lazy<Histogram> = Lazy::new(|| {
+    histogram(
+        "tuono_http_request_duration_seconds",
+        "HTTP requests duration",
+    )
+});
+static HTTP_IN_FLIGHT: Lazy<Gauge> = Lazy::new(|| {
+    gauge(
+        "tuono_http_requests_in_flight",
+        "HTTP requests being handled",
+    )
+});
+static SSR_DURATION: Lazy<Histogram> = Lazy::new(|| {
+    histogram(
+        "tuono_ssr_render_duration_seconds",
+        "Server side rendering duration",
+    )
+});
+static SSR_FAILURES: Lazy<Counter> = Lazy::new(|| {
+    counter(
+        "tuono_ssr_render_failures_total",
+        "Failed server side renderings",
+    )
+});
+
+/// A handled request recorded by the `LoggerLayer`
+pub(crate) struct RequestRecord<'a> {
+    pub method: &'a str,
+    /// The route matched by the router (e.g. `/posts/{slug}`)
+    pub route: Option<&'a str>,
+    /// Removed from the route labels
+    pub base_path: &'a str,
+    pub status: StatusCode,
+    pub content_type: Option<&'a HeaderValue>,
+    pub duration: Duration,
+}
+
+impl RequestRecord<'_> {
+    /// The route label (without the base path and the data prefix)
+    /// and the response kind
+    fn route_and_kind(&self) -> (&str, &'static str) {
+        let Some(route) = self.route else {
+            return ("fallback", self.content_kind());
+        };
+
+        let route = app_path(self.base_path, route).unwrap_or(route);
+        match split_data_route(route) {
+            (true, route) => (route, "data"),
+            (false, route) => (route, self.content_kind()),
+        }
+    }
+
+    fn content_kind(&self) -> &'static str {
+        let is_html = self
+            .content_type
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.starts_with("text/html"));
+
+        if is_html { "html" } else { "other" }
+    }
+}
+
+pub(crate) fn record_request(record: RequestRecord) {
+    let (route, kind) = record.route_and_kind();
+    let labels = [("method", record.method), ("route", route), ("kind", kind)];
+
+    HTTP_DURATION.observe_duration(&labels, record.duration);
+    HTTP_REQUESTS.inc(&[
+        ("method", record.method),
+        ("route", route),
+        ("kind", kind),
+        ("status", record.status.as_str()),
+    ]);
+}
+
+/// Counts the request as in flight until dropped
+pub(crate) struct InFlight;
+
+impl InFlight {
+    pub fn start() -> Option<Self> {
+        if !is_enabled() {
+            return None;
+        }
+
+        HTTP_IN_FLIGHT.add(&[], 1.0);
+        Some(InFlight)
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        HTTP_IN_FLIGHT.add(&[], -1.0);
+    }
+}
+
+pub(crate) fn record_render(duration: Duration, is_success: bool) {
+    if !is_enabled() {
+        return;
+    }
+
+    SSR_DURATION.observe_duration(&[], duration);
+    if !is_success {
+        SSR_FAILURES.inc(&[]);
+    }
+}
+
+async fn metrics_document() -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        render(),
+    )
+}
+
+/// Enable the built-in metrics and add the endpoint to the router
+pub(crate) fn routes(config: &MetricsConfig, router: Router) -> Router {
+    if !config.enabled {
+        return router;
+    }
+
+    ENABLED.store(true, Ordering::Relaxed);
+
+    // Expose the built-in metrics before the first request
+    Lazy::force(&HTTP_REQUESTS);
+    Lazy::force(&HTTP_DURATION);
+    HTTP_IN_FLIGHT.add(&[], 0.0);
+    Lazy::force(&SSR_DURATION);
+    Lazy::force(&SSR_FAILURES);
+
+    router.route(&config.path, get(metrics_document))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_renders_the_counters_and_gauges() {
+        let orders = counter("test_orders_total", "Orders \"placed\"\nby plan");
+        orders.inc(&[("plan", "pro")]);
+        orders.inc_by(&[("plan", "pro")], 2.0);
+        orders.inc(&[("plan", "free \"trial\"")]);
+
+        gauge("test_queue_size", "Queue size").set(&[], 4.0);
+
+        let output = render();
+
+        assert!(output.contains(
+            "# HELP test_orders_total Orders \"placed\"\\nby plan\n\
+             # TYPE test_orders_total counter\n\
+             test_orders_total{plan=\"free \\\"trial\\\"\"} 1\n\
+             test_orders_total{plan=\"pro\"} 3\n"
+        ));
+        assert!(output.contains("# TYPE test_queue_size gauge\ntest_queue_size 4\n"));
+    }
+
+    #[test]
+    fn it_renders_the_cumulative_histogram_buckets() {
+        let latency = histogram_with_buckets("test_latency_seconds", "Latency", &[0.1, 1.0]);
+        latency.observe(&[("route", "/")], 0.05);
+        latency.observe(&[("route", "/")], 0.5);
+        latency.observe(&[("route", "/")], 3.0);
+
+        assert!(render().contains(
+            "test_latency_seconds_bucket{route=\"/\",le=\"0.1\"} 1\n\
+             test_latency_seconds_bucket{route=\"/\",le=\"1\"} 2\n\
+             test_latency_seconds_bucket{route=\"/\",le=\"+Inf\"} 3\n\
+             test_latency_seconds_sum{route=\"/\"} 3.55\n\
+             test_latency_seconds_count{route=\"/\"} 3\n"
+        ));
+    }
+
+    #[test]
+    fn it_returns_the_registered_metric() {
+        counter("test_visits_total", "Visits").inc(&[]);
+        counter("test_visits_total", "Visits").inc(&[]);
+
+        assert!(render().contains("test_visits_total 2\n"));
+    }
+
+    #[test]
+    #[should_panic(expected = "The metric test_jobs is already registered as counter")]
+    fn it_rejects_the_metrics_registered_with_another_kind() {
+        counter("test_jobs", "Jobs");
+        gauge("test_jobs", "Jobs");
+    }
+
+    #[test]
+    fn it_labels_the_data_requests_with_the_page_route() {
+        let record = RequestRecord {
+            method: "GET",
+            route: Some("/__tuono/data/posts/{slug}"),
+            base_path: "",
+            status: StatusCode::OK,
+            content_type: None,
+            duration: Duration::ZERO,
+        };
+        assert_eq!(record.route_and_kind(), ("/posts/{slug}", "data"));
+
+        let html = HeaderValue::from_static("text/html; charset=utf-8");
+        let record = RequestRecord {
+            route: None,
+            content_type: Some(&html),
+            ..record
+        };
+        assert_eq!(record.route_and_kind(), ("fallback", "html"));
+
+        let record = RequestRecord {
+            route: Some("/shop/__tuono/data/posts/{slug}"),
+            base_path: "/shop",
+            ..record
+        };
+        assert_eq!(record.route_and_kind(), ("/posts/{slug}", "data"));
+
+        let record = RequestRecord {
+            route: Some("/shop/posts/{slug}"),
+            ..record
+        };
+        assert_eq!(record.route_and_kind(), ("/posts/{slug}", "html"));
+    }
+}
@@ -7,4 +7,14 @@ pub enum Mode {
     Prod,
 }
 
+impl Mode {
+    /// The name used by the mode specific `.env` files
+    pub fn env_name(self) -> &'static str {
+        match self {
+            Mode::Dev => "development",
+            Mode::Prod => "production",
+        }
+    }
+}
+
 pub static GLOBAL_MODE: OnceCell<Mode> = OnceCell::new();
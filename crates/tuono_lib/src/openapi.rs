@@ -0,0 +1,28 @@
+use axum::http::{StatusCode, header};
+use axum::response::IntoResponse;
+use std::path::PathBuf;
+use tokio::fs::read_to_string;
+
+#[cfg(target_os = "windows")]
+const OPENAPI_FILE_PATH: &str = ".\\.tuono\\openapi.json";
+#[cfg(not(target_os = "windows"))]
+const OPENAPI_FILE_PATH: &str = "./.tuono/openapi.json";
+
+/// Serve the OpenAPI document generated by the CLI on `tuono dev`.
+/// The file is read on every request so that it always reflects the
+/// latest version of the API routes, without blocking the runtime.
+pub async fn openapi_document() -> impl IntoResponse {
+    match read_to_string(PathBuf::from(OPENAPI_FILE_PATH)).await {
+        Ok(document) => (
+            StatusCode::OK,
+            [(header::CONTENT_TYPE, "application/json")],
+            document,
+        )
+            .into_response(),
+        Err(_) => (
+            StatusCode::NOT_FOUND,
+            "OpenAPI document not found. Run `tuono dev` to generate it.",
+        )
+            .into_response(),
+    }
+}
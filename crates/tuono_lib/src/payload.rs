@@ -1,4 +1,6 @@
 use crate::config::GLOBAL_CONFIG;
+use crate::head::Head;
+use crate::i18n::Locale;
 use crate::manifest::MANIFEST;
 use crate::mode::{GLOBAL_MODE, Mode};
 use erased_serde::Serialize;
@@ -19,6 +21,13 @@ pub struct Payload<'a> {
     css_bundles: Option<Vec<String>>,
     #[serde(rename(serialize = "devServerConfig"))]
     dev_server_config: Option<&'a ServerConfig>,
+    head: Option<&'a Head>,
+    #[serde(rename(serialize = "csrfToken"))]
+    csrf_token: Option<String>,
+    #[serde(rename(serialize = "basePath"))]
+    base_path: &'a str,
+    locale: Option<&'a Locale>,
+    locales: &'a [String],
 }
 
 impl<'a> Payload<'a> {
@@ -42,9 +51,20 @@ impl<'a> Payload<'a> {
             js_bundles: None,
             css_bundles: None,
             dev_server_config,
+            head: None,
+            csrf_token: req.csrf_token(),
+            base_path: &config.base_path,
+            locale: req.locale(),
+            locales: &config.i18n.locales,
         }
     }
 
+    /// Attach the document head metadata defined by the route handler
+    pub fn with_head(mut self, head: &'a Head) -> Payload<'a> {
+        self.head = Some(head);
+        self
+    }
+
     pub fn client_payload(&mut self) -> Result<String, serde_json::Error> {
         if self.mode == Mode::Prod {
             self.add_bundle_sources();
@@ -139,6 +159,11 @@ mod tests {
             js_bundles: None,
             css_bundles: None,
             dev_server_config: None,
+            head: None,
+            csrf_token: None,
+            base_path: "",
+            locale: None,
+            locales: &[],
         }
     }
 
@@ -1,4 +1,10 @@
-use axum::http::{HeaderMap, Uri};
+use crate::auth::AuthenticatedUser;
+use crate::cookies::cookie_key;
+use crate::i18n::{Locale, LocalizedUri};
+use crate::services::csrf::CsrfToken;
+use crate::session::Session;
+use axum::http::{Extensions, HeaderMap, Uri};
+use axum_extra::extract::cookie::{CookieJar, PrivateCookieJar, SignedCookieJar};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -38,6 +44,7 @@ pub struct Request {
     pub headers: HeaderMap,
     pub params: HashMap<String, String>,
     body: Option<Vec<u8>>,
+    extensions: Extensions,
 }
 
 impl Request {
@@ -52,11 +59,94 @@ impl Request {
             headers,
             params,
             body,
+            extensions: Extensions::new(),
         }
     }
 
+    /// Attach the extensions populated by the server layers (e.g. the session)
+    pub fn with_extensions(mut self, extensions: Extensions) -> Request {
+        self.extensions = extensions;
+        self
+    }
+
+    /// The location requested by the client,
+    /// including the locale prefix of the path.
     pub fn location(&self) -> Location {
-        Location::from(self.uri.to_owned())
+        match self.extensions.get::<LocalizedUri>() {
+            Some(uri) => Location::from(uri.0.to_owned()),
+            None => Location::from(self.uri.to_owned()),
+        }
+    }
+
+    /// The locale resolved by the i18n routing.
+    /// `None` when no locale is configured.
+    pub fn locale(&self) -> Option<&Locale> {
+        self.extensions.get::<Locale>()
+    }
+
+    /// The cookies sent by the client
+    pub fn cookies(&self) -> CookieJar {
+        CookieJar::from_headers(&self.headers)
+    }
+
+    /// The cookies set with `Props::add_signed_cookie`.
+    /// Cookies with an invalid signature are discarded.
+    pub fn signed_cookies(&self) -> SignedCookieJar {
+        SignedCookieJar::from_headers(&self.headers, cookie_key().clone())
+    }
+
+    /// The cookies set with `Props::add_private_cookie`.
+    /// Cookies that can't be decrypted are discarded.
+    pub fn private_cookies(&self) -> PrivateCookieJar {
+        PrivateCookieJar::from_headers(&self.headers, cookie_key().clone())
+    }
+
+    /// The session of the client.
+    ///
+    /// When the request didn't go through the tuono server (i.e. in unit tests)
+    /// a detached session is returned and its changes are never saved.
+    pub fn session(&self) -> Session {
+        self.extensions
+            .get::<Session>()
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// The user resolved by the `authenticate` hook defined in `src/app.rs`.
+    /// `None` when the visitor is not authenticated or `U` is not the
+    /// type returned by the hook.
+    pub fn user<U: Clone + 'static>(&self) -> Option<U> {
+        self.extensions
+            .get::<AuthenticatedUser>()
+            .and_then(|user| user.0.downcast_ref::<U>())
+            .cloned()
+    }
+
+    pub fn is_authenticated(&self) -> bool {
+        self.extensions.get::<AuthenticatedUser>().is_some()
+    }
+
+    /// The `ApplicationState` returned by the `main` function of `src/app.rs`.
+    /// `None` when `S` is not the application state type.
+    pub fn app_state<S: Clone + Send + Sync + 'static>(&self) -> Option<S> {
+        self.extensions.get::<S>().cloned()
+    }
+
+    /// The id of the last event received by the browser before
+    /// reconnecting to a `SseResponse` route.
+    pub fn last_event_id(&self) -> Option<String> {
+        self.headers
+            .get("last-event-id")
+            .and_then(|value| value.to_str().ok())
+            .map(String::from)
+    }
+
+    /// The token required by the unsafe requests (i.e. `POST`).
+    /// `None` when the CSRF protection is disabled.
+    pub fn csrf_token(&self) -> Option<String> {
+        self.extensions
+            .get::<CsrfToken>()
+            .map(|token| token.0.clone())
     }
 
     pub fn body<'de, T: Deserialize<'de>>(&'de self) -> Result<T, BodyParseError> {
@@ -116,6 +206,7 @@ impl From<serde_json::Error> for BodyParseError {
 mod tests {
 
     use super::*;
+    use axum_extra::extract::cookie::Cookie;
 
     #[derive(Debug, Deserialize)]
     struct FakeBody {
@@ -129,6 +220,63 @@ mod tests {
         email: Option<String>,
     }
 
+    #[test]
+    fn it_reads_the_request_cookies() {
+        let signed = crate::cookies::sign_cookie(Cookie::new("signed", "value"));
+        let private = crate::cookies::encrypt_cookie(Cookie::new("private", "value"));
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "cookie",
+            format!(
+                "plain=value; {}; {}; tampered=value",
+                signed.stripped(),
+                private.stripped()
+            )
+            .parse()
+            .unwrap(),
+        );
+
+        let request = Request::new(
+            Uri::from_static("http://localhost:3000"),
+            headers,
+            HashMap::new(),
+            None,
+        );
+
+        assert_eq!(request.cookies().get("plain").unwrap().value(), "value");
+        assert_eq!(
+            request.signed_cookies().get("signed").unwrap().value(),
+            "value"
+        );
+        assert!(request.signed_cookies().get("tampered").is_none());
+        assert_eq!(
+            request.private_cookies().get("private").unwrap().value(),
+            "value"
+        );
+        assert!(request.private_cookies().get("signed").is_none());
+    }
+
+    #[test]
+    fn it_reads_the_session_from_the_extensions() {
+        let session = Session::default();
+        session.insert("user_id", 42).unwrap();
+
+        let mut extensions = Extensions::new();
+        extensions.insert(session);
+
+        let request = Request::new(
+            Uri::from_static("http://localhost:3000"),
+            HeaderMap::new(),
+            HashMap::new(),
+            None,
+        );
+        assert_eq!(request.session().get::<u32>("user_id"), None);
+
+        let request = request.with_extensions(extensions);
+        assert_eq!(request.session().get::<u32>("user_id"), Some(42));
+    }
+
     #[test]
     fn it_correctly_parse_the_body() {
         let request = Request::new(
@@ -1,33 +1,82 @@
 use crate::Request;
+use crate::cookies::{encrypt_cookie, sign_cookie};
+use crate::head::Head;
 use crate::{Payload, ssr::Js};
 use axum::Json;
+use axum::body::{Body, Bytes};
+use axum::http::header::{self, HeaderValue, IntoHeaderName};
 use axum::http::{HeaderMap, StatusCode};
 use axum::response::{Html, IntoResponse, Redirect};
+use axum::{BoxError, response::Response as AxumResponse};
 use axum_extra::extract::cookie::{Cookie, CookieJar};
 use erased_serde::Serialize;
+use futures_util::TryStream;
+use std::io;
+use std::path::Path;
+use tokio_util::io::ReaderStream;
 
 pub struct Props {
     data: Box<dyn Serialize>,
     http_code: StatusCode,
     cookies: CookieJar,
+    headers: HeaderMap,
+    head: Head,
+}
+
+/// A response whose body is sent in chunks as soon as they are produced
+pub struct StreamResponse {
+    body: Body,
+    http_code: StatusCode,
+    headers: HeaderMap,
+}
+
+/// A response that sends a file to the client.
+/// By default the browser is asked to download it.
+pub struct FileResponse {
+    body: Body,
+    file_name: String,
+    inline: bool,
+    http_code: StatusCode,
+    headers: HeaderMap,
 }
 
 pub enum Response {
+    /// Permanent redirect (308)
     Redirect(String),
+    /// Temporary redirect (307)
+    TemporaryRedirect(String),
+    /// Redirect to a different page after a form submission (303).
+    /// The browser always follows it with a `GET` request.
+    SeeOther(String),
     Props(Props),
-    // TODO: improve this tuple to support a more generic IntoResponse
-    Custom((StatusCode, HeaderMap, String)),
+    /// Any response implementing axum's `IntoResponse`.
+    /// Use `Response::custom` to create it.
+    Custom(AxumResponse),
+    Stream(StreamResponse),
+    File(FileResponse),
 }
 
 #[derive(serde::Serialize)]
 struct JsonResponseInfo {
     redirect_destination: Option<String>,
+    /// The route can't be rendered by the client (e.g. a file download)
+    /// hence the browser needs to load it directly
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    reload: bool,
 }
 
 impl JsonResponseInfo {
     fn new(redirect_destination: Option<String>) -> JsonResponseInfo {
         JsonResponseInfo {
             redirect_destination,
+            reload: false,
+        }
+    }
+
+    fn new_reload() -> JsonResponseInfo {
+        JsonResponseInfo {
+            redirect_destination: None,
+            reload: true,
         }
     }
 }
@@ -36,13 +85,16 @@ impl JsonResponseInfo {
 struct JsonResponse<'a> {
     data: Option<&'a dyn Serialize>,
     info: JsonResponseInfo,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    head: Option<&'a Head>,
 }
 
 impl<'a> JsonResponse<'a> {
-    fn new(props: &'a dyn Serialize) -> Self {
+    fn new(props: &'a dyn Serialize, head: &'a Head) -> Self {
         JsonResponse {
             data: Some(props),
             info: JsonResponseInfo::new(None),
+            head: (!head.is_empty()).then_some(head),
         }
     }
 
@@ -50,6 +102,15 @@ impl<'a> JsonResponse<'a> {
         JsonResponse {
             data: None,
             info: JsonResponseInfo::new(Some(destination)),
+            head: None,
+        }
+    }
+
+    fn new_reload() -> Self {
+        JsonResponse {
+            data: None,
+            info: JsonResponseInfo::new_reload(),
+            head: None,
         }
     }
 }
@@ -60,6 +121,8 @@ impl Props {
             data: Box::new(data),
             http_code: StatusCode::OK,
             cookies: CookieJar::new(),
+            headers: HeaderMap::new(),
+            head: Head::default(),
         }
     }
 
@@ -72,6 +135,8 @@ impl Props {
             data: Box::new(data),
             http_code,
             cookies: CookieJar::new(),
+            headers: HeaderMap::new(),
+            head: Head::default(),
         }
     }
 
@@ -79,53 +144,251 @@ impl Props {
         let jar = self.cookies.clone().add(cookie.into_owned());
         self.cookies = jar
     }
+
+    /// Add a cookie whose value can't be tampered by the client.
+    /// Read it with `Request::signed_cookies`.
+    pub fn add_signed_cookie(&mut self, cookie: Cookie) {
+        self.add_cookie(sign_cookie(cookie.into_owned()));
+    }
+
+    /// Add a cookie whose value can't be read or tampered by the client.
+    /// Read it with `Request::private_cookies`.
+    pub fn add_private_cookie(&mut self, cookie: Cookie) {
+        self.add_cookie(encrypt_cookie(cookie.into_owned()));
+    }
+
+    /// Add a header to both the server side rendered page
+    /// and the client side navigation data response.
+    pub fn add_header(&mut self, name: impl IntoHeaderName, value: HeaderValue) {
+        self.headers.append(name, value);
+    }
+
+    /// Define the document head metadata (title, description, Open Graph tags...).
+    /// It is rendered in the server side generated HTML so that crawlers can read it.
+    pub fn head(&mut self, head: Head) {
+        self.head = head;
+    }
+}
+
+impl StreamResponse {
+    /// Create a response from a stream of bytes chunks
+    pub fn new<S>(stream: S) -> Self
+    where
+        S: TryStream + Send + 'static,
+        S::Ok: Into<Bytes>,
+        S::Error: Into<BoxError>,
+    {
+        StreamResponse {
+            body: Body::from_stream(stream),
+            http_code: StatusCode::OK,
+            headers: HeaderMap::new(),
+        }
+    }
+
+    pub fn status(&mut self, http_code: StatusCode) {
+        self.http_code = http_code;
+    }
+
+    pub fn add_header(&mut self, name: impl IntoHeaderName, value: HeaderValue) {
+        self.headers.append(name, value);
+    }
+}
+
+impl FileResponse {
+    /// Create a file response from in memory content.
+    /// The content type is guessed from the file name extension.
+    pub fn new(file_name: impl Into<String>, content: impl Into<Bytes>) -> Self {
+        FileResponse {
+            body: Body::from(content.into()),
+            file_name: file_name.into(),
+            inline: false,
+            http_code: StatusCode::OK,
+            headers: HeaderMap::new(),
+        }
+    }
+
+    /// Open the file at `path` and stream it to the client
+    pub async fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let file = tokio::fs::File::open(path).await?;
+        let length = file.metadata().await?.len();
+
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::CONTENT_LENGTH, HeaderValue::from(length));
+
+        Ok(FileResponse {
+            body: Body::from_stream(ReaderStream::new(file)),
+            file_name,
+            inline: false,
+            http_code: StatusCode::OK,
+            headers,
+        })
+    }
+
+    /// Override the file name suggested to the browser
+    pub fn file_name(&mut self, file_name: impl Into<String>) {
+        self.file_name = file_name.into();
+    }
+
+    /// Ask the browser to display the file instead of downloading it
+    pub fn inline(&mut self) {
+        self.inline = true;
+    }
+
+    pub fn status(&mut self, http_code: StatusCode) {
+        self.http_code = http_code;
+    }
+
+    pub fn add_header(&mut self, name: impl IntoHeaderName, value: HeaderValue) {
+        self.headers.append(name, value);
+    }
+
+    fn content_disposition(&self) -> HeaderValue {
+        let disposition = if self.inline { "inline" } else { "attachment" };
+
+        if self.file_name.is_empty() {
+            return HeaderValue::from_static(disposition);
+        }
+
+        let ascii_file_name: String = self
+            .file_name
+            .chars()
+            .map(|c| match c {
+                '"' | '\\' => '_',
+                c if c.is_ascii() && !c.is_ascii_control() => c,
+                _ => '_',
+            })
+            .collect();
+
+        let mut value = format!("{disposition}; filename=\"{ascii_file_name}\"");
+
+        // RFC 6266: non ASCII names are provided with the extended parameter
+        if !self.file_name.is_ascii() {
+            value.push_str("; filename*=UTF-8''");
+            for byte in self.file_name.bytes() {
+                if byte.is_ascii_alphanumeric() || b"!#$&+-.^_`|~".contains(&byte) {
+                    value.push(byte as char);
+                } else {
+                    value.push_str(&format!("%{byte:02X}"));
+                }
+            }
+        }
+
+        HeaderValue::from_str(&value).unwrap_or_else(|_| HeaderValue::from_static(disposition))
+    }
+}
+
+impl IntoResponse for StreamResponse {
+    fn into_response(self) -> AxumResponse {
+        (self.http_code, self.headers, self.body).into_response()
+    }
+}
+
+impl IntoResponse for FileResponse {
+    fn into_response(self) -> AxumResponse {
+        let content_type = mime_guess::from_path(&self.file_name).first_or_octet_stream();
+
+        let mut headers = HeaderMap::new();
+        if let Ok(content_type) = HeaderValue::from_str(content_type.as_ref()) {
+            headers.insert(header::CONTENT_TYPE, content_type);
+        }
+        headers.insert(header::CONTENT_DISPOSITION, self.content_disposition());
+        // User defined headers take precedence over the inferred ones
+        for (name, value) in self.headers.iter() {
+            headers.insert(name, value.clone());
+        }
+
+        (self.http_code, headers, self.body).into_response()
+    }
 }
 
 impl Response {
-    pub fn render_to_string(&self, req: Request) -> impl IntoResponse + use<> {
+    /// Create a response from any type implementing axum's `IntoResponse`
+    pub fn custom(response: impl IntoResponse) -> Self {
+        Self::Custom(response.into_response())
+    }
+
+    pub fn render_to_string(self, req: Request) -> impl IntoResponse + use<> {
         match self {
             Self::Props(Props {
                 data,
                 http_code,
                 cookies,
+                headers,
+                head,
             }) => {
-                let payload = Payload::new(&req, data.as_ref()).client_payload().unwrap();
+                let payload = Payload::new(&req, data.as_ref())
+                    .with_head(&head)
+                    .client_payload()
+                    .unwrap();
 
                 match Js::render_to_string(Some(&payload)) {
-                    Ok(html) => (*http_code, cookies.clone(), Html(html)),
+                    Ok(html) => (http_code, headers, cookies, Html(head.inject(html))),
                     Err(_) => (
-                        *http_code,
-                        cookies.clone(),
+                        http_code,
+                        headers,
+                        cookies,
                         Html("500 Internal server error".to_string()),
                     ),
                 }
                 .into_response()
             }
-            Self::Redirect(to) => Redirect::permanent(to).into_response(),
-            Self::Custom(response) => response.clone().into_response(),
+            Self::Redirect(to) => Redirect::permanent(&to).into_response(),
+            Self::TemporaryRedirect(to) => Redirect::temporary(&to).into_response(),
+            Self::SeeOther(to) => Redirect::to(&to).into_response(),
+            Self::Custom(response) => response,
+            Self::Stream(response) => response.into_response(),
+            Self::File(response) => response.into_response(),
         }
     }
 
-    pub fn json(&self) -> impl IntoResponse + use<> {
+    pub fn json(self) -> impl IntoResponse + use<> {
         match self {
             Self::Props(Props {
                 data,
                 http_code,
                 cookies,
+                headers,
+                head,
             }) => (
-                *http_code,
-                cookies.clone(),
-                Json(JsonResponse::new(data.as_ref())),
+                http_code,
+                headers,
+                cookies,
+                Json(JsonResponse::new(data.as_ref(), &head)),
             )
                 .into_response(),
             Self::Redirect(destination) => (
                 StatusCode::PERMANENT_REDIRECT,
-                Json(JsonResponse::new_redirect(destination.to_string())),
+                Json(JsonResponse::new_redirect(destination)),
+            )
+                .into_response(),
+            Self::TemporaryRedirect(destination) => (
+                StatusCode::TEMPORARY_REDIRECT,
+                Json(JsonResponse::new_redirect(destination)),
             )
                 .into_response(),
-            // Custom never needs the "data" response since its scope
-            // is outside the react domain
-            Self::Custom(_) => (StatusCode::OK, Json("{}")).into_response(),
+            Self::SeeOther(destination) => (
+                StatusCode::SEE_OTHER,
+                Json(JsonResponse::new_redirect(destination)),
+            )
+                .into_response(),
+            // Custom, stream and file responses are outside the react domain.
+            // The body is dropped and the client is asked to load the route
+            // from the browser.
+            Self::Custom(response) => {
+                (response.status(), Json(JsonResponse::new_reload())).into_response()
+            }
+            Self::Stream(response) => {
+                (response.http_code, Json(JsonResponse::new_reload())).into_response()
+            }
+            Self::File(response) => {
+                (response.http_code, Json(JsonResponse::new_reload())).into_response()
+            }
         }
     }
 }
@@ -134,6 +397,13 @@ impl Response {
 mod tests {
     use super::*;
 
+    async fn body_to_string(response: AxumResponse) -> String {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
     #[test]
     fn should_update_the_props_status_and_cookie() {
         let mut props = Props::new("{}");
@@ -157,4 +427,157 @@ mod tests {
             &Cookie::new("test", "cookie")
         );
     }
+
+    #[test]
+    fn should_sign_and_encrypt_the_props_cookies() {
+        let mut props = Props::new("{}");
+        props.add_signed_cookie(Cookie::new("signed", "value"));
+        props.add_private_cookie(Cookie::new("private", "value"));
+
+        let signed = props.cookies.get("signed").unwrap().value();
+        assert!(signed.len() > "value".len() && signed.ends_with("value"));
+
+        let private = props.cookies.get("private").unwrap().value();
+        assert!(!private.contains("value"));
+    }
+
+    #[tokio::test]
+    async fn should_add_the_props_headers_to_the_json_response() {
+        let mut props = Props::new("{}");
+        props.add_header(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+
+        let response = Response::Props(props).json().into_response();
+
+        assert_eq!(response.headers()[header::CACHE_CONTROL], "no-store");
+        assert_eq!(
+            body_to_string(response).await,
+            "{\"data\":\"{}\",\"info\":{\"redirect_destination\":null}}"
+        );
+    }
+
+    #[tokio::test]
+    async fn should_add_the_head_to_the_json_response() {
+        let mut props = Props::new("{}");
+        props.head(Head::new().title("Post"));
+
+        let response = Response::Props(props).json().into_response();
+
+        assert_eq!(
+            body_to_string(response).await,
+            "{\"data\":\"{}\",\"info\":{\"redirect_destination\":null},\"head\":{\"title\":\"Post\"}}"
+        );
+    }
+
+    #[tokio::test]
+    async fn should_use_the_redirect_status_code() {
+        let cases = [
+            (
+                Response::Redirect("/a".to_string()),
+                StatusCode::PERMANENT_REDIRECT,
+            ),
+            (
+                Response::TemporaryRedirect("/a".to_string()),
+                StatusCode::TEMPORARY_REDIRECT,
+            ),
+            (Response::SeeOther("/a".to_string()), StatusCode::SEE_OTHER),
+        ];
+
+        for (response, status) in cases {
+            let response = response.json().into_response();
+            assert_eq!(response.status(), status);
+            assert_eq!(
+                body_to_string(response).await,
+                "{\"data\":null,\"info\":{\"redirect_destination\":\"/a\"}}"
+            );
+        }
+
+        let response = Response::SeeOther("/a".to_string())
+            .render_to_string(Request::new(
+                "/".parse().unwrap(),
+                HeaderMap::new(),
+                Default::default(),
+                None,
+            ))
+            .into_response();
+        assert_eq!(response.status(), StatusCode::SEE_OTHER);
+        assert_eq!(response.headers()[header::LOCATION], "/a");
+    }
+
+    #[tokio::test]
+    async fn should_ask_the_client_to_reload_non_props_responses() {
+        let custom = Response::custom((StatusCode::IM_A_TEAPOT, "teapot"));
+        let response = custom.json().into_response();
+        assert_eq!(response.status(), StatusCode::IM_A_TEAPOT);
+        assert_eq!(
+            body_to_string(response).await,
+            "{\"data\":null,\"info\":{\"redirect_destination\":null,\"reload\":true}}"
+        );
+
+        let file = Response::File(FileResponse::new("report.csv", "a,b"));
+        let response = file.json().into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(body_to_string(response).await.contains("\"reload\":true"));
+
+        // The status of the stream and file responses is kept
+        let mut file = FileResponse::new("report.csv", "a,b");
+        file.status(StatusCode::NOT_FOUND);
+        let response = Response::File(file).json().into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let mut stream = StreamResponse::new(futures_util::stream::empty::<
+            Result<Bytes, std::convert::Infallible>,
+        >());
+        stream.status(StatusCode::GONE);
+        let response = Response::Stream(stream).json().into_response();
+        assert_eq!(response.status(), StatusCode::GONE);
+    }
+
+    #[tokio::test]
+    async fn should_render_a_file_download() {
+        let response = Response::File(FileResponse::new("report.csv", "a,b"))
+            .render_to_string(Request::new(
+                "/".parse().unwrap(),
+                HeaderMap::new(),
+                Default::default(),
+                None,
+            ))
+            .into_response();
+
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "text/csv");
+        assert_eq!(
+            response.headers()[header::CONTENT_DISPOSITION],
+            "attachment; filename=\"report.csv\""
+        );
+        assert_eq!(body_to_string(response).await, "a,b");
+    }
+
+    #[test]
+    fn should_encode_non_ascii_file_names() {
+        let mut file = FileResponse::new("résumé \"final\".pdf", "");
+        file.inline();
+
+        assert_eq!(
+            file.content_disposition(),
+            "inline; filename=\"r_sum_ _final_.pdf\"; filename*=UTF-8''r%C3%A9sum%C3%A9%20%22final%22.pdf"
+        );
+    }
+
+    #[tokio::test]
+    async fn should_stream_the_body() {
+        let chunks: Vec<Result<&'static str, io::Error>> = vec![Ok("hello "), Ok("world")];
+        let mut stream = StreamResponse::new(futures_util::stream::iter(chunks));
+        stream.add_header(header::CONTENT_TYPE, HeaderValue::from_static("text/plain"));
+
+        let response = Response::Stream(stream)
+            .render_to_string(Request::new(
+                "/".parse().unwrap(),
+                HeaderMap::new(),
+                Default::default(),
+                None,
+            ))
+            .into_response();
+
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "text/plain");
+        assert_eq!(body_to_string(response).await, "hello world");
+    }
 }
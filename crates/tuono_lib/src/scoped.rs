@@ -0,0 +1,196 @@
+use crate::Request;
+use axum::http::StatusCode;
+use std::sync::Arc;
+use tokio::sync::{MappedMutexGuard, Mutex, MutexGuard};
+
+/// The outcome of the request handled with the request-scoped resources
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Outcome {
+    status: StatusCode,
+}
+
+impl Outcome {
+    pub fn new(status: StatusCode) -> Self {
+        Outcome { status }
+    }
+
+    /// The status of the response sent to the client
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// `false` when the handler returned a client or server error
+    /// (e.g. the transaction should be rolled back).
+    pub fn is_success(&self) -> bool {
+        !self.status.is_client_error() && !self.status.is_server_error()
+    }
+}
+
+/// A resource living as long as a single request
+/// (e.g. a database transaction or the tenant of the request).
+///
+/// Declare it as a `Scoped<T>` argument of a `handler` or `api` function.
+/// The application state is available with `Request::app_state`.
+///
+/// ```ignore
+/// impl RequestScoped for Transaction {
+///     type Error = sqlx::Error;
+///
+///     async fn create(req: &Request) -> Result<Self, Self::Error> {
+///         let state = req.app_state::<ApplicationState>().unwrap();
+///         Ok(Transaction(state.db.begin().await?))
+///     }
+///
+///     async fn finalize(self, outcome: &Outcome) {
+///         if outcome.is_success() {
+///             let _ = self.0.commit().await;
+///         }
+///     }
+/// }
+/// ```
+pub trait RequestScoped: Sized + Send + 'static {
+    type Error: Send;
+
+    /// Create the resource the first time the handler uses it
+    fn create(req: &Request) -> impl Future<Output = Result<Self, Self::Error>> + Send;
+
+    /// Called once the handler returned, only when the resource was created
+    fn finalize(self, outcome: &Outcome) -> impl Future<Output = ()> + Send {
+        let _ = outcome;
+        async {}
+    }
+}
+
+struct ScopedInner<T> {
+    req: Request,
+    value: Mutex<Option<T>>,
+}
+
+/// A request-scoped resource created lazily with `RequestScoped::create`
+/// and finalized by the server after the handler returned.
+pub struct Scoped<T> {
+    inner: Arc<ScopedInner<T>>,
+}
+
+impl<T> Clone for Scoped<T> {
+    fn clone(&self) -> Self {
+        Scoped {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl<T: RequestScoped> Scoped<T> {
+    #[doc(hidden)]
+    pub fn tuono_internal_new(req: Request) -> Self {
+        Scoped {
+            inner: Arc::new(ScopedInner {
+                req,
+                value: Mutex::new(None),
+            }),
+        }
+    }
+
+    /// Get the resource, creating it on the first call.
+    /// A failed creation is retried on the next call.
+    pub async fn get(&self) -> Result<MappedMutexGuard<'_, T>, T::Error> {
+        let mut value = self.inner.value.lock().await;
+
+        if value.is_none() {
+            *value = Some(T::create(&self.inner.req).await?);
+        }
+
+        Ok(MutexGuard::map(value, |value| {
+            value.as_mut().expect("The resource has just been created")
+        }))
+    }
+
+    #[doc(hidden)]
+    pub async fn tuono_internal_finalize(self, outcome: &Outcome) {
+        let value = self.inner.value.lock().await.take();
+
+        if let Some(value) = value {
+            value.finalize(outcome).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static CREATED: AtomicUsize = AtomicUsize::new(0);
+    static COMMITTED: AtomicUsize = AtomicUsize::new(0);
+
+    struct Tenant(String);
+
+    impl RequestScoped for Tenant {
+        type Error = ();
+
+        async fn create(req: &Request) -> Result<Self, Self::Error> {
+            CREATED.fetch_add(1, Ordering::SeqCst);
+            req.params.get("tenant").cloned().map(Tenant).ok_or(())
+        }
+
+        async fn finalize(self, outcome: &Outcome) {
+            if outcome.is_success() {
+                COMMITTED.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+    }
+
+    fn request(params: &[(&str, &str)]) -> Request {
+        Request::new(
+            "/".parse().unwrap(),
+            Default::default(),
+            params
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect::<HashMap<_, _>>(),
+            None,
+        )
+    }
+
+    #[tokio::test]
+    async fn it_creates_the_resource_lazily_and_finalizes_it() {
+        let tenant = Scoped::<Tenant>::tuono_internal_new(request(&[("tenant", "tuono")]));
+        let created = CREATED.load(Ordering::SeqCst);
+
+        assert_eq!(tenant.get().await.unwrap().0, "tuono");
+        assert_eq!(tenant.clone().get().await.unwrap().0, "tuono");
+        assert_eq!(CREATED.load(Ordering::SeqCst), created + 1);
+
+        let committed = COMMITTED.load(Ordering::SeqCst);
+        tenant
+            .tuono_internal_finalize(&Outcome::new(StatusCode::OK))
+            .await;
+        assert_eq!(COMMITTED.load(Ordering::SeqCst), committed + 1);
+    }
+
+    #[tokio::test]
+    async fn it_skips_the_finalization_of_unused_resources() {
+        let committed = COMMITTED.load(Ordering::SeqCst);
+
+        let failed = Scoped::<Tenant>::tuono_internal_new(request(&[]));
+        assert!(failed.get().await.is_err());
+        failed
+            .tuono_internal_finalize(&Outcome::new(StatusCode::OK))
+            .await;
+
+        let unused = Scoped::<Tenant>::tuono_internal_new(request(&[("tenant", "tuono")]));
+        unused
+            .tuono_internal_finalize(&Outcome::new(StatusCode::OK))
+            .await;
+
+        assert_eq!(COMMITTED.load(Ordering::SeqCst), committed);
+    }
+
+    #[test]
+    fn it_reads_the_outcome() {
+        assert!(Outcome::new(StatusCode::SEE_OTHER).is_success());
+        assert!(!Outcome::new(StatusCode::UNPROCESSABLE_ENTITY).is_success());
+        assert!(!Outcome::new(StatusCode::INTERNAL_SERVER_ERROR).is_success());
+    }
+}
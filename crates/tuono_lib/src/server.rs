@@ -1,31 +1,147 @@
 use crate::config::GLOBAL_CONFIG;
 use crate::manifest::load_manifest;
 use crate::mode::{GLOBAL_MODE, Mode};
+use axum::ServiceExt;
+use axum::extract::{OriginalUri, Request};
+use axum::http::Uri;
 use axum::routing::{Router, get};
+use axum::serve::ListenerExt;
 use colored::Colorize;
+use futures_util::future::BoxFuture;
 use ssr_rs::Ssr;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use tower::{Layer, ServiceBuilder};
 use tower_http::services::ServeDir;
-use tuono_internal::config::Config;
+use tuono_internal::config::{Config, MetricsConfig};
+use tuono_internal::env::load_env_vars;
 use tuono_internal::tuono_println;
 
-use crate::env::load_env_vars;
+use crate::auth::Authenticator;
+use crate::cookies::load_cookie_key;
+use crate::health::{Health, HealthChecks};
+use crate::jobs::Jobs;
+use crate::metrics;
+use crate::session::{session_store, spawn_purge_task};
+use crate::socket::{self, Socket};
+use crate::telemetry;
+use crate::tls::{self, ServerListener, Tls};
 use crate::{
-    catch_all::catch_all, services::logger::LoggerLayer, vite_reverse_proxy::vite_reverse_proxy,
+    catch_all::catch_all, openapi::openapi_document, services::auth::AuthLayer,
+    services::cors::CorsLayer, services::csrf::CsrfLayer, services::i18n::I18nLayer,
+    services::limits::LimitsLayer, services::logger::LoggerLayer,
+    services::redirects::RedirectsLayer, services::session::SessionLayer,
+    services::trace::TraceLayer, vite_reverse_proxy::vite_reverse_proxy,
     vite_websocket_proxy::vite_websocket_proxy,
 };
 
 const DEV_PUBLIC_DIR: &str = "public";
 const PROD_PUBLIC_DIR: &str = "out/client";
+/// How long the open connections and the running jobs
+/// are awaited after the shutdown signal
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
 
 pub fn tuono_internal_init_v8_platform() {
     Ssr::create_platform();
 }
 
+/// Stop the server when the `main` function of `src/app.rs`
+/// fails to create the `ApplicationState`.
+#[doc(hidden)]
+pub fn tuono_internal_app_state_failed(err: impl std::fmt::Display) -> ! {
+    tuono_println!(
+        "Failed to create the application state: {}",
+        err.to_string().red()
+    );
+    std::process::exit(1);
+}
+
+/// Resolves on `ctrl+c` or `SIGTERM`
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if tokio::signal::ctrl_c().await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{SignalKind, signal};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                terminate.recv().await;
+            }
+            Err(_) => std::future::pending::<()>().await,
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
+
+/// Remove the application base path from the requests
+/// of the public files
+fn strip_base_path(base_path: String) -> impl Fn(Request) -> Request + Clone + Send + Sync {
+    move |mut req: Request| {
+        if base_path.is_empty() {
+            return req;
+        }
+
+        let path = match req.uri().path().strip_prefix(base_path.as_str()) {
+            Some("") => "/",
+            Some(path) if path.starts_with('/') => path,
+            _ => return req,
+        };
+
+        let path_and_query = match req.uri().query() {
+            Some(query) => format!("{path}?{query}"),
+            None => path.to_string(),
+        };
+
+        let mut parts = req.uri().clone().into_parts();
+        parts.path_and_query = path_and_query.parse().ok();
+
+        if let Ok(uri) = Uri::from_parts(parts) {
+            *req.uri_mut() = uri;
+        }
+
+        req
+    }
+}
+
+/// The pages rendered when no public file matches
+/// get the URI requested by the client
+fn restore_original_uri(mut req: Request) -> Request {
+    if let Some(OriginalUri(uri)) = req.extensions().get::<OriginalUri>().cloned() {
+        *req.uri_mut() = uri;
+    }
+    req
+}
+
 #[derive(Debug)]
 pub struct Server {
     router: Router,
     mode: Mode,
-    pub listener: tokio::net::TcpListener,
+    redirects: RedirectsLayer,
+    i18n: I18nLayer,
+    auth: AuthLayer,
+    session: SessionLayer,
+    csrf: CsrfLayer,
+    cors: CorsLayer,
+    limits: LimitsLayer,
+    jobs: Jobs,
+    health: Health,
+    metrics: MetricsConfig,
+    tls: Option<Tls>,
+    base_path: String,
+    pub listener: Socket,
     pub address: String,
     pub origin: Option<String>,
 }
@@ -34,7 +150,8 @@ impl Server {
     fn display_start_message(&self) {
         // Format the server address as a valid URL so that it becomes clickable in the CLI
         // @see https://github.com/tuono-labs/tuono/issues/460
-        let server_base_url = format!("http://{}", self.address);
+        let scheme = if self.tls.is_some() { "https" } else { "http" };
+        let server_base_url = format!("{scheme}://{}", self.address);
 
         // In order to avoid multiple logs on `tuono dev`
         // the server address prompt for tuono dev is made on the CLI process
@@ -49,7 +166,19 @@ impl Server {
     }
 
     pub async fn init(router: Router, mode: Mode) -> Server {
-        let config = Config::get().expect("[SERVER] Failed to load config");
+        unsafe {
+            // This function is unsafe because it modifies the OS env variables
+            // which is not thread-safe.
+            // However, we are using it in a controlled environment which hasn't
+            // spawned any threads yet.
+            // The env files are loaded first since they can override the config.
+            load_env_vars(mode.env_name());
+        }
+
+        let config = Config::get().unwrap_or_else(|err| {
+            tuono_println!("Failed to load the config: {}", err.to_string().red());
+            std::process::exit(1);
+        });
 
         let _ = GLOBAL_MODE.set(mode);
         let _ = GLOBAL_CONFIG.set(config.clone());
@@ -60,58 +189,269 @@ impl Server {
             }
         }
 
-        let server_address = format!("{}:{}", config.server.host, config.server.port);
+        let listener = socket::bind(&config.server)
+            .await
+            .unwrap_or_else(|err| panic!("[SERVER] {err}"));
+        let server_address = listener.display_address(&config.server);
 
-        unsafe {
-            // This function is unsafe because it modifies the OS env variables
-            // which is not thread-safe.
-            // However, we are using it in a controlled environment which hasn't
-            // spawned any threads yet.
-            load_env_vars(mode);
+        if config.server.tls.is_some() && !matches!(listener, Socket::Tcp(_)) {
+            panic!("[SERVER] TLS is only supported on TCP sockets");
         }
 
+        load_cookie_key(mode);
+        telemetry::init(&config.telemetry);
+
+        let tls = match &config.server.tls {
+            Some(tls) => Some(
+                Tls::init(tls, &config.server.host)
+                    .await
+                    .unwrap_or_else(|err| panic!("[SERVER] {err}")),
+            ),
+            None => None,
+        };
+
+        let store = session_store(&config.session);
+        spawn_purge_task(store.clone());
+
         Server {
             router,
             mode,
-            address: server_address.clone(),
+            redirects: RedirectsLayer::new(
+                &config.redirects,
+                &config.rewrites,
+                &config.i18n,
+                &config.base_path,
+            ),
+            i18n: I18nLayer::new(&config.i18n, &config.base_path),
+            auth: AuthLayer::default(),
+            session: SessionLayer::new(store, &config.session, mode == Mode::Prod),
+            csrf: CsrfLayer::new(&config.csrf, &config.base_path, mode == Mode::Prod),
+            cors: CorsLayer::new(&config.cors, &config.base_path),
+            limits: LimitsLayer::from_config(&config.limits, &config.base_path),
+            jobs: Jobs::default(),
+            health: Health::new(&config.health, mode),
+            metrics: config.metrics.clone(),
+            tls,
+            base_path: config.base_path.clone(),
+            address: server_address,
             origin: config.server.origin.clone(),
-            listener: tokio::net::TcpListener::bind(&server_address)
-                .await
-                .expect("[SERVER] Failed to bind to address"),
+            listener,
         }
     }
 
+    /// Resolve the user of each request with the `authenticate` hook.
+    /// The user is then available with `Request::user`.
+    pub fn with_authenticator<F, Fut, U>(mut self, authenticate: F) -> Server
+    where
+        F: Fn(crate::Request) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Option<U>> + Send + 'static,
+        U: Send + Sync + 'static,
+    {
+        self.auth = AuthLayer::new(Authenticator::new(authenticate));
+        self
+    }
+
+    /// Start the background jobs alongside the HTTP listener
+    pub fn with_jobs(mut self, jobs: Jobs) -> Server {
+        self.jobs = jobs;
+        self
+    }
+
+    /// Register the readiness checks of the application
+    pub fn with_health_checks(mut self, checks: HealthChecks) -> Server {
+        self.health.with_checks(checks);
+        self
+    }
+
     pub async fn start(self) {
         self.display_start_message();
 
-        if self.mode == Mode::Dev {
-            let router = self
-                .router
+        // `Router::layer` doesn't wrap the fallback set afterwards: the pages
+        // without handler need the same layers of the routes.
+        let catch_all = get(catch_all).layer((
+            TraceLayer::new(),
+            LoggerLayer::new(),
+            self.limits.clone(),
+            self.csrf.clone(),
+            self.session.clone(),
+            self.auth.clone(),
+        ));
+
+        let router = if self.mode == Mode::Dev {
+            self.router
                 .to_owned()
+                .layer(self.auth.clone())
+                .layer(self.session.clone())
+                .layer(self.csrf.clone())
+                .layer(self.limits.clone())
                 .layer(LoggerLayer::new())
-                .route("/vite-server/", get(vite_websocket_proxy))
-                .route("/vite-server/{*path}", get(vite_reverse_proxy))
+                .layer(TraceLayer::new())
+                .route(
+                    &format!("{}/__tuono/openapi.json", self.base_path),
+                    get(openapi_document),
+                )
+                .route(
+                    &format!("{}/vite-server/", self.base_path),
+                    get(vite_websocket_proxy),
+                )
+                .route(
+                    &format!("{}/vite-server/{{*path}}", self.base_path),
+                    get(vite_reverse_proxy),
+                )
                 .fallback_service(
-                    ServeDir::new(DEV_PUBLIC_DIR)
-                        .fallback(get(catch_all).layer(LoggerLayer::new())),
-                );
-
-            axum::serve(self.listener, router)
-                .await
-                .expect("Failed to serve development server");
+                    ServiceBuilder::new()
+                        .map_request(strip_base_path(self.base_path.clone()))
+                        .service(
+                            ServeDir::new(DEV_PUBLIC_DIR).fallback(
+                                ServiceBuilder::new()
+                                    .map_request(restore_original_uri)
+                                    .service(catch_all.clone()),
+                            ),
+                        ),
+                )
         } else {
-            let router = self
-                .router
+            self.router
                 .to_owned()
+                .layer(self.auth.clone())
+                .layer(self.session.clone())
+                .layer(self.csrf.clone())
+                .layer(self.limits.clone())
                 .layer(LoggerLayer::new())
+                .layer(TraceLayer::new())
                 .fallback_service(
-                    ServeDir::new(PROD_PUBLIC_DIR)
-                        .fallback(get(catch_all).layer(LoggerLayer::new())),
-                );
+                    ServiceBuilder::new()
+                        .map_request(strip_base_path(self.base_path.clone()))
+                        .service(
+                            ServeDir::new(PROD_PUBLIC_DIR).fallback(
+                                ServiceBuilder::new()
+                                    .map_request(restore_original_uri)
+                                    .service(catch_all),
+                            ),
+                        ),
+                )
+        };
+
+        let router = self.health.clone().routes(router);
+        let router = metrics::routes(&self.metrics, router);
+
+        // The redirects wrap the router since they have to be
+        // applied before the route matching.
+        // The locale prefix is removed after the redirects so that
+        // they can target a specific locale (data routes included).
+        // CORS wraps everything so that redirects get the CORS headers too.
+        let app = self
+            .cors
+            .layer(self.redirects.layer(self.i18n.layer(router)));
+
+        let shutdown = CancellationToken::new();
+        let jobs = (!self.jobs.is_empty()).then(|| self.jobs.start(&shutdown));
+
+        let signal = shutdown.clone();
+        tokio::spawn(async move {
+            shutdown_signal().await;
+            signal.cancel();
+        });
+
+        let server: BoxFuture<'static, std::io::Result<()>> = match self.listener {
+            Socket::Tcp(listener) => {
+                let acceptor = self.tls.map(|tls| {
+                    if let Some(redirect) = tls.redirect {
+                        let https_port = listener
+                            .local_addr()
+                            .expect("[SERVER] Failed to read the server address")
+                            .port();
+                        tls::spawn_redirect(redirect, https_port, shutdown.clone());
+                    }
+                    tls.acceptor
+                });
+
+                let listener = ServerListener::new(listener, acceptor).tap_io(tls::set_nodelay);
 
-            axum::serve(self.listener, router)
-                .await
-                .expect("Failed to serve production server");
+                Box::pin(
+                    axum::serve(
+                        listener,
+                        ServiceExt::<Request>::into_make_service_with_connect_info::<SocketAddr>(
+                            app,
+                        ),
+                    )
+                    .with_graceful_shutdown(shutdown.clone().cancelled_owned())
+                    .into_future(),
+                )
+            }
+            #[cfg(unix)]
+            Socket::Unix(listener, file) => {
+                let server = axum::serve(listener, ServiceExt::<Request>::into_make_service(app))
+                    .with_graceful_shutdown(shutdown.clone().cancelled_owned());
+
+                Box::pin(async move {
+                    // The socket file is removed once the server stops
+                    let _file = file;
+                    server.await
+                })
+            }
+        };
+
+        // Long-lived connections (i.e. WebSockets) would hold the shutdown forever
+        let grace_period = async {
+            shutdown.cancelled().await;
+            tokio::time::sleep(SHUTDOWN_TIMEOUT).await;
+        };
+
+        tokio::select! {
+            result = server => result.expect(if self.mode == Mode::Dev {
+                "Failed to serve development server"
+            } else {
+                "Failed to serve production server"
+            }),
+            _ = grace_period => tuono_println!(
+                "Closing the connections still open after {}s",
+                SHUTDOWN_TIMEOUT.as_secs()
+            ),
         }
+
+        if let Some(jobs) = jobs {
+            jobs.stop(SHUTDOWN_TIMEOUT).await;
+        }
+
+        telemetry::flush().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+
+    fn stripped_uri(base_path: &str, uri: &str) -> String {
+        let req = Request::builder().uri(uri).body(Body::empty()).unwrap();
+        strip_base_path(base_path.to_string())(req)
+            .uri()
+            .to_string()
+    }
+
+    #[test]
+    fn it_strips_the_base_path_from_the_public_files() {
+        assert_eq!(
+            stripped_uri("/shop", "/shop/assets/index.js?v=1"),
+            "/assets/index.js?v=1"
+        );
+        assert_eq!(stripped_uri("/shop", "/shop"), "/");
+        assert_eq!(
+            stripped_uri("/shop", "/shopping/logo.png"),
+            "/shopping/logo.png"
+        );
+        assert_eq!(stripped_uri("", "/favicon.ico"), "/favicon.ico");
+    }
+
+    #[test]
+    fn it_restores_the_original_uri() {
+        let mut req = Request::builder()
+            .uri("/assets/missing.js")
+            .body(Body::empty())
+            .unwrap();
+        req.extensions_mut()
+            .insert(OriginalUri(Uri::from_static("/shop/assets/missing.js")));
+
+        assert_eq!(restore_original_uri(req).uri(), "/shop/assets/missing.js");
     }
 }
@@ -0,0 +1,89 @@
+use crate::auth::Authenticator;
+use axum::body::Body;
+use axum::response::Response;
+use futures_util::future::BoxFuture;
+use http::Request;
+use std::collections::HashMap;
+use std::task::{Context, Poll};
+use tower::{Layer, Service};
+
+/// Resolve the user of each request with the `authenticate`
+/// hook defined in `src/app.rs`.
+///
+/// The hook receives a request without the path parameters since
+/// it runs before the route handler.
+#[derive(Clone, Default)]
+pub struct AuthLayer {
+    authenticator: Option<Authenticator>,
+}
+
+impl std::fmt::Debug for AuthLayer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AuthLayer")
+            .field("enabled", &self.authenticator.is_some())
+            .finish()
+    }
+}
+
+impl AuthLayer {
+    pub(crate) fn new(authenticator: Authenticator) -> Self {
+        AuthLayer {
+            authenticator: Some(authenticator),
+        }
+    }
+}
+
+impl<S> Layer<S> for AuthLayer {
+    type Service = Auth<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Auth {
+            inner,
+            authenticator: self.authenticator.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Auth<S> {
+    inner: S,
+    authenticator: Option<Authenticator>,
+}
+
+impl<S> Service<Request<Body>> for Auth<S>
+where
+    S: Service<Request<Body>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Response, S::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<Body>) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let authenticator = self.authenticator.clone();
+
+        Box::pin(async move {
+            if let Some(authenticator) = authenticator {
+                let tuono_req = crate::Request::new(
+                    req.uri().clone(),
+                    req.headers().clone(),
+                    HashMap::new(),
+                    None,
+                )
+                .with_extensions(req.extensions().clone());
+
+                if let Some(user) = authenticator.authenticate(tuono_req).await {
+                    req.extensions_mut().insert(user);
+                }
+            }
+
+            inner.call(req).await
+        })
+    }
+}
@@ -0,0 +1,392 @@
+use super::path_pattern::{PathPattern, app_path};
+use axum::body::Body;
+use axum::response::Response;
+use futures_util::future::BoxFuture;
+use http::header::{
+    ACCESS_CONTROL_ALLOW_CREDENTIALS, ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_METHODS,
+    ACCESS_CONTROL_ALLOW_ORIGIN, ACCESS_CONTROL_EXPOSE_HEADERS, ACCESS_CONTROL_MAX_AGE,
+    ACCESS_CONTROL_REQUEST_HEADERS, ACCESS_CONTROL_REQUEST_METHOD, ALLOW, ORIGIN, VARY,
+};
+use http::{HeaderMap, HeaderValue, Method, Request, StatusCode};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tower::{Layer, Service};
+use tuono_internal::config::{CorsConfig, CorsRouteConfig};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CorsPolicy {
+    origins: Vec<String>,
+    methods: Vec<String>,
+    headers: Vec<String>,
+    expose_headers: Vec<String>,
+    credentials: bool,
+    max_age: Option<u64>,
+}
+
+impl CorsPolicy {
+    fn from_config(config: &CorsConfig) -> Self {
+        CorsPolicy {
+            origins: config.origins.clone(),
+            methods: config.methods.clone(),
+            headers: config.headers.clone(),
+            expose_headers: config.expose_headers.clone(),
+            credentials: config.credentials,
+            max_age: config.max_age,
+        }
+    }
+
+    fn with_overrides(&self, route: &CorsRouteConfig) -> Self {
+        CorsPolicy {
+            origins: route.origins.clone().unwrap_or(self.origins.clone()),
+            methods: route.methods.clone().unwrap_or(self.methods.clone()),
+            headers: route.headers.clone().unwrap_or(self.headers.clone()),
+            expose_headers: route
+                .expose_headers
+                .clone()
+                .unwrap_or(self.expose_headers.clone()),
+            credentials: route.credentials.unwrap_or(self.credentials),
+            max_age: route.max_age.or(self.max_age),
+        }
+    }
+
+    fn allows_origin(&self, origin: &str) -> bool {
+        self.origins
+            .iter()
+            .any(|allowed| allowed == "*" || allowed == origin)
+    }
+
+    fn allows_method(&self, method: &str) -> bool {
+        self.methods
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(method))
+    }
+
+    /// Headers added to both the preflight and the actual response
+    fn insert_origin_headers(&self, origin: &str, headers: &mut HeaderMap) {
+        // The wildcard can't be used with credentials
+        let allow_origin = if self.origins.iter().any(|allowed| allowed == "*") && !self.credentials
+        {
+            HeaderValue::from_static("*")
+        } else {
+            match HeaderValue::from_str(origin) {
+                Ok(origin) => origin,
+                Err(_) => return,
+            }
+        };
+
+        headers.insert(ACCESS_CONTROL_ALLOW_ORIGIN, allow_origin);
+        headers.append(VARY, HeaderValue::from_static("origin"));
+
+        if self.credentials {
+            headers.insert(
+                ACCESS_CONTROL_ALLOW_CREDENTIALS,
+                HeaderValue::from_static("true"),
+            );
+        }
+    }
+
+    fn preflight_response(&self, origin: &str, request_headers: Option<&HeaderValue>) -> Response {
+        let mut response = Response::new(Body::empty());
+        *response.status_mut() = StatusCode::NO_CONTENT;
+
+        let headers = response.headers_mut();
+        self.insert_origin_headers(origin, headers);
+
+        if let Ok(methods) = HeaderValue::from_str(&self.methods.join(", ")) {
+            headers.insert(ACCESS_CONTROL_ALLOW_METHODS, methods);
+        }
+
+        let allow_headers = if self.headers.is_empty() {
+            request_headers.cloned()
+        } else {
+            HeaderValue::from_str(&self.headers.join(", ")).ok()
+        };
+        if let Some(allow_headers) = allow_headers {
+            headers.insert(ACCESS_CONTROL_ALLOW_HEADERS, allow_headers);
+        }
+
+        if let Some(max_age) = self.max_age {
+            headers.insert(ACCESS_CONTROL_MAX_AGE, HeaderValue::from(max_age));
+        }
+
+        response
+    }
+}
+
+#[derive(Debug)]
+struct CorsPolicies {
+    default: CorsPolicy,
+    routes: Vec<(PathPattern, CorsPolicy)>,
+    /// The route paths are relative to the base path
+    base_path: String,
+}
+
+impl CorsPolicies {
+    fn for_path(&self, path: &str) -> &CorsPolicy {
+        app_path(&self.base_path, path)
+            .and_then(|path| {
+                self.routes
+                    .iter()
+                    .find(|(pattern, _)| pattern.matches(path).is_some())
+            })
+            .map(|(_, policy)| policy)
+            .unwrap_or(&self.default)
+    }
+}
+
+/// Apply the `cors` config section and answer the `OPTIONS` requests.
+///
+/// Preflight requests are answered without reaching the router.
+/// Any other `OPTIONS` request gets the methods allowed by the route.
+#[derive(Debug, Clone)]
+pub struct CorsLayer {
+    policies: Arc<CorsPolicies>,
+}
+
+impl CorsLayer {
+    pub fn new(config: &CorsConfig, base_path: &str) -> Self {
+        let default = CorsPolicy::from_config(config);
+        let routes = config
+            .routes
+            .iter()
+            .map(|route| (PathPattern::new(&route.path), default.with_overrides(route)))
+            .collect();
+
+        CorsLayer {
+            policies: Arc::new(CorsPolicies {
+                default,
+                routes,
+                base_path: base_path.to_string(),
+            }),
+        }
+    }
+}
+
+impl<S> Layer<S> for CorsLayer {
+    type Service = Cors<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Cors {
+            inner,
+            policies: Arc::clone(&self.policies),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Cors<S> {
+    inner: S,
+    policies: Arc<CorsPolicies>,
+}
+
+impl<S> Service<Request<Body>> for Cors<S>
+where
+    S: Service<Request<Body>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Response, S::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let policies = Arc::clone(&self.policies);
+
+        Box::pin(async move {
+            let policy = policies.for_path(req.uri().path());
+            let origin = req
+                .headers()
+                .get(ORIGIN)
+                .and_then(|origin| origin.to_str().ok())
+                .filter(|origin| policy.allows_origin(origin))
+                .map(String::from);
+
+            let is_options = req.method() == Method::OPTIONS;
+
+            if is_options
+                && let Some(requested_method) = req.headers().get(ACCESS_CONTROL_REQUEST_METHOD)
+            {
+                return Ok(match &origin {
+                    Some(origin)
+                        if requested_method
+                            .to_str()
+                            .is_ok_and(|method| policy.allows_method(method)) =>
+                    {
+                        policy.preflight_response(
+                            origin,
+                            req.headers().get(ACCESS_CONTROL_REQUEST_HEADERS),
+                        )
+                    }
+                    // Without the CORS headers the browser blocks the request
+                    _ => {
+                        let mut response = Response::new(Body::empty());
+                        *response.status_mut() = StatusCode::NO_CONTENT;
+                        response
+                    }
+                });
+            }
+
+            let mut response = inner.call(req).await?;
+
+            // Routes don't declare an OPTIONS handler: the router
+            // answers 405 listing the methods of the route
+            if is_options && response.status() == StatusCode::METHOD_NOT_ALLOWED {
+                let allow = response.headers().get(ALLOW).cloned();
+                response = Response::new(Body::empty());
+                *response.status_mut() = StatusCode::NO_CONTENT;
+                if let Some(allow) = allow {
+                    response.headers_mut().insert(ALLOW, allow);
+                }
+            }
+
+            if let Some(origin) = &origin {
+                let headers = response.headers_mut();
+                policy.insert_origin_headers(origin, headers);
+
+                if !policy.expose_headers.is_empty()
+                    && let Ok(expose_headers) =
+                        HeaderValue::from_str(&policy.expose_headers.join(", "))
+                {
+                    headers.insert(ACCESS_CONTROL_EXPOSE_HEADERS, expose_headers);
+                }
+            }
+
+            Ok(response)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::Infallible;
+    use tower::ServiceExt;
+    use tower::service_fn;
+
+    fn config() -> CorsConfig {
+        CorsConfig {
+            origins: vec!["https://m.tuono.dev".to_string()],
+            expose_headers: vec!["x-total-count".to_string()],
+            max_age: Some(600),
+            routes: vec![CorsRouteConfig {
+                path: "/api/public/{*path}".to_string(),
+                origins: Some(vec!["*".to_string()]),
+                methods: Some(vec!["GET".to_string()]),
+                ..CorsRouteConfig::default()
+            }],
+            ..CorsConfig::default()
+        }
+    }
+
+    async fn call(req: Request<Body>) -> Response {
+        let service =
+            CorsLayer::new(&config(), "").layer(service_fn(|req: Request<Body>| async move {
+                let mut response = Response::new(Body::empty());
+                if req.method() != Method::GET {
+                    *response.status_mut() = StatusCode::METHOD_NOT_ALLOWED;
+                    response
+                        .headers_mut()
+                        .insert(ALLOW, HeaderValue::from_static("GET,HEAD"));
+                }
+                Ok::<_, Infallible>(response)
+            }));
+
+        service.oneshot(req).await.unwrap()
+    }
+
+    fn preflight(path: &str, origin: &str, method: &str) -> Request<Body> {
+        Request::builder()
+            .method(Method::OPTIONS)
+            .uri(path)
+            .header(ORIGIN, origin)
+            .header(ACCESS_CONTROL_REQUEST_METHOD, method)
+            .header(ACCESS_CONTROL_REQUEST_HEADERS, "content-type")
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_answers_the_preflight_requests() {
+        let response = call(preflight("/api/users", "https://m.tuono.dev", "POST")).await;
+
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        let headers = response.headers();
+        assert_eq!(headers[ACCESS_CONTROL_ALLOW_ORIGIN], "https://m.tuono.dev");
+        assert_eq!(
+            headers[ACCESS_CONTROL_ALLOW_METHODS],
+            "GET, HEAD, POST, PUT, PATCH, DELETE"
+        );
+        assert_eq!(headers[ACCESS_CONTROL_ALLOW_HEADERS], "content-type");
+        assert_eq!(headers[ACCESS_CONTROL_MAX_AGE], "600");
+    }
+
+    #[tokio::test]
+    async fn it_does_not_allow_unknown_origins_and_methods() {
+        let response = call(preflight("/api/users", "https://evil.dev", "POST")).await;
+        assert!(!response.headers().contains_key(ACCESS_CONTROL_ALLOW_ORIGIN));
+
+        let response = call(preflight("/api/public/users", "https://evil.dev", "POST")).await;
+        assert!(!response.headers().contains_key(ACCESS_CONTROL_ALLOW_ORIGIN));
+    }
+
+    #[tokio::test]
+    async fn it_applies_the_route_overrides() {
+        let response = call(preflight("/api/public/users", "https://evil.dev", "GET")).await;
+
+        assert_eq!(response.headers()[ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+        assert_eq!(response.headers()[ACCESS_CONTROL_ALLOW_METHODS], "GET");
+    }
+
+    #[test]
+    fn the_route_paths_are_relative_to_the_base_path() {
+        let layer = CorsLayer::new(&config(), "/shop");
+        let policies = &layer.policies;
+        let route_policy = &policies.routes[0].1;
+
+        assert!(std::ptr::eq(
+            policies.for_path("/shop/api/public/users"),
+            route_policy
+        ));
+        assert!(std::ptr::eq(
+            policies.for_path("/api/public/users"),
+            &policies.default
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_adds_the_cors_headers_to_the_actual_response() {
+        let response = call(
+            Request::builder()
+                .uri("/api/users")
+                .header(ORIGIN, "https://m.tuono.dev")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+
+        let headers = response.headers();
+        assert_eq!(headers[ACCESS_CONTROL_ALLOW_ORIGIN], "https://m.tuono.dev");
+        assert_eq!(headers[ACCESS_CONTROL_EXPOSE_HEADERS], "x-total-count");
+        assert_eq!(headers[VARY], "origin");
+    }
+
+    #[tokio::test]
+    async fn it_answers_the_options_requests() {
+        let response = call(
+            Request::builder()
+                .method(Method::OPTIONS)
+                .uri("/api/users")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_eq!(response.headers()[ALLOW], "GET,HEAD");
+    }
+}
@@ -0,0 +1,442 @@
+use super::path_pattern::{PathPattern, app_path};
+use crate::cookies::{cookie_key, sign_cookie};
+use axum::body::Body;
+use axum::response::{IntoResponse, Response};
+use axum_extra::extract::cookie::{Cookie, SameSite, SignedCookieJar};
+use futures_util::future::BoxFuture;
+use http::header::{CONTENT_TYPE, SET_COOKIE};
+use http::{HeaderValue, Method, Request, StatusCode};
+use rand::RngCore;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tower::{Layer, Service};
+use tuono_internal::config::CsrfConfig;
+
+/// The token of the current client.
+/// Exposed to React through the hydration payload.
+#[derive(Debug, Clone)]
+pub(crate) struct CsrfToken(pub String);
+
+/// The largest form read to look for the token, as the default body limit.
+const MAX_FORM_SIZE: usize = 2 * 1024 * 1024;
+
+fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::rng().fill_bytes(&mut bytes);
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Compare the tokens in constant time
+fn tokens_match(expected: &str, submitted: &str) -> bool {
+    expected.len() == submitted.len()
+        && expected
+            .bytes()
+            .zip(submitted.bytes())
+            .fold(0, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+fn is_unsafe_method(method: &Method) -> bool {
+    !matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE
+    )
+}
+
+struct CsrfSettings {
+    enabled: bool,
+    cookie_name: String,
+    header_name: String,
+    field_name: String,
+    /// The exempt paths are relative to the base path
+    exempt: Vec<PathPattern>,
+    base_path: String,
+    secure: bool,
+}
+
+impl CsrfSettings {
+    fn is_exempt(&self, path: &str) -> bool {
+        app_path(&self.base_path, path).is_some_and(|path| {
+            self.exempt
+                .iter()
+                .any(|pattern| pattern.matches(path).is_some())
+        })
+    }
+
+    fn forbidden(&self, reason: &str) -> Response {
+        (
+            StatusCode::FORBIDDEN,
+            format!(
+                "{reason}: send the token with the `{}` header or the `{}` form field",
+                self.header_name, self.field_name
+            ),
+        )
+            .into_response()
+    }
+
+    fn cookie(&self, token: String) -> HeaderValue {
+        let cookie = sign_cookie(
+            Cookie::build((self.cookie_name.clone(), token))
+                .path("/")
+                .http_only(true)
+                .secure(self.secure)
+                .same_site(SameSite::Lax)
+                .build(),
+        );
+
+        HeaderValue::from_str(&cookie.encoded().to_string())
+            .expect("The CSRF cookie is always a valid header value")
+    }
+}
+
+/// Issue a CSRF token to each client and require it on the
+/// requests using an unsafe method (double submit cookie).
+///
+/// The token is read from the configured header first and from the
+/// url encoded form body otherwise. The body is buffered and handed
+/// back to the route untouched.
+#[derive(Clone)]
+pub struct CsrfLayer {
+    settings: Arc<CsrfSettings>,
+}
+
+impl std::fmt::Debug for CsrfLayer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CsrfLayer")
+            .field("enabled", &self.settings.enabled)
+            .finish()
+    }
+}
+
+impl CsrfLayer {
+    /// `secure` is used when the config doesn't define it
+    pub fn new(config: &CsrfConfig, base_path: &str, secure: bool) -> Self {
+        CsrfLayer {
+            settings: Arc::new(CsrfSettings {
+                enabled: config.enabled,
+                cookie_name: config.cookie_name.clone(),
+                header_name: config.header_name.clone(),
+                field_name: config.field_name.clone(),
+                exempt: config
+                    .exempt
+                    .iter()
+                    .map(|path| PathPattern::new(path))
+                    .collect(),
+                base_path: base_path.to_string(),
+                secure: config.secure.unwrap_or(secure),
+            }),
+        }
+    }
+}
+
+impl<S> Layer<S> for CsrfLayer {
+    type Service = Csrf<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Csrf {
+            inner,
+            settings: Arc::clone(&self.settings),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Csrf<S> {
+    inner: S,
+    settings: Arc<CsrfSettings>,
+}
+
+impl<S> Service<Request<Body>> for Csrf<S>
+where
+    S: Service<Request<Body>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Response, S::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<Body>) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let settings = Arc::clone(&self.settings);
+
+        Box::pin(async move {
+            if !settings.enabled {
+                return inner.call(req).await;
+            }
+
+            let cookie_token = SignedCookieJar::from_headers(req.headers(), cookie_key().clone())
+                .get(&settings.cookie_name)
+                .map(|cookie| cookie.value().to_string());
+
+            if is_unsafe_method(req.method()) && !settings.is_exempt(req.uri().path()) {
+                let Some(expected) = &cookie_token else {
+                    return Ok(settings.forbidden("Missing CSRF token"));
+                };
+
+                let (submitted, checked_req) = match submitted_token(req, &settings).await {
+                    Ok(result) => result,
+                    Err(response) => return Ok(response),
+                };
+                req = checked_req;
+
+                match submitted {
+                    None => return Ok(settings.forbidden("Missing CSRF token")),
+                    Some(submitted) if !tokens_match(expected, &submitted) => {
+                        return Ok(settings.forbidden("Invalid CSRF token"));
+                    }
+                    Some(_) => {}
+                }
+            }
+
+            let (token, is_new) = match cookie_token {
+                Some(token) => (token, false),
+                None => (generate_token(), true),
+            };
+
+            req.extensions_mut().insert(CsrfToken(token.clone()));
+
+            let mut response = inner.call(req).await?;
+
+            if is_new {
+                response
+                    .headers_mut()
+                    .append(SET_COOKIE, settings.cookie(token));
+            }
+
+            Ok(response)
+        })
+    }
+}
+
+/// Read the token from the header or from the url encoded form.
+/// The request is rebuilt since reading the form consumes the body.
+/// Forms larger than `MAX_FORM_SIZE` are rejected with a 413.
+async fn submitted_token(
+    req: Request<Body>,
+    settings: &CsrfSettings,
+) -> Result<(Option<String>, Request<Body>), Response> {
+    if let Some(token) = req
+        .headers()
+        .get(&settings.header_name)
+        .and_then(|value| value.to_str().ok())
+    {
+        return Ok((Some(token.to_string()), req));
+    }
+
+    let is_form = req
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/x-www-form-urlencoded"));
+
+    if !is_form {
+        return Ok((None, req));
+    }
+
+    let (parts, body) = req.into_parts();
+    let Ok(bytes) = axum::body::to_bytes(body, MAX_FORM_SIZE).await else {
+        return Err((
+            StatusCode::PAYLOAD_TOO_LARGE,
+            format!(
+                "The form is too large to look for the CSRF token: send it with the `{}` header",
+                settings.header_name
+            ),
+        )
+            .into_response());
+    };
+
+    let token = serde_urlencoded::from_bytes::<Vec<(String, String)>>(&bytes)
+        .ok()
+        .and_then(|fields| {
+            fields
+                .into_iter()
+                .find(|(name, _)| name == &settings.field_name)
+                .map(|(_, value)| value)
+        });
+
+    Ok((token, Request::from_parts(parts, Body::from(bytes))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::Infallible;
+    use tower::ServiceExt;
+    use tower::service_fn;
+
+    async fn call(config: &CsrfConfig, req: Request<Body>) -> Response {
+        let service =
+            CsrfLayer::new(config, "", false).layer(service_fn(|req: Request<Body>| async move {
+                let token = req.extensions().get::<CsrfToken>().unwrap().0.clone();
+                let body = axum::body::to_bytes(req.into_body(), usize::MAX)
+                    .await
+                    .unwrap();
+                let body = format!("{token}|{}", String::from_utf8_lossy(&body));
+                Ok::<_, Infallible>(Response::new(Body::from(body)))
+            }));
+
+        service.oneshot(req).await.unwrap()
+    }
+
+    async fn body(response: Response) -> String {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    /// Returns the signed cookie and the token
+    async fn issue_token(config: &CsrfConfig) -> (String, String) {
+        let response = call(config, Request::new(Body::empty())).await;
+        let cookie = response.headers()[SET_COOKIE]
+            .to_str()
+            .unwrap()
+            .split(';')
+            .next()
+            .unwrap()
+            .to_string();
+        let body = body(response).await;
+        let (token, _) = body.split_once('|').unwrap();
+        (cookie, token.to_string())
+    }
+
+    fn post(cookie: Option<&str>) -> http::request::Builder {
+        let builder = Request::builder().method(Method::POST).uri("/api/post");
+        match cookie {
+            Some(cookie) => builder.header("cookie", cookie),
+            None => builder,
+        }
+    }
+
+    #[tokio::test]
+    async fn it_issues_a_token_on_safe_requests() {
+        let (cookie, token) = issue_token(&CsrfConfig::default()).await;
+
+        assert!(cookie.starts_with("tuono_csrf="));
+        assert_eq!(token.len(), 64);
+    }
+
+    #[tokio::test]
+    async fn it_rejects_unsafe_requests_without_the_token() {
+        let config = CsrfConfig::default();
+
+        let response = call(&config, post(None).body(Body::empty()).unwrap()).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        assert!(body(response).await.starts_with("Missing CSRF token"));
+
+        let (cookie, _) = issue_token(&config).await;
+        let response = call(
+            &config,
+            post(Some(&cookie))
+                .header("x-csrf-token", "forged")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        assert!(body(response).await.starts_with("Invalid CSRF token"));
+    }
+
+    #[tokio::test]
+    async fn it_accepts_the_token_from_the_header() {
+        let config = CsrfConfig::default();
+        let (cookie, token) = issue_token(&config).await;
+
+        let response = call(
+            &config,
+            post(Some(&cookie))
+                .header("x-csrf-token", &token)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(!response.headers().contains_key(SET_COOKIE));
+    }
+
+    #[tokio::test]
+    async fn it_accepts_the_token_from_the_form_and_keeps_the_body() {
+        let config = CsrfConfig::default();
+        let (cookie, token) = issue_token(&config).await;
+        let form = format!("name=tuono&_csrf={token}");
+
+        let response = call(
+            &config,
+            post(Some(&cookie))
+                .header("content-type", "application/x-www-form-urlencoded")
+                .body(Body::from(form.clone()))
+                .unwrap(),
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body(response).await, format!("{token}|{form}"));
+    }
+
+    #[tokio::test]
+    async fn it_rejects_the_forms_too_large_to_read() {
+        let config = CsrfConfig::default();
+        let (cookie, token) = issue_token(&config).await;
+        let form = format!("name={}&_csrf={token}", "a".repeat(MAX_FORM_SIZE));
+
+        let response = call(
+            &config,
+            post(Some(&cookie))
+                .header("content-type", "application/x-www-form-urlencoded")
+                .body(Body::from(form))
+                .unwrap(),
+        )
+        .await;
+
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn it_skips_the_exempt_paths_and_the_disabled_layer() {
+        let exempt = CsrfConfig {
+            exempt: vec!["/api/{*path}".to_string()],
+            ..CsrfConfig::default()
+        };
+        let response = call(&exempt, post(None).body(Body::empty()).unwrap()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let disabled = CsrfConfig {
+            enabled: false,
+            ..CsrfConfig::default()
+        };
+        let service =
+            CsrfLayer::new(&disabled, "", false).layer(service_fn(|_: Request<Body>| async {
+                Ok::<_, Infallible>(Response::new(Body::empty()))
+            }));
+        let response = service
+            .oneshot(post(None).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn the_exempt_paths_are_relative_to_the_base_path() {
+        let config = CsrfConfig {
+            exempt: vec!["/api/{*path}".to_string()],
+            ..CsrfConfig::default()
+        };
+        let layer = CsrfLayer::new(&config, "/shop", false);
+
+        assert!(layer.settings.is_exempt("/shop/api/webhooks"));
+        assert!(!layer.settings.is_exempt("/api/webhooks"));
+    }
+
+    #[test]
+    fn it_compares_the_tokens() {
+        assert!(tokens_match("abc", "abc"));
+        assert!(!tokens_match("abc", "abd"));
+        assert!(!tokens_match("abc", "abcd"));
+    }
+}
@@ -0,0 +1,307 @@
+use super::path_pattern::{app_path, with_base_path};
+use crate::i18n::{Locale, LocalizedUri, negotiate};
+use axum::body::Body;
+use axum::response::{IntoResponse, Redirect, Response};
+use axum_extra::extract::cookie::CookieJar;
+use futures_util::future::{Either, Ready, ready};
+use http::header::{ACCEPT_LANGUAGE, HOST, VARY};
+use http::uri::Authority;
+use http::{HeaderValue, Method, Request, Uri};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tower::{Layer, Service};
+use tuono_internal::config::I18nConfig;
+
+/// Split the locale prefix from the application `path`
+pub(super) fn split_locale<'a>(config: &I18nConfig, path: &'a str) -> Option<(&'a str, &'a str)> {
+    let segment = path.strip_prefix('/')?.split('/').next()?;
+
+    if !config.locales.iter().any(|locale| locale == segment) {
+        return None;
+    }
+
+    match &path[segment.len() + 1..] {
+        "" => Some((segment, "/")),
+        rest => Some((segment, rest)),
+    }
+}
+
+/// Replace the path of the `uri` keeping the query
+fn with_path(uri: &Uri, path: &str) -> Option<Uri> {
+    let path_and_query = match uri.query() {
+        Some(query) => format!("{path}?{query}"),
+        None => path.to_string(),
+    };
+
+    let mut parts = uri.clone().into_parts();
+    parts.path_and_query = Some(path_and_query.parse().ok()?);
+    Uri::from_parts(parts).ok()
+}
+
+/// The requested host name without the port
+fn host(req: &Request<Body>) -> Option<String> {
+    let host = match req.headers().get(HOST) {
+        Some(host) => host.to_str().ok()?,
+        None => req.uri().authority()?.as_str(),
+    };
+
+    let authority: Authority = host.parse().ok()?;
+    Some(authority.host().to_string())
+}
+
+#[derive(Debug)]
+struct I18n {
+    config: I18nConfig,
+    base_path: String,
+}
+
+impl I18n {
+    /// The redirect to the locale preferred by the client.
+    /// Only the application root is redirected.
+    fn detect(&self, req: &Request<Body>, path: &str) -> Option<Response> {
+        if !self.config.locale_detection || req.method() != Method::GET || path != "/" {
+            return None;
+        }
+
+        let cookies = CookieJar::from_headers(req.headers());
+        let cookie = cookies.get(&self.config.cookie_name).map(|c| c.value());
+        let accept_language = req
+            .headers()
+            .get(ACCEPT_LANGUAGE)
+            .and_then(|header| header.to_str().ok());
+
+        let locale = negotiate(&self.config, cookie, accept_language)?;
+        let prefix = self.config.path_prefix(locale);
+
+        if prefix.is_empty() {
+            return None;
+        }
+
+        let destination = match req.uri().query() {
+            Some(query) => format!("{}{prefix}?{query}", self.base_path),
+            None => format!("{}{prefix}", self.base_path),
+        };
+
+        let mut response = Redirect::temporary(&destination).into_response();
+        response
+            .headers_mut()
+            .insert(VARY, HeaderValue::from_static("Accept-Language, Cookie"));
+
+        Some(response)
+    }
+}
+
+/// Resolve the locale of the requests.
+///
+/// The locale prefix is removed from the path before the route matching
+/// so that every locale is served by the same routes.
+#[derive(Debug, Clone)]
+pub struct I18nLayer {
+    i18n: Arc<I18n>,
+}
+
+impl I18nLayer {
+    pub fn new(config: &I18nConfig, base_path: &str) -> Self {
+        I18nLayer {
+            i18n: Arc::new(I18n {
+                config: config.clone(),
+                base_path: base_path.to_string(),
+            }),
+        }
+    }
+}
+
+impl<S> Layer<S> for I18nLayer {
+    type Service = I18nService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        I18nService {
+            inner,
+            i18n: Arc::clone(&self.i18n),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct I18nService<S> {
+    inner: S,
+    i18n: Arc<I18n>,
+}
+
+impl<S> Service<Request<Body>> for I18nService<S>
+where
+    S: Service<Request<Body>, Response = Response>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Either<Ready<Result<Response, S::Error>>, S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<Body>) -> Self::Future {
+        let i18n = &self.i18n;
+
+        let Some(default_locale) = i18n.config.default_locale() else {
+            return Either::Right(self.inner.call(req));
+        };
+
+        let uri = req.uri().clone();
+        let Some(path) = app_path(&i18n.base_path, uri.path()) else {
+            return Either::Right(self.inner.call(req));
+        };
+
+        let locale = if let Some((locale, path)) = split_locale(&i18n.config, path) {
+            if let Some(unprefixed_uri) = with_path(&uri, &with_base_path(&i18n.base_path, path)) {
+                *req.uri_mut() = unprefixed_uri;
+                req.extensions_mut().insert(LocalizedUri(uri.clone()));
+            }
+
+            Locale {
+                code: locale.to_string(),
+                path_prefix: format!("/{locale}"),
+            }
+        } else if let Some(locale) = host(&req)
+            .as_deref()
+            .and_then(|host| i18n.config.domain_locale(host))
+        {
+            Locale {
+                code: locale.to_string(),
+                path_prefix: String::new(),
+            }
+        } else if let Some(redirect) = i18n.detect(&req, path) {
+            return Either::Left(ready(Ok(redirect)));
+        } else {
+            Locale {
+                code: default_locale.to_string(),
+                path_prefix: String::new(),
+            }
+        };
+
+        req.extensions_mut().insert(locale);
+
+        Either::Right(self.inner.call(req))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::Infallible;
+    use tower::ServiceExt;
+
+    fn config() -> I18nConfig {
+        I18nConfig {
+            locales: vec!["en".to_string(), "it".to_string()],
+            domains: vec![tuono_internal::config::I18nDomainConfig {
+                domain: "tuono.it".to_string(),
+                locale: "it".to_string(),
+            }],
+            ..Default::default()
+        }
+    }
+
+    /// Echo the routed path and the resolved locale
+    async fn echo(req: Request<Body>) -> Result<Response, Infallible> {
+        let locale = req.extensions().get::<Locale>();
+        let original = req.extensions().get::<LocalizedUri>();
+
+        Ok(format!(
+            "{} {} {} {}",
+            req.uri(),
+            locale.map_or("-", |locale| locale.code.as_str()),
+            locale.map_or("-", |locale| locale.path_prefix.as_str()),
+            original.map_or("-".to_string(), |uri| uri.0.to_string()),
+        )
+        .into_response())
+    }
+
+    async fn call(base_path: &str, req: Request<Body>) -> Response {
+        I18nLayer::new(&config(), base_path)
+            .layer(tower::service_fn(echo))
+            .oneshot(req)
+            .await
+            .unwrap()
+    }
+
+    async fn body(response: Response) -> String {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    fn get(uri: &str) -> Request<Body> {
+        Request::get(uri).body(Body::empty()).unwrap()
+    }
+
+    #[test]
+    fn it_splits_the_locale_prefix() {
+        let config = config();
+
+        assert_eq!(split_locale(&config, "/it"), Some(("it", "/")));
+        assert_eq!(split_locale(&config, "/it/about"), Some(("it", "/about")));
+        assert_eq!(split_locale(&config, "/italy"), None);
+        assert_eq!(split_locale(&config, "/"), None);
+    }
+
+    #[tokio::test]
+    async fn it_removes_the_locale_prefix() {
+        let response = call("", get("/it/about?page=2")).await;
+        assert_eq!(
+            body(response).await,
+            "/about?page=2 it /it /it/about?page=2"
+        );
+
+        let response = call("/shop", get("/shop/it")).await;
+        assert_eq!(body(response).await, "/shop it /it /shop/it");
+
+        let response = call("", get("/it/__tuono/data/about")).await;
+        assert_eq!(
+            body(response).await,
+            "/__tuono/data/about it /it /it/__tuono/data/about"
+        );
+    }
+
+    #[tokio::test]
+    async fn it_resolves_the_domain_locale() {
+        let req = Request::get("/about")
+            .header(HOST, "tuono.it:3000")
+            .body(Body::empty())
+            .unwrap();
+
+        assert_eq!(body(call("", req).await).await, "/about it  -");
+    }
+
+    #[tokio::test]
+    async fn it_redirects_the_root_to_the_preferred_locale() {
+        let req = Request::get("/shop?ref=home")
+            .header(ACCEPT_LANGUAGE, "it-IT,it;q=0.9,en;q=0.8")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = call("/shop", req).await;
+        assert_eq!(response.status(), 307);
+        assert_eq!(response.headers()["location"], "/shop/it?ref=home");
+
+        // The cookie wins over the header
+        let req = Request::get("/")
+            .header(ACCEPT_LANGUAGE, "it")
+            .header("cookie", "TUONO_LOCALE=en")
+            .body(Body::empty())
+            .unwrap();
+
+        assert_eq!(body(call("", req).await).await, "/ en  -");
+    }
+
+    #[tokio::test]
+    async fn it_defaults_to_the_default_locale() {
+        let req = Request::get("/about")
+            .header(ACCEPT_LANGUAGE, "it")
+            .body(Body::empty())
+            .unwrap();
+
+        assert_eq!(body(call("", req).await).await, "/about en  -");
+    }
+}
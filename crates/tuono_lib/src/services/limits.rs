@@ -0,0 +1,791 @@
+use super::path_pattern::{PathPattern, app_path};
+use crate::config::trusted_proxies;
+use axum::body::Body;
+use axum::extract::ConnectInfo;
+use axum::response::{IntoResponse, Response};
+use colored::Colorize;
+use futures_util::future::BoxFuture;
+use http::header::{FORWARDED, RETRY_AFTER};
+use http::{HeaderValue, Request, StatusCode};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, Once, PoisonError};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tower::{Layer, Service};
+use tuono_internal::config::{LimitsConfig, RouteLimitsConfig};
+use tuono_internal::tuono_println;
+
+/// Number of checks after which the idle buckets are removed
+const BUCKETS_CLEANUP_INTERVAL: usize = 1000;
+
+const X_FORWARDED_FOR: &str = "x-forwarded-for";
+
+/// The rate limit key of the clients without IP
+const UNIDENTIFIED_CLIENT: &str = "unidentified";
+
+/// Parse a duration like `500ms`, `30s`, `5m` or `1h`
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let unit_start = value
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(|| format!("missing unit in `{value}` (ms, s, m or h)"))?;
+
+    let (amount, unit) = value.split_at(unit_start);
+    let amount: u64 = amount
+        .parse()
+        .map_err(|_| format!("invalid amount in `{value}`"))?;
+
+    match unit {
+        "ms" => Ok(Duration::from_millis(amount)),
+        "s" => Ok(Duration::from_secs(amount)),
+        "m" => Ok(Duration::from_secs(amount * 60)),
+        "h" => Ok(Duration::from_secs(amount * 60 * 60)),
+        _ => Err(format!("invalid unit `{unit}` (ms, s, m or h)")),
+    }
+}
+
+/// Parse a rate limit like `10/1m` (10 requests per minute)
+fn parse_rate_limit(value: &str) -> Result<(u32, Duration), String> {
+    let (requests, per) = value.split_once('/').ok_or_else(|| {
+        format!("expected `<requests>/<duration>` (e.g. `10/1m`), found `{value}`")
+    })?;
+
+    let requests: u32 = requests
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid number of requests in `{value}`"))?;
+    let per = parse_duration(per)?;
+
+    if requests == 0 || per.is_zero() {
+        return Err(format!("`{value}` never allows a request"));
+    }
+
+    Ok((requests, per))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum RateLimitKey {
+    /// The IP of the connected client
+    Ip,
+    /// The value of a request header (e.g. an API key).
+    /// Requests without the header fallback to the client IP.
+    Header(String),
+}
+
+impl RateLimitKey {
+    fn parse(value: &str) -> Result<Self, String> {
+        match value.split_once(':') {
+            None if value == "ip" => Ok(RateLimitKey::Ip),
+            Some(("header", name)) if !name.trim().is_empty() => {
+                Ok(RateLimitKey::Header(name.trim().to_lowercase()))
+            }
+            _ => Err(format!(
+                "expected `ip` or `header:<name>` as rate limit key, found `{value}`"
+            )),
+        }
+    }
+
+    /// The clients that can't be identified share the same bucket:
+    /// leaving them unlimited would disable the rate limit.
+    fn extract(&self, req: &Request<Body>, trusted_proxies: usize) -> String {
+        if let RateLimitKey::Header(name) = self
+            && let Some(value) = req.headers().get(name).and_then(|v| v.to_str().ok())
+        {
+            return format!("{name}:{value}");
+        }
+
+        client_ip(req, trusted_proxies).unwrap_or_else(|| {
+            MISSING_IP_WARNING.call_once(|| {
+                tuono_println!(
+                    "{}",
+                    "[SERVER] The client IP is not available: the unidentified clients share the same rate limit"
+                        .yellow()
+                );
+            });
+            UNIDENTIFIED_CLIENT.to_string()
+        })
+    }
+}
+
+static MISSING_IP_WARNING: Once = Once::new();
+
+/// The addresses appended by the proxies, from the farthest to the closest one.
+/// Reads the `X-Forwarded-For` header or the `for` parameters of the `Forwarded` one.
+fn forwarded_addresses(req: &Request<Body>) -> Vec<&str> {
+    let header = |name: &str| req.headers().get(name).and_then(|v| v.to_str().ok());
+
+    if let Some(value) = header(X_FORWARDED_FOR) {
+        return value.split(',').map(str::trim).collect();
+    }
+
+    // `Forwarded: for=192.0.2.60;proto=http, for="[2001:db8::1]:4711"`
+    header(FORWARDED.as_str())
+        .into_iter()
+        .flat_map(|value| value.split(','))
+        .filter_map(|element| {
+            element.split(';').find_map(|pair| {
+                let (name, value) = pair.trim().split_once('=')?;
+                name.eq_ignore_ascii_case("for")
+                    .then(|| value.trim_matches('"'))
+            })
+        })
+        .collect()
+}
+
+/// The IP of the connected client.
+///
+/// Behind `trusted_proxies` reverse proxies the client is the right-most
+/// address they didn't append: the ones on its left are set by the client
+/// itself and can't be trusted.
+fn client_ip(req: &Request<Body>, trusted_proxies: usize) -> Option<String> {
+    let peer = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(address)| address.ip().to_string());
+
+    if trusted_proxies == 0 {
+        return peer;
+    }
+
+    let addresses = forwarded_addresses(req);
+
+    addresses
+        .len()
+        .checked_sub(trusted_proxies)
+        .map(|index| addresses[index])
+        .filter(|ip| !ip.is_empty())
+        .map(String::from)
+        .or(peer)
+}
+
+/// The traffic controls of a route.
+///
+/// Built by the `rate_limit`, `rate_limit_key`, `concurrency` and `timeout`
+/// arguments of the `handler` and `api` macros.
+///
+/// Invalid values panic when the server starts.
+#[derive(Debug, Clone)]
+pub struct RouteLimits {
+    rate_limit: Option<(u32, Duration)>,
+    rate_limit_key: RateLimitKey,
+    concurrency: Option<usize>,
+    timeout: Option<Duration>,
+}
+
+impl Default for RouteLimits {
+    fn default() -> Self {
+        RouteLimits {
+            rate_limit: None,
+            rate_limit_key: RateLimitKey::Ip,
+            concurrency: None,
+            timeout: None,
+        }
+    }
+}
+
+fn invalid_limit(name: &str, err: String) -> ! {
+    panic!("[SERVER] Invalid {name}: {err}")
+}
+
+impl RouteLimits {
+    pub fn new() -> Self {
+        RouteLimits::default()
+    }
+
+    /// Token bucket rate limit (e.g. `10/1m`)
+    pub fn rate_limit(mut self, rate_limit: &str) -> Self {
+        self.rate_limit = Some(
+            parse_rate_limit(rate_limit).unwrap_or_else(|err| invalid_limit("rate_limit", err)),
+        );
+        self
+    }
+
+    /// `ip` (default) or `header:<name>`
+    pub fn rate_limit_key(mut self, key: &str) -> Self {
+        self.rate_limit_key =
+            RateLimitKey::parse(key).unwrap_or_else(|err| invalid_limit("rate_limit_key", err));
+        self
+    }
+
+    /// Max number of requests handled at the same time
+    pub fn concurrency(mut self, max: usize) -> Self {
+        self.concurrency = Some(max);
+        self
+    }
+
+    /// Max duration of the request (e.g. `10s`)
+    pub fn timeout(mut self, timeout: &str) -> Self {
+        self.timeout =
+            Some(parse_duration(timeout).unwrap_or_else(|err| invalid_limit("timeout", err)));
+        self
+    }
+
+    fn is_empty(&self) -> bool {
+        self.rate_limit.is_none() && self.concurrency.is_none() && self.timeout.is_none()
+    }
+
+    fn from_config(
+        rate_limit: Option<&String>,
+        rate_limit_key: Option<&String>,
+        concurrency: Option<usize>,
+        timeout: Option<&String>,
+    ) -> Self {
+        let mut limits = RouteLimits::new();
+        if let Some(rate_limit) = rate_limit {
+            limits = limits.rate_limit(rate_limit);
+        }
+        if let Some(key) = rate_limit_key {
+            limits = limits.rate_limit_key(key);
+        }
+        if let Some(max) = concurrency {
+            limits = limits.concurrency(max);
+        }
+        if let Some(timeout) = timeout {
+            limits = limits.timeout(timeout);
+        }
+        limits
+    }
+}
+
+impl From<&RouteLimitsConfig> for RouteLimits {
+    fn from(config: &RouteLimitsConfig) -> Self {
+        RouteLimits::from_config(
+            config.rate_limit.as_ref(),
+            config.rate_limit_key.as_ref(),
+            config.concurrency,
+            config.timeout.as_ref(),
+        )
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+#[derive(Debug)]
+struct RateLimiter {
+    capacity: f64,
+    /// Tokens added per second
+    refill_rate: f64,
+    key: RateLimitKey,
+    buckets: Mutex<HashMap<String, Bucket>>,
+    checks: AtomicUsize,
+}
+
+impl RateLimiter {
+    fn new((requests, per): (u32, Duration), key: RateLimitKey) -> Self {
+        RateLimiter {
+            capacity: requests as f64,
+            refill_rate: requests as f64 / per.as_secs_f64(),
+            key,
+            buckets: Mutex::new(HashMap::new()),
+            checks: AtomicUsize::new(0),
+        }
+    }
+
+    /// Take a token from the client bucket.
+    /// Returns the time to wait for the next token when the bucket is empty.
+    fn check(&self, key: String, now: Instant) -> Result<(), Duration> {
+        let mut buckets = self.buckets.lock().unwrap_or_else(PoisonError::into_inner);
+
+        if self
+            .checks
+            .fetch_add(1, Ordering::Relaxed)
+            .is_multiple_of(BUCKETS_CLEANUP_INTERVAL)
+        {
+            // Full buckets behave exactly as missing ones
+            let (capacity, refill_rate) = (self.capacity, self.refill_rate);
+            buckets.retain(|_, bucket| {
+                let elapsed = now.duration_since(bucket.updated_at).as_secs_f64();
+                bucket.tokens + elapsed * refill_rate < capacity
+            });
+        }
+
+        let bucket = buckets.entry(key).or_insert(Bucket {
+            tokens: self.capacity,
+            updated_at: now,
+        });
+
+        let elapsed = now.duration_since(bucket.updated_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.refill_rate).min(self.capacity);
+        bucket.updated_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            return Ok(());
+        }
+
+        // Rounded to avoid the float errors to leak into the `Retry-After` header
+        let wait_ms = ((1.0 - bucket.tokens) / self.refill_rate * 1000.0).round();
+        Err(Duration::from_millis(wait_ms as u64))
+    }
+}
+
+#[derive(Debug)]
+struct Limiter {
+    rate_limiter: Option<RateLimiter>,
+    semaphore: Option<Arc<Semaphore>>,
+    timeout: Option<Duration>,
+}
+
+/// Why a request was not admitted by the limiter
+#[derive(Debug, PartialEq, Eq)]
+enum Rejection {
+    RateLimited { retry_after: Duration },
+    TooManyInFlight,
+}
+
+impl IntoResponse for Rejection {
+    fn into_response(self) -> Response {
+        let (status, message, retry_after) = match self {
+            Rejection::RateLimited { retry_after } => (
+                StatusCode::TOO_MANY_REQUESTS,
+                "Too many requests",
+                retry_after,
+            ),
+            Rejection::TooManyInFlight => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "Too many concurrent requests",
+                Duration::from_secs(1),
+            ),
+        };
+
+        // Round up: retrying earlier would be rejected again
+        let seconds = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+        let mut response = (status, message).into_response();
+        response
+            .headers_mut()
+            .insert(RETRY_AFTER, HeaderValue::from(seconds.max(1)));
+        response
+    }
+}
+
+impl Limiter {
+    fn new(limits: RouteLimits) -> Self {
+        Limiter {
+            rate_limiter: limits
+                .rate_limit
+                .map(|rate_limit| RateLimiter::new(rate_limit, limits.rate_limit_key)),
+            semaphore: limits.concurrency.map(|max| Arc::new(Semaphore::new(max))),
+            timeout: limits.timeout,
+        }
+    }
+
+    /// Check the rate limit and reserve a concurrency slot.
+    /// The returned permit must be kept until the response is sent.
+    fn admit(&self, req: &Request<Body>) -> Result<Option<OwnedSemaphorePermit>, Rejection> {
+        if let Some(rate_limiter) = &self.rate_limiter
+            && let Err(retry_after) = rate_limiter.check(
+                rate_limiter.key.extract(req, trusted_proxies()),
+                Instant::now(),
+            )
+        {
+            return Err(Rejection::RateLimited { retry_after });
+        }
+
+        match &self.semaphore {
+            Some(semaphore) => match Arc::clone(semaphore).try_acquire_owned() {
+                Ok(permit) => Ok(Some(permit)),
+                Err(_) => Err(Rejection::TooManyInFlight),
+            },
+            None => Ok(None),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Limiters {
+    global: Option<Limiter>,
+    routes: Vec<(PathPattern, Limiter)>,
+    /// The route paths are relative to the base path
+    base_path: String,
+}
+
+impl Limiters {
+    /// The global limiter and the first route limiter matching the path
+    fn for_path(&self, path: &str) -> impl Iterator<Item = &Limiter> {
+        let route = app_path(&self.base_path, path).and_then(|path| {
+            self.routes
+                .iter()
+                .find(|(pattern, _)| pattern.matches(path).is_some())
+                .map(|(_, limiter)| limiter)
+        });
+
+        self.global.iter().chain(route)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.global.is_none() && self.routes.is_empty()
+    }
+}
+
+/// Enforce rate limits (429), concurrency limits (503) and timeouts (504).
+///
+/// The `handler` and `api` macros create one for each route; the
+/// server applies the one defined by the `limits` config section.
+#[derive(Debug, Clone)]
+pub struct LimitsLayer {
+    limiters: Arc<Limiters>,
+}
+
+impl LimitsLayer {
+    pub fn new(limits: RouteLimits) -> Self {
+        let global = (!limits.is_empty()).then(|| Limiter::new(limits));
+
+        LimitsLayer {
+            limiters: Arc::new(Limiters {
+                global,
+                ..Limiters::default()
+            }),
+        }
+    }
+
+    pub fn from_config(config: &LimitsConfig, base_path: &str) -> Self {
+        let global = RouteLimits::from_config(
+            config.rate_limit.as_ref(),
+            config.rate_limit_key.as_ref(),
+            config.concurrency,
+            config.timeout.as_ref(),
+        );
+
+        let routes = config
+            .routes
+            .iter()
+            .map(|route| {
+                (
+                    PathPattern::new(&route.path),
+                    Limiter::new(RouteLimits::from(route)),
+                )
+            })
+            .collect();
+
+        LimitsLayer {
+            limiters: Arc::new(Limiters {
+                global: (!global.is_empty()).then(|| Limiter::new(global)),
+                routes,
+                base_path: base_path.to_string(),
+            }),
+        }
+    }
+}
+
+impl<S> Layer<S> for LimitsLayer {
+    type Service = Limits<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Limits {
+            inner,
+            limiters: Arc::clone(&self.limiters),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Limits<S> {
+    inner: S,
+    limiters: Arc<Limiters>,
+}
+
+impl<S> Service<Request<Body>> for Limits<S>
+where
+    S: Service<Request<Body>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Response, S::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let limiters = Arc::clone(&self.limiters);
+
+        Box::pin(async move {
+            if limiters.is_empty() {
+                return inner.call(req).await;
+            }
+
+            let mut permits = Vec::new();
+            let mut timeout: Option<Duration> = None;
+
+            for limiter in limiters.for_path(req.uri().path()) {
+                match limiter.admit(&req) {
+                    Ok(permit) => permits.extend(permit),
+                    Err(rejection) => return Ok(rejection.into_response()),
+                }
+                if let Some(limit) = limiter.timeout {
+                    timeout = Some(timeout.map_or(limit, |current| current.min(limit)));
+                }
+            }
+
+            let response = match timeout {
+                Some(timeout) => match tokio::time::timeout(timeout, inner.call(req)).await {
+                    Ok(response) => response,
+                    Err(_) => {
+                        Ok((StatusCode::GATEWAY_TIMEOUT, "Request timed out").into_response())
+                    }
+                },
+                None => inner.call(req).await,
+            };
+
+            drop(permits);
+            response
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::Infallible;
+    use tower::ServiceExt;
+    use tower::service_fn;
+
+    fn service(
+        layer: &LimitsLayer,
+        delay: Duration,
+    ) -> impl Service<Request<Body>, Response = Response, Error = Infallible, Future: Send> + Clone + use<>
+    {
+        layer.layer(service_fn(move |_: Request<Body>| async move {
+            tokio::time::sleep(delay).await;
+            Ok::<_, Infallible>(Response::new(Body::empty()))
+        }))
+    }
+
+    fn request(path: &str, api_key: Option<&str>) -> Request<Body> {
+        let mut builder = Request::builder().uri(path);
+        if let Some(api_key) = api_key {
+            builder = builder.header("x-api-key", api_key);
+        }
+        builder.body(Body::empty()).unwrap()
+    }
+
+    #[test]
+    fn it_parses_the_limits() {
+        assert_eq!(parse_duration("500ms"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_duration("2m"), Ok(Duration::from_secs(120)));
+        assert!(parse_duration("10").is_err());
+        assert!(parse_duration("10d").is_err());
+
+        assert_eq!(parse_rate_limit("10/1m"), Ok((10, Duration::from_secs(60))));
+        assert!(parse_rate_limit("10").is_err());
+        assert!(parse_rate_limit("0/1s").is_err());
+
+        assert_eq!(
+            RateLimitKey::parse("header:X-Api-Key"),
+            Ok(RateLimitKey::Header("x-api-key".to_string()))
+        );
+        assert!(RateLimitKey::parse("cookie").is_err());
+    }
+
+    #[test]
+    fn it_reads_the_client_ip_from_the_trusted_proxy_headers() {
+        let forwarded_request = |name: &str, value| {
+            let mut req = Request::builder()
+                .header(name, value)
+                .body(Body::empty())
+                .unwrap();
+            req.extensions_mut()
+                .insert(ConnectInfo(SocketAddr::from(([10, 0, 0, 2], 4000))));
+            req
+        };
+
+        let req = forwarded_request(X_FORWARDED_FOR, "198.51.100.7, 203.0.113.1, 10.0.0.1");
+        // The headers are ignored without trusted proxies
+        assert_eq!(client_ip(&req, 0), Some("10.0.0.2".to_string()));
+        assert_eq!(client_ip(&req, 1), Some("10.0.0.1".to_string()));
+        // The left-most address is set by the client
+        assert_eq!(client_ip(&req, 2), Some("203.0.113.1".to_string()));
+        // Not all the proxies appended the address
+        assert_eq!(client_ip(&req, 4), Some("10.0.0.2".to_string()));
+
+        assert_eq!(
+            client_ip(
+                &forwarded_request(
+                    FORWARDED.as_str(),
+                    "for=198.51.100.7, For=\"[2001:db8::1]:4711\";proto=https"
+                ),
+                1
+            ),
+            Some("[2001:db8::1]:4711".to_string())
+        );
+        assert_eq!(client_ip(&request("/", None), 1), None);
+    }
+
+    #[tokio::test]
+    async fn the_unidentified_clients_share_the_rate_limit() {
+        let layer = LimitsLayer::new(RouteLimits::new().rate_limit("1/1m"));
+
+        let response = service(&layer, Duration::ZERO)
+            .oneshot(request("/login", None))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let mut spoofed = request("/login", None);
+        spoofed
+            .headers_mut()
+            .insert(X_FORWARDED_FOR, HeaderValue::from_static("203.0.113.1"));
+
+        let response = service(&layer, Duration::ZERO)
+            .oneshot(spoofed)
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[test]
+    #[should_panic(expected = "[SERVER] Invalid timeout")]
+    fn it_panics_on_invalid_limits() {
+        RouteLimits::new().timeout("soon");
+    }
+
+    #[test]
+    fn the_bucket_refills_over_time() {
+        let limiter = RateLimiter::new((2, Duration::from_secs(10)), RateLimitKey::Ip);
+        let now = Instant::now();
+
+        assert!(limiter.check("a".to_string(), now).is_ok());
+        assert!(limiter.check("a".to_string(), now).is_ok());
+        assert_eq!(
+            limiter.check("a".to_string(), now),
+            Err(Duration::from_secs(5))
+        );
+        // Other clients have their own bucket
+        assert!(limiter.check("b".to_string(), now).is_ok());
+
+        assert!(
+            limiter
+                .check("a".to_string(), now + Duration::from_secs(6))
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn it_rate_limits_by_key() {
+        let layer = LimitsLayer::new(
+            RouteLimits::new()
+                .rate_limit("1/1m")
+                .rate_limit_key("header:x-api-key"),
+        );
+
+        let response = service(&layer, Duration::ZERO)
+            .oneshot(request("/login", Some("first")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = service(&layer, Duration::ZERO)
+            .oneshot(request("/login", Some("first")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[RETRY_AFTER], "60");
+
+        let response = service(&layer, Duration::ZERO)
+            .oneshot(request("/login", Some("second")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn it_limits_the_in_flight_requests() {
+        let layer = LimitsLayer::new(RouteLimits::new().concurrency(1));
+
+        let slow = tokio::spawn(
+            service(&layer, Duration::from_millis(200)).oneshot(request("/search", None)),
+        );
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let response = service(&layer, Duration::ZERO)
+            .oneshot(request("/search", None))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers()[RETRY_AFTER], "1");
+
+        assert_eq!(slow.await.unwrap().unwrap().status(), StatusCode::OK);
+
+        let response = service(&layer, Duration::ZERO)
+            .oneshot(request("/search", None))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn it_times_out_slow_requests() {
+        let layer = LimitsLayer::new(RouteLimits::new().timeout("50ms"));
+
+        let response = service(&layer, Duration::from_millis(200))
+            .oneshot(request("/search", None))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+    }
+
+    #[tokio::test]
+    async fn it_applies_the_config_routes_and_the_global_limits() {
+        let layer = LimitsLayer::from_config(
+            &LimitsConfig {
+                rate_limit: Some("100/1s".to_string()),
+                routes: vec![RouteLimitsConfig {
+                    path: "/api/login".to_string(),
+                    rate_limit: Some("1/1h".to_string()),
+                    ..RouteLimitsConfig::default()
+                }],
+                ..LimitsConfig::default()
+            },
+            "",
+        );
+
+        for _ in 0..2 {
+            let response = service(&layer, Duration::ZERO)
+                .oneshot(request("/search", None))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        let response = service(&layer, Duration::ZERO)
+            .oneshot(request("/api/login", None))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = service(&layer, Duration::ZERO)
+            .oneshot(request("/api/login", None))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[RETRY_AFTER], "3600");
+    }
+
+    #[test]
+    fn the_route_paths_are_relative_to_the_base_path() {
+        let layer = LimitsLayer::from_config(
+            &LimitsConfig {
+                routes: vec![RouteLimitsConfig {
+                    path: "/api/login".to_string(),
+                    rate_limit: Some("1/1h".to_string()),
+                    ..RouteLimitsConfig::default()
+                }],
+                ..LimitsConfig::default()
+            },
+            "/shop",
+        );
+
+        assert_eq!(layer.limiters.for_path("/shop/api/login").count(), 1);
+        assert_eq!(layer.limiters.for_path("/api/login").count(), 0);
+    }
+}
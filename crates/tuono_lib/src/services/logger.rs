@@ -1,3 +1,7 @@
+use super::path_pattern::{app_path, split_data_route};
+use crate::config;
+use crate::metrics::{self, InFlight, RequestRecord};
+use axum::extract::MatchedPath;
 use colored::Colorize;
 use http::{Request, Response, method::Method};
 use pin_project::pin_project;
@@ -53,11 +57,17 @@ where
     fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
         let method = req.method().clone();
         let path = req.uri().path().to_string();
+        let route = req
+            .extensions()
+            .get::<MatchedPath>()
+            .map(|route| route.as_str().to_string());
 
         LoggerFuture {
             future: self.inner.call(req),
             method,
             path,
+            route,
+            in_flight: InFlight::start(),
             start: Instant::now(),
         }
     }
@@ -69,6 +79,9 @@ pub struct LoggerFuture<F> {
     future: F,
     method: Method,
     path: String,
+    /// The route matched by the router, used as metrics label
+    route: Option<String>,
+    in_flight: Option<InFlight>,
     start: Instant,
 }
 
@@ -87,7 +100,21 @@ where
             Poll::Pending => return Poll::Pending,
         };
 
-        if this.path.starts_with("/__tuono/data") {
+        if let (Some(_), Ok(response)) = (this.in_flight.take(), &res) {
+            metrics::record_request(RequestRecord {
+                method: this.method.as_str(),
+                route: this.route.as_deref(),
+                base_path: config::base_path(),
+                status: response.status(),
+                content_type: response.headers().get(http::header::CONTENT_TYPE),
+                duration: this.start.elapsed(),
+            });
+        }
+
+        let is_data_request =
+            app_path(config::base_path(), this.path).is_some_and(|path| split_data_route(path).0);
+
+        if is_data_request {
             return Poll::Ready(res);
         }
 
@@ -1 +1,10 @@
+pub mod auth;
+pub mod cors;
+pub mod csrf;
+pub mod i18n;
+pub mod limits;
 pub mod logger;
+pub(crate) mod path_pattern;
+pub mod redirects;
+pub mod session;
+pub mod trace;
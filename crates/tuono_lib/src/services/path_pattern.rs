@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+
+/// The prefix of the routes used by the client side navigation
+pub(crate) const DATA_ROUTE_PREFIX: &str = "/__tuono/data";
+
+/// The path relative to the application base path.
+/// `None` when the path is outside the application.
+pub(crate) fn app_path<'a>(base_path: &str, path: &'a str) -> Option<&'a str> {
+    match path.strip_prefix(base_path)? {
+        "" => Some("/"),
+        path if path.starts_with('/') => Some(path),
+        _ => None,
+    }
+}
+
+/// Prefix the application `path` with the base path.
+/// The root of the application is the base path itself.
+pub(crate) fn with_base_path(base_path: &str, path: &str) -> String {
+    match path {
+        "/" if !base_path.is_empty() => base_path.to_string(),
+        _ => format!("{base_path}{path}"),
+    }
+}
+
+/// Remove the data route prefix from the application `path`.
+/// The flag tells whether the path is a data route.
+pub(crate) fn split_data_route(path: &str) -> (bool, &str) {
+    match path.strip_prefix(DATA_ROUTE_PREFIX) {
+        Some("") => (true, "/"),
+        Some(path) if path.starts_with('/') => (true, path),
+        _ => (false, path),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Static(String),
+    Param(String),
+    CatchAll(String),
+}
+
+/// A path using the router parameters syntax (e.g. `/blog/{slug}`)
+#[derive(Debug, Clone)]
+pub(crate) struct PathPattern {
+    segments: Vec<Segment>,
+}
+
+impl PathPattern {
+    pub(crate) fn new(pattern: &str) -> Self {
+        let segments = pattern
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .map(|segment| {
+                match segment
+                    .strip_prefix('{')
+                    .and_then(|segment| segment.strip_suffix('}'))
+                {
+                    Some(name) => match name.strip_prefix('*') {
+                        Some(name) => Segment::CatchAll(name.to_string()),
+                        None => Segment::Param(name.to_string()),
+                    },
+                    None => Segment::Static(segment.to_string()),
+                }
+            })
+            .collect();
+
+        PathPattern { segments }
+    }
+
+    /// Returns the path parameters when `path` matches the pattern
+    pub(crate) fn matches(&self, path: &str) -> Option<HashMap<&str, String>> {
+        let mut params = HashMap::new();
+        let mut path_segments = path.split('/').filter(|segment| !segment.is_empty());
+
+        for segment in &self.segments {
+            match segment {
+                Segment::Static(expected) => {
+                    if path_segments.next()? != expected {
+                        return None;
+                    }
+                }
+                Segment::Param(name) => {
+                    params.insert(name.as_str(), path_segments.next()?.to_string());
+                }
+                Segment::CatchAll(name) => {
+                    let rest: Vec<&str> = path_segments.by_ref().collect();
+                    if rest.is_empty() {
+                        return None;
+                    }
+                    params.insert(name.as_str(), rest.join("/"));
+                }
+            }
+        }
+
+        if path_segments.next().is_some() {
+            return None;
+        }
+
+        Some(params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_matches_static_paths() {
+        let pattern = PathPattern::new("/pokemons/GOAT");
+        assert!(pattern.matches("/pokemons/GOAT").is_some());
+        assert!(pattern.matches("/pokemons/GOAT/").is_some());
+        assert!(pattern.matches("/pokemons/mewtwo").is_none());
+        assert!(pattern.matches("/pokemons/GOAT/1").is_none());
+    }
+
+    #[test]
+    fn it_removes_the_base_path_and_the_data_prefix() {
+        assert_eq!(app_path("", "/about"), Some("/about"));
+        assert_eq!(app_path("/shop", "/shop"), Some("/"));
+        assert_eq!(app_path("/shop", "/shop/about"), Some("/about"));
+        assert_eq!(app_path("/shop", "/shopping"), None);
+        assert_eq!(app_path("/shop", "/about"), None);
+
+        assert_eq!(with_base_path("/shop", "/"), "/shop");
+        assert_eq!(with_base_path("/shop", "/about"), "/shop/about");
+        assert_eq!(with_base_path("", "/"), "/");
+
+        assert_eq!(split_data_route("/__tuono/data/about"), (true, "/about"));
+        assert_eq!(split_data_route("/__tuono/data"), (true, "/"));
+        assert_eq!(
+            split_data_route("/__tuono/database"),
+            (false, "/__tuono/database")
+        );
+        assert_eq!(split_data_route("/about"), (false, "/about"));
+    }
+
+    #[test]
+    fn it_reads_the_path_params() {
+        let pattern = PathPattern::new("/api/{version}/{*path}");
+        let params = pattern.matches("/api/v1/users/1").unwrap();
+
+        assert_eq!(params["version"], "v1");
+        assert_eq!(params["path"], "users/1");
+        assert!(pattern.matches("/api/v1").is_none());
+    }
+}
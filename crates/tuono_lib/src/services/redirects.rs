@@ -0,0 +1,362 @@
+use super::i18n::split_locale;
+use super::path_pattern::{
+    DATA_ROUTE_PREFIX, PathPattern, app_path, split_data_route, with_base_path,
+};
+use crate::Response as TuonoResponse;
+use axum::body::Body;
+use axum::response::{IntoResponse, Redirect, Response};
+use futures_util::future::{Either, Ready, ready};
+use http::{Request, Uri};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tower::{Layer, Service};
+use tuono_internal::config::{I18nConfig, RedirectConfig, RewriteConfig};
+
+/// Replace the `{param}` and `{*param}` placeholders of the destination
+fn fill_destination(destination: &str, params: &HashMap<&str, String>) -> String {
+    let mut destination = destination.to_string();
+    for (name, value) in params {
+        destination = destination
+            .replace(&format!("{{*{name}}}"), value)
+            .replace(&format!("{{{name}}}"), value);
+    }
+    destination
+}
+
+/// Keep the request query string unless the destination defines its own
+fn with_query(destination: String, query: Option<&str>) -> String {
+    match query {
+        Some(query) if !destination.contains('?') => format!("{destination}?{query}"),
+        _ => destination,
+    }
+}
+
+/// The page requested by a data route.
+/// The locale prefix precedes the data prefix (e.g. `/it/__tuono/data/about` is `/it/about`).
+fn data_route_page<'a>(i18n: &I18nConfig, path: &'a str) -> Option<Cow<'a, str>> {
+    if let (true, page) = split_data_route(path) {
+        return Some(Cow::Borrowed(page));
+    }
+
+    let (locale, path) = split_locale(i18n, path)?;
+    match split_data_route(path) {
+        (true, page) => Some(Cow::Owned(with_base_path(&format!("/{locale}"), page))),
+        (false, _) => None,
+    }
+}
+
+/// The data route of the `page`, keeping its locale prefix first
+fn to_data_route(i18n: &I18nConfig, page: &str) -> String {
+    match split_locale(i18n, page) {
+        Some((locale, page)) => format!("/{locale}{DATA_ROUTE_PREFIX}{page}"),
+        None => format!("{DATA_ROUTE_PREFIX}{page}"),
+    }
+}
+
+#[derive(Debug)]
+struct Rule {
+    source: PathPattern,
+    destination: String,
+}
+
+impl Rule {
+    fn resolve(&self, path: &str) -> Option<String> {
+        let params = self.source.matches(path)?;
+        Some(fill_destination(&self.destination, &params))
+    }
+}
+
+#[derive(Debug, Default)]
+struct Rules {
+    redirects: Vec<(Rule, bool)>,
+    rewrites: Vec<Rule>,
+    /// The sources and the local destinations are relative to the base path
+    base_path: String,
+    i18n: I18nConfig,
+}
+
+impl Rules {
+    fn is_empty(&self) -> bool {
+        self.redirects.is_empty() && self.rewrites.is_empty()
+    }
+}
+
+/// Apply the redirects and rewrites defined in the tuono config
+/// before the request reaches the router.
+///
+/// The sources are matched against the page path including the locale prefix
+/// (e.g. `/it/about`), also when the page data is fetched by the client side
+/// navigation (e.g. `/it/__tuono/data/about`).
+#[derive(Debug, Clone)]
+pub struct RedirectsLayer {
+    rules: Arc<Rules>,
+}
+
+impl RedirectsLayer {
+    pub fn new(
+        redirects: &[RedirectConfig],
+        rewrites: &[RewriteConfig],
+        i18n: &I18nConfig,
+        base_path: &str,
+    ) -> Self {
+        let rules = Rules {
+            redirects: redirects
+                .iter()
+                .map(|redirect| {
+                    (
+                        Rule {
+                            source: PathPattern::new(&redirect.source),
+                            destination: redirect.destination.clone(),
+                        },
+                        redirect.permanent,
+                    )
+                })
+                .collect(),
+            rewrites: rewrites
+                .iter()
+                .map(|rewrite| Rule {
+                    source: PathPattern::new(&rewrite.source),
+                    destination: rewrite.destination.clone(),
+                })
+                .collect(),
+            base_path: base_path.to_string(),
+            i18n: i18n.clone(),
+        };
+
+        RedirectsLayer {
+            rules: Arc::new(rules),
+        }
+    }
+}
+
+impl<S> Layer<S> for RedirectsLayer {
+    type Service = Redirects<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Redirects {
+            inner,
+            rules: Arc::clone(&self.rules),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Redirects<S> {
+    inner: S,
+    rules: Arc<Rules>,
+}
+
+impl<S> Service<Request<Body>> for Redirects<S>
+where
+    S: Service<Request<Body>, Response = Response>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Either<Ready<Result<Response, S::Error>>, S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<Body>) -> Self::Future {
+        if self.rules.is_empty() {
+            return Either::Right(self.inner.call(req));
+        }
+
+        let base_path = &self.rules.base_path;
+        let uri = req.uri();
+        let Some(path) = app_path(base_path, uri.path()) else {
+            return Either::Right(self.inner.call(req));
+        };
+        let page = data_route_page(&self.rules.i18n, path);
+        let is_data_request = page.is_some();
+        let path = page.as_deref().unwrap_or(path);
+
+        for (rule, permanent) in &self.rules.redirects {
+            let Some(destination) = rule.resolve(path) else {
+                continue;
+            };
+            // External destinations are left untouched
+            let destination = if destination.starts_with('/') {
+                with_base_path(base_path, &destination)
+            } else {
+                destination
+            };
+            let destination = with_query(destination, uri.query());
+
+            let response = match (is_data_request, permanent) {
+                // Client side navigation reads the destination from the JSON payload
+                (true, true) => TuonoResponse::Redirect(destination).json().into_response(),
+                (true, false) => TuonoResponse::TemporaryRedirect(destination)
+                    .json()
+                    .into_response(),
+                (false, true) => Redirect::permanent(&destination).into_response(),
+                (false, false) => Redirect::temporary(&destination).into_response(),
+            };
+
+            return Either::Left(ready(Ok(response)));
+        }
+
+        if let Some(destination) = self
+            .rules
+            .rewrites
+            .iter()
+            .find_map(|rule| rule.resolve(path))
+        {
+            let destination = if is_data_request {
+                with_base_path(base_path, &to_data_route(&self.rules.i18n, &destination))
+            } else {
+                with_base_path(base_path, &destination)
+            };
+
+            if let Ok(rewritten_uri) = with_query(destination, uri.query()).parse::<Uri>() {
+                *req.uri_mut() = rewritten_uri;
+            }
+        }
+
+        Either::Right(self.inner.call(req))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::StatusCode;
+    use http::header::LOCATION;
+    use std::convert::Infallible;
+    use tower::ServiceExt;
+    use tower::service_fn;
+
+    /// Call the layer with a service echoing the request URI
+    async fn call(layer: &RedirectsLayer, uri: &str) -> Response {
+        layer
+            .layer(service_fn(|req: Request<Body>| async move {
+                Ok::<_, Infallible>(Response::new(Body::from(req.uri().to_string())))
+            }))
+            .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+    }
+
+    async fn body(response: Response) -> String {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    #[test]
+    fn it_fills_the_destination_params() {
+        let rule = Rule {
+            source: PathPattern::new("/blog/{year}/{slug}"),
+            destination: "/posts/{slug}?year={year}".to_string(),
+        };
+        assert_eq!(
+            rule.resolve("/blog/2024/hello"),
+            Some("/posts/hello?year=2024".to_string())
+        );
+        assert_eq!(rule.resolve("/blog/2024"), None);
+
+        let rule = Rule {
+            source: PathPattern::new("/docs/{*path}"),
+            destination: "https://docs.tuono.dev/{path}".to_string(),
+        };
+        assert_eq!(
+            rule.resolve("/docs/getting-started/installation"),
+            Some("https://docs.tuono.dev/getting-started/installation".to_string())
+        );
+        assert_eq!(rule.resolve("/docs"), None);
+    }
+
+    #[test]
+    fn it_keeps_the_request_query() {
+        assert_eq!(with_query("/new".to_string(), Some("a=1")), "/new?a=1");
+        assert_eq!(with_query("/new?b=2".to_string(), Some("a=1")), "/new?b=2");
+        assert_eq!(with_query("/new".to_string(), None), "/new");
+    }
+
+    #[tokio::test]
+    async fn it_applies_the_rules_under_the_base_path() {
+        let layer = RedirectsLayer::new(
+            &[
+                RedirectConfig {
+                    source: "/old/{slug}".to_string(),
+                    destination: "/new/{slug}".to_string(),
+                    permanent: true,
+                },
+                RedirectConfig {
+                    source: "/docs".to_string(),
+                    destination: "https://docs.tuono.dev".to_string(),
+                    permanent: false,
+                },
+            ],
+            &[RewriteConfig {
+                source: "/feed".to_string(),
+                destination: "/api/rss".to_string(),
+            }],
+            &I18nConfig::default(),
+            "/shop",
+        );
+
+        let response = call(&layer, "/shop/old/hello").await;
+        assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+        assert_eq!(response.headers()[LOCATION], "/shop/new/hello");
+
+        let response = call(&layer, "/shop/__tuono/data/old/hello").await;
+        assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+        assert!(body(response).await.contains("\"/shop/new/hello\""));
+
+        let response = call(&layer, "/shop/docs").await;
+        assert_eq!(response.headers()[LOCATION], "https://docs.tuono.dev");
+
+        assert_eq!(
+            body(call(&layer, "/shop/feed?page=2").await).await,
+            "/shop/api/rss?page=2"
+        );
+        assert_eq!(
+            body(call(&layer, "/shop/__tuono/data/feed").await).await,
+            "/shop/__tuono/data/api/rss"
+        );
+
+        // The paths outside of the application are not matched
+        assert_eq!(body(call(&layer, "/old/hello").await).await, "/old/hello");
+    }
+
+    #[tokio::test]
+    async fn it_matches_the_localized_data_routes() {
+        let i18n = I18nConfig {
+            locales: vec!["en".to_string(), "it".to_string()],
+            ..I18nConfig::default()
+        };
+        let layer = RedirectsLayer::new(
+            &[RedirectConfig {
+                source: "/it/old/{slug}".to_string(),
+                destination: "/it/new/{slug}".to_string(),
+                permanent: false,
+            }],
+            &[RewriteConfig {
+                source: "/it/feed".to_string(),
+                destination: "/it/api/rss".to_string(),
+            }],
+            &i18n,
+            "",
+        );
+
+        let response = call(&layer, "/it/__tuono/data/old/hello").await;
+        assert_eq!(response.status(), StatusCode::TEMPORARY_REDIRECT);
+        assert!(body(response).await.contains("\"/it/new/hello\""));
+
+        assert_eq!(
+            body(call(&layer, "/it/__tuono/data/feed").await).await,
+            "/it/__tuono/data/api/rss"
+        );
+
+        // The sources include the locale prefix
+        assert_eq!(
+            body(call(&layer, "/__tuono/data/old/hello").await).await,
+            "/__tuono/data/old/hello"
+        );
+    }
+}
@@ -0,0 +1,367 @@
+use crate::session::{
+    Session, SessionRecord, SessionStore, generate_session_id, is_valid_session_id, now,
+};
+use axum::body::Body;
+use axum::response::Response;
+use axum_extra::extract::cookie::CookieJar;
+use colored::Colorize;
+use cookie::time::Duration;
+use cookie::{Cookie, SameSite};
+use futures_util::future::BoxFuture;
+use http::header::SET_COOKIE;
+use http::{HeaderValue, Request};
+use std::fmt;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tower::{Layer, Service};
+use tuono_internal::config::{SameSiteConfig, SessionConfig};
+use tuono_internal::tuono_println;
+
+struct SessionSettings {
+    cookie_name: String,
+    idle_timeout: u64,
+    absolute_timeout: u64,
+    secure: bool,
+    same_site: SameSite,
+}
+
+impl SessionSettings {
+    fn cookie(&self, value: String, max_age: u64) -> HeaderValue {
+        let cookie = Cookie::build((self.cookie_name.clone(), value))
+            .path("/")
+            .http_only(true)
+            .secure(self.secure)
+            .same_site(self.same_site)
+            .max_age(Duration::seconds(max_age as i64))
+            .build();
+
+        HeaderValue::from_str(&cookie.encoded().to_string())
+            .expect("The session cookie is always a valid header value")
+    }
+}
+
+/// Load the request session from the store and save
+/// its changes once the response is created.
+#[derive(Clone)]
+pub struct SessionLayer {
+    store: Arc<dyn SessionStore>,
+    settings: Arc<SessionSettings>,
+}
+
+impl fmt::Debug for SessionLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SessionLayer")
+            .field("cookie_name", &self.settings.cookie_name)
+            .finish()
+    }
+}
+
+impl SessionLayer {
+    /// `secure` is used when the config doesn't define it
+    pub fn new(store: Arc<dyn SessionStore>, config: &SessionConfig, secure: bool) -> Self {
+        let same_site = match config.same_site {
+            SameSiteConfig::Lax => SameSite::Lax,
+            SameSiteConfig::Strict => SameSite::Strict,
+            SameSiteConfig::None => SameSite::None,
+        };
+
+        SessionLayer {
+            store,
+            settings: Arc::new(SessionSettings {
+                cookie_name: config.cookie_name.clone(),
+                idle_timeout: config.idle_timeout,
+                absolute_timeout: config.absolute_timeout,
+                secure: config.secure.unwrap_or(secure),
+                same_site,
+            }),
+        }
+    }
+}
+
+impl<S> Layer<S> for SessionLayer {
+    type Service = SessionService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        SessionService {
+            inner,
+            store: Arc::clone(&self.store),
+            settings: Arc::clone(&self.settings),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct SessionService<S> {
+    inner: S,
+    store: Arc<dyn SessionStore>,
+    settings: Arc<SessionSettings>,
+}
+
+impl<S> Service<Request<Body>> for SessionService<S>
+where
+    S: Service<Request<Body>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Response, S::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<Body>) -> Self::Future {
+        // The service that was polled ready is the one that must handle the request
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let store = Arc::clone(&self.store);
+        let settings = Arc::clone(&self.settings);
+
+        Box::pin(async move {
+            let cookie_id = CookieJar::from_headers(req.headers())
+                .get(&settings.cookie_name)
+                .map(|cookie| cookie.value().to_string());
+
+            let session = load_session(&*store, cookie_id.as_deref()).await;
+            req.extensions_mut().insert(session.clone());
+
+            let mut response = inner.call(req).await?;
+
+            if let Some(cookie) =
+                persist_session(&*store, &settings, session, cookie_id.is_some()).await
+            {
+                response.headers_mut().append(SET_COOKIE, cookie);
+            }
+
+            Ok(response)
+        })
+    }
+}
+
+async fn load_session(store: &dyn SessionStore, id: Option<&str>) -> Session {
+    let Some(id) = id.filter(|id| is_valid_session_id(id)) else {
+        return Session::default();
+    };
+
+    match store.load(id).await {
+        Ok(Some(record)) if !record.is_expired(now()) => Session::new(Some(id.to_string()), record),
+        Ok(Some(_)) => {
+            if let Err(err) = store.delete(id).await {
+                tuono_println!(
+                    "Failed to delete expired session: {}",
+                    err.to_string().red()
+                );
+            }
+            Session::default()
+        }
+        Ok(None) => Session::default(),
+        Err(err) => {
+            tuono_println!("Failed to load session: {}", err.to_string().red());
+            Session::default()
+        }
+    }
+}
+
+/// Save the session changes and return the cookie to send to the client
+async fn persist_session(
+    store: &dyn SessionStore,
+    settings: &SessionSettings,
+    session: Session,
+    has_cookie: bool,
+) -> Option<HeaderValue> {
+    let state = session.take_state();
+    let now = now();
+
+    let mut id = state.id;
+
+    if (state.destroyed || state.rotated)
+        && let Some(old_id) = id.take()
+        && let Err(err) = store.delete(&old_id).await
+    {
+        tuono_println!("Failed to delete session: {}", err.to_string().red());
+    }
+
+    // Nothing worth storing: remove the stale cookie if any
+    if state.destroyed || (id.is_none() && state.record.data.is_empty()) {
+        return has_cookie.then(|| settings.cookie(String::new(), 0));
+    }
+
+    let record = state.record;
+
+    // Avoid writing the store on each request: the idle expiration
+    // is extended only once half of it has elapsed
+    let needs_refresh = record.expires_at.saturating_sub(now) < settings.idle_timeout / 2;
+    if id.is_some() && !state.changed && !needs_refresh {
+        return None;
+    }
+
+    let created_at = if record.created_at == 0 {
+        now
+    } else {
+        record.created_at
+    };
+    let expires_at = (now + settings.idle_timeout).min(created_at + settings.absolute_timeout);
+
+    if expires_at <= now {
+        return has_cookie.then(|| settings.cookie(String::new(), 0));
+    }
+
+    let record = SessionRecord {
+        created_at,
+        expires_at,
+        ..record
+    };
+    let id = id.unwrap_or_else(generate_session_id);
+
+    if let Err(err) = store.save(&id, &record).await {
+        tuono_println!("Failed to save session: {}", err.to_string().red());
+        return None;
+    }
+
+    Some(settings.cookie(id, expires_at - now))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::MemoryStore;
+    use std::convert::Infallible;
+    use tower::ServiceExt;
+    use tower::service_fn;
+
+    fn layer(store: Arc<MemoryStore>) -> SessionLayer {
+        SessionLayer::new(store, &SessionConfig::default(), false)
+    }
+
+    async fn call(
+        layer: &SessionLayer,
+        cookie: Option<&str>,
+        handler: fn(&Session),
+    ) -> Option<String> {
+        let service = layer.layer(service_fn(move |req: Request<Body>| async move {
+            handler(req.extensions().get::<Session>().unwrap());
+            Ok::<_, Infallible>(Response::new(Body::empty()))
+        }));
+
+        let mut request = Request::new(Body::empty());
+        if let Some(cookie) = cookie {
+            request
+                .headers_mut()
+                .insert("cookie", HeaderValue::from_str(cookie).unwrap());
+        }
+
+        let response = service.oneshot(request).await.unwrap();
+        response
+            .headers()
+            .get(SET_COOKIE)
+            .map(|cookie| cookie.to_str().unwrap().to_string())
+    }
+
+    fn session_cookie(set_cookie: &str) -> String {
+        set_cookie.split(';').next().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn it_does_not_create_empty_sessions() {
+        let layer = layer(Arc::new(MemoryStore::new()));
+        assert_eq!(call(&layer, None, |_| {}).await, None);
+    }
+
+    #[tokio::test]
+    async fn it_persists_the_session_between_requests() {
+        let layer = layer(Arc::new(MemoryStore::new()));
+
+        let set_cookie = call(&layer, None, |session| {
+            session.insert("user_id", 42).unwrap();
+        })
+        .await
+        .unwrap();
+        assert!(set_cookie.starts_with("tuono_session="));
+        assert!(set_cookie.contains("HttpOnly"));
+        assert!(set_cookie.contains("SameSite=Lax"));
+
+        let cookie = session_cookie(&set_cookie);
+        let set_cookie = call(&layer, Some(&cookie), |session| {
+            assert_eq!(session.get::<u32>("user_id"), Some(42));
+        })
+        .await;
+        // Unchanged sessions are not saved again
+        assert_eq!(set_cookie, None);
+    }
+
+    #[tokio::test]
+    async fn it_rotates_the_session_id() {
+        let store = Arc::new(MemoryStore::new());
+        let layer = layer(store.clone());
+
+        let first = session_cookie(
+            &call(&layer, None, |session| {
+                session.insert("cart", vec![1, 2]).unwrap();
+            })
+            .await
+            .unwrap(),
+        );
+        let second = session_cookie(
+            &call(&layer, Some(&first), |session| session.rotate())
+                .await
+                .unwrap(),
+        );
+
+        assert_ne!(first, second);
+        let (_, old_id) = first.split_once('=').unwrap();
+        assert_eq!(store.load(old_id).await.unwrap(), None);
+
+        call(&layer, Some(&second), |session| {
+            assert_eq!(session.get::<Vec<u32>>("cart"), Some(vec![1, 2]));
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn it_destroys_the_session() {
+        let store = Arc::new(MemoryStore::new());
+        let layer = layer(store.clone());
+
+        let cookie = session_cookie(
+            &call(&layer, None, |session| {
+                session.insert("user_id", 42).unwrap();
+            })
+            .await
+            .unwrap(),
+        );
+
+        let set_cookie = call(&layer, Some(&cookie), |session| session.destroy())
+            .await
+            .unwrap();
+        assert!(set_cookie.contains("Max-Age=0"));
+
+        let (_, id) = cookie.split_once('=').unwrap();
+        assert_eq!(store.load(id).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn it_discards_expired_sessions() {
+        let store = Arc::new(MemoryStore::new());
+        let layer = layer(store.clone());
+        let id = generate_session_id();
+
+        store
+            .save(
+                &id,
+                &SessionRecord {
+                    data: [("user_id".to_string(), serde_json::json!(42))].into(),
+                    created_at: now() - 100,
+                    expires_at: now() - 1,
+                },
+            )
+            .await
+            .unwrap();
+
+        call(&layer, Some(&format!("tuono_session={id}")), |session| {
+            assert_eq!(session.get::<u32>("user_id"), None)
+        })
+        .await;
+
+        assert_eq!(store.load(&id).await.unwrap(), None);
+    }
+}
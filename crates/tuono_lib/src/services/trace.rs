@@ -0,0 +1,110 @@
+use crate::telemetry::{self, AttributeValue, Span, SpanContext, SpanKind};
+use axum::body::Body;
+use axum::extract::MatchedPath;
+use axum::response::Response;
+use futures_util::future::BoxFuture;
+use http::Request;
+use std::task::{Context, Poll};
+use tower::{Layer, Service};
+
+/// Create the OpenTelemetry span of each request.
+///
+/// The trace started by the caller is continued when the request
+/// has a valid W3C `traceparent` header.
+#[derive(Clone, Debug, Default)]
+pub struct TraceLayer {}
+
+impl TraceLayer {
+    pub fn new() -> Self {
+        TraceLayer {}
+    }
+}
+
+impl<S> Layer<S> for TraceLayer {
+    type Service = Trace<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Trace { inner }
+    }
+}
+
+#[derive(Clone)]
+pub struct Trace<S> {
+    inner: S,
+}
+
+fn request_span(req: &Request<Body>) -> Span {
+    let parent = req
+        .headers()
+        .get("traceparent")
+        .and_then(|value| value.to_str().ok())
+        .and_then(SpanContext::from_traceparent);
+    let method = req.method().as_str();
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(MatchedPath::as_str);
+
+    let name = match route {
+        Some(route) => format!("{method} {route}"),
+        None => method.to_string(),
+    };
+
+    let mut span = Span::start(name, SpanKind::Server, parent);
+    span.set_attribute(
+        "http.request.method",
+        AttributeValue::String(method.to_string()),
+    );
+    span.set_attribute(
+        "url.path",
+        AttributeValue::String(req.uri().path().to_string()),
+    );
+    if let Some(route) = route {
+        span.set_attribute("http.route", AttributeValue::String(route.to_string()));
+    }
+
+    span
+}
+
+impl<S> Service<Request<Body>> for Trace<S>
+where
+    S: Service<Request<Body>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Response, S::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        if !telemetry::is_enabled() {
+            return Box::pin(inner.call(req));
+        }
+
+        let mut span = request_span(&req);
+
+        Box::pin(async move {
+            let result = telemetry::in_span(span.context(), inner.call(req)).await;
+
+            if let Ok(response) = &result {
+                let status = response.status();
+                span.set_attribute(
+                    "http.response.status_code",
+                    AttributeValue::Int(status.as_u16().into()),
+                );
+                if status.is_server_error() {
+                    span.set_error();
+                }
+            }
+
+            span.end();
+            result
+        })
+    }
+}
@@ -0,0 +1,178 @@
+use super::store::{SessionRecord, SessionStore, StoreFuture};
+use super::{is_valid_session_id, now};
+use std::io;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+/// Keep each session in a JSON file.
+///
+/// The sessions survive the server restarts, which makes this store
+/// handy during development.
+#[derive(Debug, Clone)]
+pub struct FileStore {
+    directory: PathBuf,
+}
+
+impl FileStore {
+    pub fn new(directory: impl AsRef<Path>) -> Self {
+        FileStore {
+            directory: directory.as_ref().to_path_buf(),
+        }
+    }
+
+    fn record_path(&self, id: &str) -> io::Result<PathBuf> {
+        // The id is used as file name: never allow path traversals
+        if !is_valid_session_id(id) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Invalid session id",
+            ));
+        }
+        Ok(self.directory.join(format!("{id}.json")))
+    }
+}
+
+impl SessionStore for FileStore {
+    fn load<'a>(&'a self, id: &'a str) -> StoreFuture<'a, Option<SessionRecord>> {
+        Box::pin(async move {
+            let content = match fs::read(self.record_path(id)?).await {
+                Ok(content) => content,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+                Err(err) => return Err(err),
+            };
+
+            let record = serde_json::from_slice(&content)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+            Ok(Some(record))
+        })
+    }
+
+    fn save<'a>(&'a self, id: &'a str, record: &'a SessionRecord) -> StoreFuture<'a, ()> {
+        Box::pin(async move {
+            let path = self.record_path(id)?;
+            let content = serde_json::to_vec(record)?;
+
+            fs::create_dir_all(&self.directory).await?;
+
+            // Write and rename so that a crash never leaves a truncated record
+            let temp_path = path.with_extension("json.tmp");
+            fs::write(&temp_path, content).await?;
+            fs::rename(temp_path, path).await
+        })
+    }
+
+    fn delete<'a>(&'a self, id: &'a str) -> StoreFuture<'a, ()> {
+        Box::pin(async move {
+            match fs::remove_file(self.record_path(id)?).await {
+                Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+                _ => Ok(()),
+            }
+        })
+    }
+
+    fn purge_expired(&self) -> StoreFuture<'_, ()> {
+        Box::pin(async move {
+            let mut entries = match fs::read_dir(&self.directory).await {
+                Ok(entries) => entries,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+                Err(err) => return Err(err),
+            };
+
+            let now = now();
+
+            while let Some(entry) = entries.next_entry().await? {
+                let path = entry.path();
+                if path.extension().is_none_or(|ext| ext != "json") {
+                    continue;
+                }
+
+                let is_expired = match fs::read(&path).await {
+                    Ok(content) => serde_json::from_slice::<SessionRecord>(&content)
+                        .map(|record| record.is_expired(now))
+                        .unwrap_or(true),
+                    Err(_) => false,
+                };
+
+                if is_expired {
+                    let _ = fs::remove_file(path).await;
+                }
+            }
+
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::generate_session_id;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn records_survive_a_new_store_instance() {
+        let dir = tempdir().unwrap();
+        let id = generate_session_id();
+        let mut record = SessionRecord {
+            created_at: now(),
+            expires_at: now() + 60,
+            ..SessionRecord::default()
+        };
+        record
+            .data
+            .insert("user_id".to_string(), serde_json::json!(42));
+
+        FileStore::new(dir.path()).save(&id, &record).await.unwrap();
+
+        let store = FileStore::new(dir.path());
+        assert_eq!(store.load(&id).await.unwrap(), Some(record));
+
+        store.delete(&id).await.unwrap();
+        assert_eq!(store.load(&id).await.unwrap(), None);
+        // Deleting a missing session is not an error
+        store.delete(&id).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn it_rejects_invalid_ids() {
+        let dir = tempdir().unwrap();
+        let store = FileStore::new(dir.path());
+
+        assert!(store.load("../config").await.is_err());
+        assert!(
+            store
+                .save("../config", &SessionRecord::default())
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn it_purges_the_expired_records() {
+        let dir = tempdir().unwrap();
+        let store = FileStore::new(dir.path());
+        let expired = generate_session_id();
+        let valid = generate_session_id();
+
+        store
+            .save(&expired, &SessionRecord::default())
+            .await
+            .unwrap();
+        store
+            .save(
+                &valid,
+                &SessionRecord {
+                    expires_at: now() + 60,
+                    ..SessionRecord::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        store.purge_expired().await.unwrap();
+
+        assert!(store.load(&expired).await.unwrap().is_none());
+        assert!(store.load(&valid).await.unwrap().is_some());
+    }
+}
@@ -0,0 +1,103 @@
+use super::now;
+use super::store::{SessionRecord, SessionStore, StoreFuture};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, PoisonError};
+
+/// Number of saves after which the expired sessions are removed
+const PURGE_INTERVAL: usize = 100;
+
+/// Keep the sessions in the server memory.
+///
+/// The sessions are lost when the server restarts.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    records: Mutex<HashMap<String, SessionRecord>>,
+    saves: AtomicUsize,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        MemoryStore::default()
+    }
+
+    fn purge(&self) {
+        let now = now();
+        self.records
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .retain(|_, record| !record.is_expired(now));
+    }
+}
+
+impl SessionStore for MemoryStore {
+    fn load<'a>(&'a self, id: &'a str) -> StoreFuture<'a, Option<SessionRecord>> {
+        let record = self
+            .records
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(id)
+            .cloned();
+
+        Box::pin(async move { Ok(record) })
+    }
+
+    fn save<'a>(&'a self, id: &'a str, record: &'a SessionRecord) -> StoreFuture<'a, ()> {
+        if self.saves.fetch_add(1, Ordering::Relaxed) % PURGE_INTERVAL == PURGE_INTERVAL - 1 {
+            self.purge();
+        }
+
+        self.records
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(id.to_string(), record.clone());
+
+        Box::pin(async { Ok(()) })
+    }
+
+    fn delete<'a>(&'a self, id: &'a str) -> StoreFuture<'a, ()> {
+        self.records
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(id);
+
+        Box::pin(async { Ok(()) })
+    }
+
+    fn purge_expired(&self) -> StoreFuture<'_, ()> {
+        self.purge();
+        Box::pin(async { Ok(()) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn it_saves_loads_and_deletes_records() {
+        let store = MemoryStore::new();
+        let record = SessionRecord {
+            expires_at: now() + 60,
+            ..SessionRecord::default()
+        };
+
+        store.save("id", &record).await.unwrap();
+        assert_eq!(store.load("id").await.unwrap(), Some(record));
+
+        store.delete("id").await.unwrap();
+        assert_eq!(store.load("id").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn it_purges_the_expired_records() {
+        let store = MemoryStore::new();
+        store
+            .save("expired", &SessionRecord::default())
+            .await
+            .unwrap();
+
+        store.purge_expired().await.unwrap();
+        assert_eq!(store.load("expired").await.unwrap(), None);
+    }
+}
@@ -0,0 +1,235 @@
+//! Server side sessions.
+//!
+//! Every request handled by a route or an api carries a [`Session`]
+//! that can be read with `req.session()`. The session data is kept in a
+//! [`SessionStore`] and the client only receives the session id cookie.
+//!
+//! ```ignore
+//! #[tuono_lib::handler]
+//! async fn login(req: Request) -> Response {
+//!     let session = req.session();
+//!     // Avoid session fixation when the user privileges change
+//!     session.rotate();
+//!     session.insert("user_id", 42).unwrap();
+//!
+//!     Response::Redirect("/dashboard".to_string())
+//! }
+//! ```
+//!
+//! The store is selected in the `session` section of the tuono config.
+//! External stores can be plugged in with [`set_session_store`] from the
+//! `main` function of `src/app.rs`.
+
+mod file;
+mod memory;
+mod store;
+
+pub use file::FileStore;
+pub use memory::MemoryStore;
+pub use store::{SessionRecord, SessionStore, StoreFuture};
+
+use colored::Colorize;
+use once_cell::sync::OnceCell;
+use rand::RngCore;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tuono_internal::config::{SessionConfig, SessionStoreKind};
+use tuono_internal::tuono_println;
+
+static SESSION_STORE: OnceCell<Arc<dyn SessionStore>> = OnceCell::new();
+
+/// How often the expired sessions are removed from the store
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Replace the store selected in the tuono config.
+///
+/// It must be called before the server starts (i.e. in the `main`
+/// function of `src/app.rs`). Subsequent calls are ignored.
+pub fn set_session_store(store: impl SessionStore) {
+    let _ = SESSION_STORE.set(Arc::new(store));
+}
+
+pub(crate) fn session_store(config: &SessionConfig) -> Arc<dyn SessionStore> {
+    SESSION_STORE
+        .get_or_init(|| match config.store {
+            SessionStoreKind::Memory => Arc::new(MemoryStore::new()),
+            SessionStoreKind::File => Arc::new(FileStore::new(&config.directory)),
+        })
+        .clone()
+}
+
+/// Remove the expired sessions at startup and then periodically
+/// so that they don't pile up on long running servers.
+pub(crate) fn spawn_purge_task(store: Arc<dyn SessionStore>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(PURGE_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+            if let Err(err) = store.purge_expired().await {
+                tuono_println!(
+                    "Failed to purge expired sessions: {}",
+                    err.to_string().red()
+                );
+            }
+        }
+    });
+}
+
+/// Seconds since the UNIX epoch
+pub(crate) fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+/// 256 bits of randomness encoded as hex
+pub(crate) fn generate_session_id() -> String {
+    let mut bytes = [0u8; 32];
+    rand::rng().fill_bytes(&mut bytes);
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Reject ids that were not generated by the server.
+/// The id comes from the client so it is checked before reaching the store.
+pub fn is_valid_session_id(id: &str) -> bool {
+    id.len() == 64 && id.bytes().all(|byte| byte.is_ascii_hexdigit())
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct SessionState {
+    /// `None` until the session is saved for the first time
+    pub id: Option<String>,
+    pub record: SessionRecord,
+    pub changed: bool,
+    pub rotated: bool,
+    pub destroyed: bool,
+}
+
+/// The session attached to the current request.
+///
+/// The handle is cheap to clone and all the clones share the same data.
+/// Changes are saved in the store once the response is created.
+#[derive(Debug, Clone, Default)]
+pub struct Session {
+    state: Arc<Mutex<SessionState>>,
+}
+
+impl Session {
+    pub(crate) fn new(id: Option<String>, record: SessionRecord) -> Self {
+        Session {
+            state: Arc::new(Mutex::new(SessionState {
+                id,
+                record,
+                ..SessionState::default()
+            })),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, SessionState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub(crate) fn take_state(&self) -> SessionState {
+        std::mem::take(&mut *self.state())
+    }
+
+    /// The session id. `None` for sessions not saved yet.
+    pub fn id(&self) -> Option<String> {
+        self.state().id.clone()
+    }
+
+    /// Read a value. Returns `None` when the key is missing or
+    /// when the stored value can't be deserialized into `T`.
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let state = self.state();
+        let value = state.record.data.get(key)?;
+        serde_json::from_value(value.clone()).ok()
+    }
+
+    pub fn insert<T: Serialize>(&self, key: &str, value: T) -> Result<(), serde_json::Error> {
+        let value = serde_json::to_value(value)?;
+        let mut state = self.state();
+        state.record.data.insert(key.to_string(), value);
+        state.changed = true;
+        Ok(())
+    }
+
+    pub fn remove<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let mut state = self.state();
+        let value = state.record.data.remove(key)?;
+        state.changed = true;
+        serde_json::from_value(value).ok()
+    }
+
+    /// Remove all the values keeping the session alive
+    pub fn clear(&self) {
+        let mut state = self.state();
+        state.record.data.clear();
+        state.changed = true;
+    }
+
+    /// Move the data to a new session id.
+    /// Call it on login or whenever the user privileges change.
+    pub fn rotate(&self) {
+        let mut state = self.state();
+        state.rotated = true;
+        state.changed = true;
+    }
+
+    /// Delete the session from the store and the client (e.g. on logout)
+    pub fn destroy(&self) {
+        let mut state = self.state();
+        state.record.data.clear();
+        state.destroyed = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct User {
+        id: u32,
+        name: String,
+    }
+
+    #[test]
+    fn it_reads_and_writes_typed_values() {
+        let session = Session::default();
+        let user = User {
+            id: 1,
+            name: "tuono".to_string(),
+        };
+
+        session.insert("user", &user).unwrap();
+
+        assert_eq!(session.get::<User>("user"), Some(user));
+        assert_eq!(session.get::<u32>("user"), None);
+        assert_eq!(session.get::<User>("missing"), None);
+        assert!(session.take_state().changed);
+    }
+
+    #[test]
+    fn clones_share_the_same_data() {
+        let session = Session::default();
+        session.clone().insert("count", 1).unwrap();
+
+        assert_eq!(session.remove::<u32>("count"), Some(1));
+        assert_eq!(session.get::<u32>("count"), None);
+    }
+
+    #[test]
+    fn it_generates_valid_session_ids() {
+        let id = generate_session_id();
+        assert!(is_valid_session_id(&id));
+        assert_ne!(id, generate_session_id());
+        assert!(!is_valid_session_id("../../etc/passwd"));
+    }
+}
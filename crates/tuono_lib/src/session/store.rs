@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+
+pub type StoreFuture<'a, T> = Pin<Box<dyn Future<Output = io::Result<T>> + Send + 'a>>;
+
+/// The session data persisted by the stores
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct SessionRecord {
+    pub data: HashMap<String, serde_json::Value>,
+    /// Seconds since the UNIX epoch
+    pub created_at: u64,
+    /// Seconds since the UNIX epoch. It is the earliest between
+    /// the idle and the absolute expiration.
+    pub expires_at: u64,
+}
+
+impl SessionRecord {
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expires_at <= now
+    }
+}
+
+/// The backend where the sessions are kept.
+///
+/// Implement it to keep the sessions in an external service (i.e. redis)
+/// and register it with [`super::set_session_store`].
+pub trait SessionStore: Send + Sync + 'static {
+    /// Expired records can be returned: they are discarded by the caller
+    fn load<'a>(&'a self, id: &'a str) -> StoreFuture<'a, Option<SessionRecord>>;
+
+    fn save<'a>(&'a self, id: &'a str, record: &'a SessionRecord) -> StoreFuture<'a, ()>;
+
+    fn delete<'a>(&'a self, id: &'a str) -> StoreFuture<'a, ()>;
+
+    /// Remove the expired records. Called when the server starts.
+    fn purge_expired(&self) -> StoreFuture<'_, ()> {
+        Box::pin(async { Ok(()) })
+    }
+}
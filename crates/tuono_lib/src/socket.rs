@@ -0,0 +1,220 @@
+use std::io;
+use std::net::SocketAddr;
+use tokio::net::TcpListener;
+use tuono_internal::config::ServerConfig;
+
+#[cfg(unix)]
+use std::path::{Path, PathBuf};
+#[cfg(unix)]
+use tokio::net::UnixListener;
+
+/// The socket accepting the server connections
+#[derive(Debug)]
+pub enum Socket {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(UnixListener, Option<SocketFile>),
+}
+
+impl Socket {
+    /// The address of the TCP socket.
+    /// Fails for the Unix sockets.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        match self {
+            Socket::Tcp(listener) => listener.local_addr(),
+            #[cfg(unix)]
+            Socket::Unix(..) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "The server is listening on a Unix socket",
+            )),
+        }
+    }
+
+    /// The address displayed when the server starts
+    pub(crate) fn display_address(&self, config: &ServerConfig) -> String {
+        match self {
+            Socket::Tcp(listener) if !config.binds_address() => listener
+                .local_addr()
+                .map(|address| address.to_string())
+                .unwrap_or_default(),
+            Socket::Tcp(_) => format!("{}:{}", config.host, config.port),
+            #[cfg(unix)]
+            Socket::Unix(listener, _) => match listener.local_addr() {
+                Ok(address) => match address.as_pathname() {
+                    Some(path) => format!("unix:{}", path.display()),
+                    None => "unix socket".to_string(),
+                },
+                Err(_) => "unix socket".to_string(),
+            },
+        }
+    }
+}
+
+/// The Unix socket file created by the server.
+/// It is removed when the server stops.
+#[cfg(unix)]
+#[derive(Debug)]
+pub struct SocketFile(PathBuf);
+
+#[cfg(unix)]
+impl Drop for SocketFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// Remove the socket file left by a server that didn't stop gracefully.
+/// Fails if another server is still listening on it.
+#[cfg(unix)]
+fn remove_stale_socket(path: &Path) -> Result<(), String> {
+    use std::os::unix::fs::FileTypeExt;
+
+    let Ok(metadata) = std::fs::symlink_metadata(path) else {
+        return Ok(());
+    };
+
+    if !metadata.file_type().is_socket() {
+        return Err(format!(
+            "{} already exists and is not a socket",
+            path.display()
+        ));
+    }
+
+    if std::os::unix::net::UnixStream::connect(path).is_ok() {
+        return Err(format!(
+            "{} is already used by another process",
+            path.display()
+        ));
+    }
+
+    std::fs::remove_file(path).map_err(|err| {
+        format!(
+            "Failed to remove the stale socket {}: {err}",
+            path.display()
+        )
+    })
+}
+
+#[cfg(unix)]
+fn bind_unix(path: &Path) -> Result<Socket, String> {
+    remove_stale_socket(path)?;
+
+    let listener = UnixListener::bind(path)
+        .map_err(|err| format!("Failed to bind to the socket {}: {err}", path.display()))?;
+
+    Ok(Socket::Unix(listener, Some(SocketFile(path.to_path_buf()))))
+}
+
+/// Take the first socket passed with the systemd socket activation protocol
+#[cfg(unix)]
+fn inherited_socket() -> Result<Socket, String> {
+    use std::os::fd::{FromRawFd, IntoRawFd};
+
+    /// The first inherited file descriptor
+    const SD_LISTEN_FDS_START: i32 = 3;
+
+    if let Ok(pid) = std::env::var("LISTEN_PID")
+        && pid.parse::<u32>() != Ok(std::process::id())
+    {
+        return Err("The inherited sockets belong to another process (LISTEN_PID)".into());
+    }
+
+    let fds = std::env::var("LISTEN_FDS")
+        .ok()
+        .and_then(|fds| fds.parse::<u32>().ok())
+        .unwrap_or(0);
+
+    if fds == 0 {
+        return Err("No socket inherited from the service manager (LISTEN_FDS)".into());
+    }
+
+    // SAFETY: with the socket activation the file descriptors starting
+    // from SD_LISTEN_FDS_START are owned by this process.
+    let tcp = unsafe { std::net::TcpListener::from_raw_fd(SD_LISTEN_FDS_START) };
+
+    let socket = if tcp.local_addr().is_ok() {
+        tcp.set_nonblocking(true)
+            .and_then(|_| TcpListener::from_std(tcp))
+            .map(Socket::Tcp)
+    } else {
+        // Not an IP socket: the service manager passed a Unix one
+        // SAFETY: the file descriptor ownership moves to the Unix listener
+        let unix = unsafe { std::os::unix::net::UnixListener::from_raw_fd(tcp.into_raw_fd()) };
+        unix.local_addr()
+            .and_then(|_| unix.set_nonblocking(true))
+            .and_then(|_| UnixListener::from_std(unix))
+            .map(|listener| Socket::Unix(listener, None))
+    };
+
+    socket.map_err(|err| format!("Failed to use the inherited socket: {err}"))
+}
+
+/// Bind the socket configured by the `server` options
+pub(crate) async fn bind(config: &ServerConfig) -> Result<Socket, String> {
+    #[cfg(unix)]
+    {
+        if config.socket_activation {
+            return inherited_socket();
+        }
+
+        if let Some(path) = &config.unix_socket {
+            return bind_unix(path);
+        }
+    }
+
+    #[cfg(not(unix))]
+    if !config.binds_address() {
+        return Err("The Unix sockets and the socket activation require a Unix system".into());
+    }
+
+    let address = format!("{}:{}", config.host, config.port);
+
+    TcpListener::bind(&address)
+        .await
+        .map(Socket::Tcp)
+        .map_err(|err| format!("Failed to bind to address {address}: {err}"))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn it_replaces_the_stale_socket_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tuono.sock");
+
+        // A socket file without a listener
+        drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+        assert!(path.exists());
+
+        let socket = bind_unix(&path).unwrap();
+        assert!(path.exists());
+        assert!(socket.local_addr().is_err());
+
+        drop(socket);
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn it_refuses_the_socket_used_by_another_server() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tuono.sock");
+
+        let _socket = bind_unix(&path).unwrap();
+
+        let err = bind_unix(&path).err().unwrap();
+        assert!(err.ends_with("is already used by another process"));
+    }
+
+    #[tokio::test]
+    async fn it_refuses_to_replace_a_regular_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tuono.sock");
+        std::fs::write(&path, "").unwrap();
+
+        let err = bind_unix(&path).err().unwrap();
+        assert!(err.ends_with("already exists and is not a socket"));
+        assert!(path.exists());
+    }
+}
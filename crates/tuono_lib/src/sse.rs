@@ -0,0 +1,198 @@
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response as AxumResponse};
+use colored::Colorize;
+use futures_util::stream::{BoxStream, Stream, StreamExt};
+use serde::Serialize;
+use std::convert::Infallible;
+use std::time::Duration;
+use tuono_internal::tuono_println;
+
+const DEFAULT_KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+fn assert_single_line(name: &str, value: &str) {
+    assert!(
+        !value.contains(['\r', '\n', '\0']),
+        "The event {name} cannot contain new lines or null characters"
+    );
+}
+
+/// An event sent by `SseResponse`.
+/// The data is serialized as JSON.
+pub struct SseEvent<T> {
+    data: T,
+    id: Option<String>,
+    event: Option<String>,
+    retry: Option<Duration>,
+}
+
+impl<T> SseEvent<T> {
+    pub fn new(data: T) -> Self {
+        SseEvent {
+            data,
+            id: None,
+            event: None,
+            retry: None,
+        }
+    }
+
+    /// Sent back by the browser in the `Last-Event-ID` header when it reconnects.
+    /// Read it with `Request::last_event_id` to resume the stream.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` contains new lines or null characters.
+    pub fn id(mut self, id: impl Into<String>) -> Self {
+        let id = id.into();
+        assert_single_line("id", &id);
+        self.id = Some(id);
+        self
+    }
+
+    /// The event name listened by `EventSource.addEventListener`.
+    /// Unnamed events are dispatched as `message`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `event` contains new lines or null characters.
+    pub fn event(mut self, event: impl Into<String>) -> Self {
+        let event = event.into();
+        assert_single_line("name", &event);
+        self.event = Some(event);
+        self
+    }
+
+    /// How long the browser waits before reconnecting
+    pub fn retry(mut self, retry: Duration) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+}
+
+impl<T: Serialize> SseEvent<T> {
+    fn into_event(self) -> Result<Event, axum::Error> {
+        let mut event = Event::default().json_data(&self.data)?;
+
+        if let Some(id) = self.id {
+            event = event.id(id);
+        }
+        if let Some(name) = self.event {
+            event = event.event(name);
+        }
+        if let Some(retry) = self.retry {
+            event = event.retry(retry);
+        }
+
+        Ok(event)
+    }
+}
+
+/// A Server-Sent Events response.
+///
+/// The connection is kept open until the stream ends. A keep-alive comment
+/// is sent every 15 seconds so that the proxies don't close it.
+///
+/// The CLI exports the `T` type of the api route as
+/// `RouteEvent<'/api/route'>` in the `"tuono/types"` module.
+pub struct SseResponse<T> {
+    events: BoxStream<'static, SseEvent<T>>,
+    keep_alive: Option<Duration>,
+}
+
+impl<T> SseResponse<T> {
+    pub fn new<S>(events: S) -> Self
+    where
+        S: Stream<Item = SseEvent<T>> + Send + 'static,
+    {
+        SseResponse {
+            events: events.boxed(),
+            keep_alive: Some(DEFAULT_KEEP_ALIVE),
+        }
+    }
+
+    pub fn keep_alive(&mut self, interval: Duration) {
+        self.keep_alive = Some(interval);
+    }
+
+    pub fn disable_keep_alive(&mut self) {
+        self.keep_alive = None;
+    }
+}
+
+impl<T: Serialize + Send + 'static> IntoResponse for SseResponse<T> {
+    fn into_response(self) -> AxumResponse {
+        let events = self.events.filter_map(|event| async move {
+            match event.into_event() {
+                Ok(event) => Some(Ok::<_, Infallible>(event)),
+                Err(err) => {
+                    tuono_println!("Failed to serialize the event: {}", err.to_string().red());
+                    None
+                }
+            }
+        });
+
+        let sse = Sse::new(events);
+
+        match self.keep_alive {
+            Some(interval) => sse
+                .keep_alive(KeepAlive::new().interval(interval))
+                .into_response(),
+            None => sse.into_response(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::header::CONTENT_TYPE;
+
+    #[derive(Serialize)]
+    struct Notification {
+        title: &'static str,
+    }
+
+    async fn body(response: AxumResponse) -> String {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn it_streams_the_serialized_events() {
+        let events = futures_util::stream::iter([
+            SseEvent::new(Notification { title: "first" }).id("1"),
+            SseEvent::new(Notification { title: "second" })
+                .id("2")
+                .event("update")
+                .retry(Duration::from_secs(5)),
+        ]);
+
+        let response = SseResponse::new(events).into_response();
+
+        assert_eq!(response.headers()[CONTENT_TYPE], "text/event-stream");
+        assert_eq!(
+            body(response).await,
+            "data: {\"title\":\"first\"}\nid: 1\n\n\
+             data: {\"title\":\"second\"}\nid: 2\nevent: update\nretry: 5000\n\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn it_sends_the_keep_alive_comments() {
+        let events = futures_util::stream::pending::<SseEvent<Notification>>();
+        let mut response = SseResponse::new(events);
+        response.keep_alive(Duration::from_millis(10));
+
+        let mut body = response.into_response().into_body().into_data_stream();
+        let chunk = body.next().await.unwrap().unwrap();
+
+        assert!(chunk.starts_with(b":"));
+    }
+
+    #[test]
+    #[should_panic(expected = "The event id cannot contain new lines")]
+    fn it_rejects_the_multiline_ids() {
+        let _ = SseEvent::new(()).id("1\n2");
+    }
+}
@@ -1,8 +1,11 @@
+use crate::metrics;
 use crate::mode::{GLOBAL_MODE, Mode};
+use crate::telemetry::Span;
 use ssr_rs::{Ssr, SsrError};
 use std::cell::RefCell;
 use std::fs::read_to_string;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
 
 /// For the server side rendering we need to split the implementation between dev and prod.
 /// This completely remove the multi-thread optimization on dev but allow the dev server to
@@ -22,12 +25,36 @@ const DEV_BUNDLE_PATH: &str = "./.tuono/server/dev-server.js";
 impl Js {
     pub fn render_to_string(payload: Option<&str>) -> Result<String, SsrError> {
         let mode = GLOBAL_MODE.get().expect("Failed to get GLOBAL_MODE");
+        let start = Instant::now();
+        let span = Span::start_child("ssr render");
 
-        if *mode == Mode::Dev {
+        let result = if *mode == Mode::Dev {
             DevJs::render_to_string(payload)
         } else {
             ProdJs::SSR.with(|ssr| ssr.borrow_mut().render_to_string(payload))
+        };
+
+        metrics::record_render(start.elapsed(), result.is_ok());
+
+        if let Some(mut span) = span {
+            if result.is_err() {
+                span.set_error();
+            }
+            span.end();
         }
+
+        result
+    }
+
+    /// The server bundle loaded by the SSR in `mode` exists
+    pub fn is_bundle_available(mode: Mode) -> bool {
+        let bundle_path = if mode == Mode::Dev {
+            DEV_BUNDLE_PATH
+        } else {
+            PROD_BUNDLE_PATH
+        };
+
+        Path::new(bundle_path).is_file()
     }
 }
 
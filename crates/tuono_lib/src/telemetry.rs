@@ -0,0 +1,441 @@
+use colored::Colorize;
+use once_cell::sync::OnceCell;
+use serde_json::{Value, json};
+use std::fmt::Write;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::{mpsc, oneshot};
+use tuono_internal::config::TelemetryConfig;
+use tuono_internal::tuono_println;
+
+/// Spans exported in a single OTLP request
+const MAX_BATCH_SIZE: usize = 512;
+/// Spans waiting for the export. The new ones are dropped when full.
+const QUEUE_SIZE: usize = 4096;
+const EXPORT_INTERVAL: Duration = Duration::from_secs(2);
+
+tokio::task_local! {
+    /// The span wrapping the code being executed
+    static CURRENT_SPAN: SpanContext;
+}
+
+/// The W3C trace context of a span
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct SpanContext {
+    trace_id: [u8; 16],
+    span_id: [u8; 8],
+    sampled: bool,
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut hex, byte| {
+        let _ = write!(hex, "{byte:02x}");
+        hex
+    })
+}
+
+fn from_hex<const N: usize>(hex: &str) -> Option<[u8; N]> {
+    if hex.len() != N * 2
+        || !hex
+            .bytes()
+            .all(|byte| matches!(byte, b'0'..=b'9' | b'a'..=b'f'))
+    {
+        return None;
+    }
+
+    let mut bytes = [0; N];
+    for (index, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[index * 2..index * 2 + 2], 16).ok()?;
+    }
+
+    // All zeros ids are invalid
+    bytes.iter().any(|byte| *byte != 0).then_some(bytes)
+}
+
+impl SpanContext {
+    fn root() -> Self {
+        SpanContext {
+            trace_id: rand::random(),
+            span_id: rand::random(),
+            sampled: true,
+        }
+    }
+
+    fn child(&self) -> Self {
+        SpanContext {
+            span_id: rand::random(),
+            ..*self
+        }
+    }
+
+    /// Parse the `traceparent` header (`{version}-{trace-id}-{parent-id}-{flags}`)
+    pub fn from_traceparent(traceparent: &str) -> Option<Self> {
+        let mut parts = traceparent.trim().split('-');
+        let version = parts.next()?;
+        let trace_id = from_hex::<16>(parts.next()?)?;
+        let span_id = from_hex::<8>(parts.next()?)?;
+        let flags = parts.next().filter(|flags| flags.len() == 2)?;
+        let flags = u8::from_str_radix(flags, 16).ok()?;
+
+        // Future versions can append fields, the version 00 can't
+        if version.len() != 2 || version == "ff" || (version == "00" && parts.next().is_some()) {
+            return None;
+        }
+
+        Some(SpanContext {
+            trace_id,
+            span_id,
+            sampled: flags & 1 == 1,
+        })
+    }
+
+    pub fn traceparent(&self) -> String {
+        format!(
+            "00-{}-{}-{:02x}",
+            to_hex(&self.trace_id),
+            to_hex(&self.span_id),
+            u8::from(self.sampled)
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SpanKind {
+    Internal = 1,
+    Server = 2,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum AttributeValue {
+    String(String),
+    Int(i64),
+}
+
+impl AttributeValue {
+    fn to_otlp(&self) -> Value {
+        match self {
+            AttributeValue::String(value) => json!({ "stringValue": value }),
+            // OTLP JSON encodes the 64 bits integers as strings
+            AttributeValue::Int(value) => json!({ "intValue": value.to_string() }),
+        }
+    }
+}
+
+fn unix_nanos(time: SystemTime) -> String {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos()
+        .to_string()
+}
+
+/// A span sent to the exporter when it ends
+#[derive(Debug)]
+pub(crate) struct Span {
+    context: SpanContext,
+    parent_span_id: Option<[u8; 8]>,
+    name: String,
+    kind: SpanKind,
+    start: SystemTime,
+    attributes: Vec<(&'static str, AttributeValue)>,
+    is_error: bool,
+}
+
+impl Span {
+    /// Start a span continuing the `parent` trace or a new one
+    pub fn start(name: impl Into<String>, kind: SpanKind, parent: Option<SpanContext>) -> Self {
+        let context = parent.map_or_else(SpanContext::root, |parent| parent.child());
+
+        Span {
+            context,
+            parent_span_id: parent.map(|parent| parent.span_id),
+            name: name.into(),
+            kind,
+            start: SystemTime::now(),
+            attributes: Vec::new(),
+            is_error: false,
+        }
+    }
+
+    /// Start a child of the current span.
+    /// `None` when the code is not traced.
+    pub fn start_child(name: impl Into<String>) -> Option<Self> {
+        current_context().map(|parent| Span::start(name, SpanKind::Internal, Some(parent)))
+    }
+
+    pub fn context(&self) -> SpanContext {
+        self.context
+    }
+
+    pub fn set_attribute(&mut self, key: &'static str, value: AttributeValue) {
+        self.attributes.push((key, value));
+    }
+
+    pub fn set_error(&mut self) {
+        self.is_error = true;
+    }
+
+    fn to_otlp(&self, end: SystemTime) -> Value {
+        let attributes: Vec<Value> = self
+            .attributes
+            .iter()
+            .map(|(key, value)| json!({ "key": key, "value": value.to_otlp() }))
+            .collect();
+
+        let mut span = json!({
+            "traceId": to_hex(&self.context.trace_id),
+            "spanId": to_hex(&self.context.span_id),
+            "name": self.name,
+            "kind": self.kind as u8,
+            "startTimeUnixNano": unix_nanos(self.start),
+            "endTimeUnixNano": unix_nanos(end),
+            "attributes": attributes,
+            // Unset or error
+            "status": { "code": if self.is_error { 2 } else { 0 } },
+        });
+
+        if let Some(parent_span_id) = self.parent_span_id {
+            span["parentSpanId"] = json!(to_hex(&parent_span_id));
+        }
+
+        span
+    }
+
+    /// Send the span to the exporter when the trace is sampled
+    pub fn end(self) {
+        let Some(exporter) = EXPORTER.get() else {
+            return;
+        };
+
+        if self.context.sampled {
+            // The span is dropped when the collector can't keep up
+            let _ = exporter.try_send(Message::Span(self.to_otlp(SystemTime::now())));
+        }
+    }
+}
+
+fn current_context() -> Option<SpanContext> {
+    CURRENT_SPAN.try_with(|context| *context).ok()
+}
+
+/// Run `future` as the current span
+pub(crate) async fn in_span<F: Future>(context: SpanContext, future: F) -> F::Output {
+    CURRENT_SPAN.scope(context, future).await
+}
+
+/// The `traceparent` header value of the current span.
+/// Forward it to the called services to propagate the trace.
+pub fn current_traceparent() -> Option<String> {
+    current_context().map(|context| context.traceparent())
+}
+
+/// Trace the user handler as a child of the request span
+#[doc(hidden)]
+pub async fn tuono_internal_trace_handler<F: Future>(name: &'static str, handler: F) -> F::Output {
+    let Some(mut span) = Span::start_child(format!("handler {name}")) else {
+        return handler.await;
+    };
+    span.set_attribute("code.function.name", AttributeValue::String(name.into()));
+
+    let output = in_span(span.context(), handler).await;
+    span.end();
+    output
+}
+
+enum Message {
+    Span(Value),
+    Flush(oneshot::Sender<()>),
+}
+
+static EXPORTER: OnceCell<mpsc::Sender<Message>> = OnceCell::new();
+
+pub(crate) fn is_enabled() -> bool {
+    EXPORTER.get().is_some()
+}
+
+/// Start exporting the spans to the OTLP collector
+pub(crate) fn init(config: &TelemetryConfig) {
+    if !config.enabled || is_enabled() {
+        return;
+    }
+
+    let (sender, receiver) = mpsc::channel(QUEUE_SIZE);
+    let url = format!("{}/v1/traces", config.endpoint.trim_end_matches('/'));
+    let resource = json!({
+        "attributes": [{
+            "key": "service.name",
+            "value": { "stringValue": config.service_name },
+        }],
+    });
+
+    if EXPORTER.set(sender).is_err() {
+        return;
+    }
+
+    // The exporter has its own runtime so that it outlives the server one
+    // and can export the spans ending during the shutdown.
+    let spawned = std::thread::Builder::new()
+        .name("tuono-otlp-exporter".to_string())
+        .spawn(move || {
+            match tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+            {
+                Ok(runtime) => runtime.block_on(export(receiver, url, resource)),
+                Err(err) => tuono_println!(
+                    "Failed to start the traces exporter: {}",
+                    err.to_string().red()
+                ),
+            }
+        });
+
+    if let Err(err) = spawned {
+        tuono_println!(
+            "Failed to start the traces exporter: {}",
+            err.to_string().red()
+        );
+    }
+}
+
+/// Export the spans still queued (i.e. before the server stops)
+pub(crate) async fn flush() {
+    let Some(exporter) = EXPORTER.get() else {
+        return;
+    };
+
+    let (done, flushed) = oneshot::channel();
+    if exporter.send(Message::Flush(done)).await.is_ok() {
+        let _ = flushed.await;
+    }
+}
+
+async fn send_batch(client: &reqwest::Client, url: &str, resource: &Value, batch: &mut Vec<Value>) {
+    if batch.is_empty() {
+        return;
+    }
+
+    let body = json!({
+        "resourceSpans": [{
+            "resource": resource,
+            "scopeSpans": [{
+                "scope": { "name": "tuono", "version": env!("CARGO_PKG_VERSION") },
+                "spans": std::mem::take(batch),
+            }],
+        }],
+    });
+
+    let result = client
+        .post(url)
+        .json(&body)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status());
+
+    if let Err(err) = result {
+        tuono_println!("Failed to export the traces: {}", err.to_string().red());
+    }
+}
+
+async fn export(mut receiver: mpsc::Receiver<Message>, url: String, resource: Value) {
+    let client = reqwest::Client::new();
+    let mut batch = Vec::new();
+    let mut interval = tokio::time::interval(EXPORT_INTERVAL);
+
+    loop {
+        tokio::select! {
+            message = receiver.recv() => match message {
+                Some(Message::Span(span)) => {
+                    batch.push(span);
+                    if batch.len() >= MAX_BATCH_SIZE {
+                        send_batch(&client, &url, &resource, &mut batch).await;
+                    }
+                }
+                Some(Message::Flush(done)) => {
+                    send_batch(&client, &url, &resource, &mut batch).await;
+                    let _ = done.send(());
+                }
+                None => {
+                    send_batch(&client, &url, &resource, &mut batch).await;
+                    return;
+                }
+            },
+            _ = interval.tick() => send_batch(&client, &url, &resource, &mut batch).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRACEPARENT: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+    #[test]
+    fn it_parses_the_traceparent_header() {
+        let context = SpanContext::from_traceparent(TRACEPARENT).unwrap();
+
+        assert!(context.sampled);
+        assert_eq!(context.traceparent(), TRACEPARENT);
+
+        let context = SpanContext::from_traceparent(
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-00",
+        )
+        .unwrap();
+        assert!(!context.sampled);
+    }
+
+    #[test]
+    fn it_rejects_the_invalid_traceparent_headers() {
+        for traceparent in [
+            "",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7",
+            "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-0000000000000000-01",
+            "00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01",
+            "ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra",
+        ] {
+            assert_eq!(
+                SpanContext::from_traceparent(traceparent),
+                None,
+                "{traceparent}"
+            );
+        }
+
+        // Future versions can append fields
+        assert!(
+            SpanContext::from_traceparent(
+                "01-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra"
+            )
+            .is_some()
+        );
+    }
+
+    #[test]
+    fn it_continues_the_remote_trace() {
+        let parent = SpanContext::from_traceparent(TRACEPARENT).unwrap();
+        let mut span = Span::start("GET /posts/{slug}", SpanKind::Server, Some(parent));
+        span.set_attribute("http.response.status_code", AttributeValue::Int(500));
+        span.set_error();
+
+        let otlp = span.to_otlp(SystemTime::now());
+
+        assert_eq!(otlp["traceId"], "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert_eq!(otlp["parentSpanId"], "00f067aa0ba902b7");
+        assert_ne!(otlp["spanId"], "00f067aa0ba902b7");
+        assert_eq!(otlp["kind"], 2);
+        assert_eq!(otlp["status"]["code"], 2);
+        assert_eq!(
+            otlp["attributes"][0],
+            json!({ "key": "http.response.status_code", "value": { "intValue": "500" } })
+        );
+    }
+
+    #[tokio::test]
+    async fn it_exposes_the_current_traceparent() {
+        assert_eq!(current_traceparent(), None);
+
+        let context = SpanContext::from_traceparent(TRACEPARENT).unwrap();
+        let traceparent = in_span(context, async { current_traceparent() }).await;
+
+        assert_eq!(traceparent.as_deref(), Some(TRACEPARENT));
+    }
+}
@@ -0,0 +1,287 @@
+use axum::extract::Request;
+use axum::http::{StatusCode, Uri, header, uri::Authority};
+use axum::response::{IntoResponse, Redirect};
+use axum::routing::Router;
+use axum::serve::Listener;
+use colored::Colorize;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinSet;
+use tokio_rustls::TlsAcceptor;
+use tokio_rustls::rustls::crypto::ring;
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tokio_rustls::server::TlsStream;
+use tokio_util::either::Either;
+use tokio_util::sync::CancellationToken;
+use tuono_internal::config::TlsConfig;
+use tuono_internal::tuono_println;
+
+/// Clients not completing the handshake in time are disconnected
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Load the certificate chain and the private key of the HTTPS server.
+/// HTTP/2 is negotiated with ALPN when the client supports it.
+pub(crate) fn load_acceptor(config: &TlsConfig) -> Result<TlsAcceptor, String> {
+    let certs = CertificateDer::pem_file_iter(&config.cert)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|err| {
+            format!(
+                "Failed to read the TLS certificate {}: {err}",
+                config.cert.display()
+            )
+        })?;
+
+    if certs.is_empty() {
+        return Err(format!(
+            "No TLS certificate found in {}",
+            config.cert.display()
+        ));
+    }
+
+    let key = PrivateKeyDer::from_pem_file(&config.key).map_err(|err| {
+        format!(
+            "Failed to read the TLS private key {}: {err}",
+            config.key.display()
+        )
+    })?;
+
+    let mut tls = tokio_rustls::rustls::ServerConfig::builder_with_provider(Arc::new(
+        ring::default_provider(),
+    ))
+    .with_safe_default_protocol_versions()
+    .map_err(|err| err.to_string())?
+    .with_no_client_auth()
+    .with_single_cert(certs, key)
+    .map_err(|err| format!("Invalid TLS certificate or private key: {err}"))?;
+
+    tls.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+
+    Ok(TlsAcceptor::from(Arc::new(tls)))
+}
+
+/// The HTTPS settings of the server
+pub(crate) struct Tls {
+    pub acceptor: TlsAcceptor,
+    /// The plain HTTP listener redirecting to HTTPS
+    pub redirect: Option<TcpListener>,
+}
+
+impl std::fmt::Debug for Tls {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Tls")
+            .field("redirect", &self.redirect)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Tls {
+    pub async fn init(config: &TlsConfig, host: &str) -> Result<Tls, String> {
+        let acceptor = load_acceptor(config)?;
+
+        let redirect =
+            match config.redirect_port {
+                Some(port) => Some(TcpListener::bind(format!("{host}:{port}")).await.map_err(
+                    |err| format!("Failed to bind the HTTPS redirect port {port}: {err}"),
+                )?),
+                None => None,
+            };
+
+        Ok(Tls { acceptor, redirect })
+    }
+}
+
+type Handshake = Option<(TlsStream<TcpStream>, SocketAddr)>;
+
+/// Accept the plain TCP connections, or the TLS ones when an acceptor is set.
+///
+/// The handshakes run concurrently so that a slow client
+/// doesn't hold the other connections.
+pub(crate) struct ServerListener {
+    tcp: TcpListener,
+    tls: Option<TlsAcceptor>,
+    handshakes: JoinSet<Handshake>,
+}
+
+impl ServerListener {
+    pub fn new(tcp: TcpListener, tls: Option<TlsAcceptor>) -> Self {
+        ServerListener {
+            tcp,
+            tls,
+            handshakes: JoinSet::new(),
+        }
+    }
+}
+
+impl Listener for ServerListener {
+    type Io = Either<TcpStream, TlsStream<TcpStream>>;
+    type Addr = SocketAddr;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        loop {
+            tokio::select! {
+                (stream, address) = Listener::accept(&mut self.tcp) => {
+                    let Some(acceptor) = self.tls.clone() else {
+                        return (Either::Left(stream), address);
+                    };
+
+                    self.handshakes.spawn(async move {
+                        // Failed handshakes (i.e. plain HTTP requests or scanners) are dropped
+                        match tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                            Ok(Ok(stream)) => Some((stream, address)),
+                            _ => None,
+                        }
+                    });
+                }
+                Some(handshake) = self.handshakes.join_next(), if !self.handshakes.is_empty() => {
+                    if let Ok(Some((stream, address))) = handshake {
+                        return (Either::Right(stream), address);
+                    }
+                }
+            }
+        }
+    }
+
+    fn local_addr(&self) -> io::Result<Self::Addr> {
+        self.tcp.local_addr()
+    }
+}
+
+/// The HTTPS URL of the requested resource.
+/// `None` when the request has no valid `Host` header.
+fn https_location(host: Option<&str>, uri: &Uri, https_port: u16) -> Option<String> {
+    let authority: Authority = host?.parse().ok()?;
+    let path = uri.path_and_query().map_or("/", |path| path.as_str());
+
+    Some(if https_port == 443 {
+        format!("https://{}{path}", authority.host())
+    } else {
+        format!("https://{}:{https_port}{path}", authority.host())
+    })
+}
+
+/// The plain HTTP server permanently redirecting to the HTTPS one
+pub(crate) fn redirect_router(https_port: u16) -> Router {
+    Router::new().fallback(move |req: Request| async move {
+        let host = req
+            .headers()
+            .get(header::HOST)
+            .and_then(|host| host.to_str().ok());
+
+        match https_location(host, req.uri(), https_port) {
+            Some(location) => Redirect::permanent(&location).into_response(),
+            None => StatusCode::BAD_REQUEST.into_response(),
+        }
+    })
+}
+
+/// Serve the HTTPS redirects until the shutdown
+pub(crate) fn spawn_redirect(listener: TcpListener, https_port: u16, shutdown: CancellationToken) {
+    let server = axum::serve(listener, redirect_router(https_port))
+        .with_graceful_shutdown(shutdown.cancelled_owned());
+
+    tokio::spawn(async move {
+        if let Err(err) = server.await {
+            tuono_println!("HTTPS redirect server failed: {}", err.to_string().red());
+        }
+    });
+}
+
+/// Disable Nagle's algorithm on the accepted connections
+/// since the responses are written in few large chunks.
+pub(crate) fn set_nodelay(io: &mut Either<TcpStream, TlsStream<TcpStream>>) {
+    let stream = match io {
+        Either::Left(stream) => stream,
+        Either::Right(stream) => stream.get_ref().0,
+    };
+
+    let _ = stream.set_nodelay(true);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use tokio_rustls::TlsConnector;
+    use tokio_rustls::rustls::{ClientConfig, RootCertStore, pki_types::ServerName};
+
+    fn test_config() -> TlsConfig {
+        TlsConfig {
+            cert: PathBuf::from("tests/assets/tls/cert.pem"),
+            key: PathBuf::from("tests/assets/tls/key.pem"),
+            redirect_port: None,
+        }
+    }
+
+    #[test]
+    fn it_builds_the_https_location() {
+        let uri: Uri = "/blog/post?page=2".parse().unwrap();
+
+        assert_eq!(
+            https_location(Some("tuono.dev"), &uri, 443).as_deref(),
+            Some("https://tuono.dev/blog/post?page=2")
+        );
+        assert_eq!(
+            https_location(Some("localhost:3000"), &uri, 3443).as_deref(),
+            Some("https://localhost:3443/blog/post?page=2")
+        );
+        assert_eq!(
+            https_location(Some("[::1]:3000"), &Uri::from_static("/"), 3443).as_deref(),
+            Some("https://[::1]:3443/")
+        );
+        assert_eq!(https_location(None, &uri, 443), None);
+        assert_eq!(https_location(Some("bad host"), &uri, 443), None);
+    }
+
+    #[test]
+    fn it_reports_the_missing_certificate() {
+        let config = TlsConfig {
+            cert: PathBuf::from("missing/cert.pem"),
+            ..test_config()
+        };
+
+        let err = load_acceptor(&config).err().unwrap();
+        assert!(err.starts_with("Failed to read the TLS certificate missing/cert.pem"));
+    }
+
+    #[tokio::test]
+    async fn it_negotiates_http2_over_tls() {
+        let acceptor = load_acceptor(&test_config()).unwrap();
+        let tcp = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = tcp.local_addr().unwrap();
+        let mut listener = ServerListener::new(tcp, Some(acceptor));
+
+        let mut roots = RootCertStore::empty();
+        for cert in CertificateDer::pem_file_iter("tests/assets/tls/cert.pem").unwrap() {
+            roots.add(cert.unwrap()).unwrap();
+        }
+        let mut client = ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        client.alpn_protocols = vec![b"h2".to_vec()];
+
+        // A client never starting the handshake doesn't block the others
+        let _idle = TcpStream::connect(address).await.unwrap();
+
+        let connect = async {
+            let stream = TcpStream::connect(address).await.unwrap();
+            TlsConnector::from(Arc::new(client))
+                .connect(ServerName::try_from("localhost").unwrap(), stream)
+                .await
+                .unwrap()
+        };
+
+        let ((io, _), client_stream) = tokio::join!(listener.accept(), connect);
+
+        let Either::Right(server_stream) = io else {
+            panic!("Expected a TLS connection");
+        };
+        assert_eq!(server_stream.get_ref().1.alpn_protocol(), Some(&b"h2"[..]));
+        assert_eq!(client_stream.get_ref().1.alpn_protocol(), Some(&b"h2"[..]));
+    }
+}
@@ -18,9 +18,10 @@ pub async fn vite_reverse_proxy(
         .expect("Failed to get the internal config");
 
     let vite_url = format!(
-        "http://{}:{}/vite-server",
+        "http://{}:{}{}/vite-server",
         config.server.host,
-        config.server.port + 1
+        config.server.port + 1,
+        config.base_path
     );
 
     let query_string = query
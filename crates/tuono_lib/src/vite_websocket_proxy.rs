@@ -33,9 +33,10 @@ async fn handle_socket(mut tuono_socket: WebSocket) {
         .expect("Failed to get the internal config");
 
     let vite_ws = format!(
-        "ws://{}:{}/vite-server/",
+        "ws://{}:{}{}/vite-server/",
         config.server.host,
-        config.server.port + 1
+        config.server.port + 1,
+        config.base_path
     );
 
     let vite_ws_request = ClientRequestBuilder::new(vite_ws.parse().unwrap())
@@ -0,0 +1,97 @@
+use crate::config::GLOBAL_CONFIG;
+use axum::http::header::{HOST, ORIGIN};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response as AxumResponse};
+
+/// Whether the browser opening the WebSocket runs a page of an allowed origin:
+/// the server itself or one of the `cors.origins`.
+///
+/// The browsers don't apply the same-origin policy to the WebSockets,
+/// hence any website could otherwise open one with the user cookies.
+fn is_allowed_origin(headers: &HeaderMap, server_origin: Option<&str>, origins: &[String]) -> bool {
+    // Only the browsers send the header
+    let Some(origin) = headers.get(ORIGIN) else {
+        return true;
+    };
+    let Ok(origin) = origin.to_str() else {
+        return false;
+    };
+    let origin = origin.trim_end_matches('/');
+
+    if let Some(server_origin) = server_origin {
+        if server_origin
+            .trim_end_matches('/')
+            .eq_ignore_ascii_case(origin)
+        {
+            return true;
+        }
+    } else if let Some(host) = headers.get(HOST).and_then(|host| host.to_str().ok())
+        && origin
+            .split_once("://")
+            .is_some_and(|(_, origin_host)| origin_host.eq_ignore_ascii_case(host))
+    {
+        return true;
+    }
+
+    origins
+        .iter()
+        .any(|allowed| allowed == "*" || allowed.trim_end_matches('/') == origin)
+}
+
+/// Returns the response sent instead of upgrading the connection
+/// when the request comes from a page of another origin.
+#[doc(hidden)]
+pub fn tuono_internal_ws_origin_guard(headers: &HeaderMap) -> Option<AxumResponse> {
+    let (server_origin, origins) = GLOBAL_CONFIG
+        .get()
+        .map(|config| {
+            (
+                config.server.origin.as_deref(),
+                config.cors.origins.as_slice(),
+            )
+        })
+        .unwrap_or_default();
+
+    if is_allowed_origin(headers, server_origin, origins) {
+        return None;
+    }
+
+    Some((StatusCode::FORBIDDEN, "WebSocket origin not allowed").into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(origin: Option<&str>) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(HOST, "localhost:3000".parse().unwrap());
+        if let Some(origin) = origin {
+            headers.insert(ORIGIN, origin.parse().unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn it_allows_the_server_origin() {
+        let allowed = |origin| is_allowed_origin(&headers(origin), None, &[]);
+
+        assert!(allowed(None));
+        assert!(allowed(Some("http://localhost:3000")));
+        assert!(!allowed(Some("https://evil.example")));
+        assert!(!allowed(Some("null")));
+    }
+
+    #[test]
+    fn it_allows_the_configured_origins() {
+        let origins = vec!["https://m.tuono.dev".to_string()];
+        let allowed =
+            |origin| is_allowed_origin(&headers(Some(origin)), Some("https://tuono.dev"), &origins);
+
+        assert!(allowed("https://tuono.dev"));
+        assert!(allowed("https://m.tuono.dev"));
+        // The host is not trusted when the origin is configured
+        assert!(!allowed("http://localhost:3000"));
+        assert!(!allowed("https://evil.example"));
+    }
+}
@@ -108,6 +108,44 @@ async fn api_route_route() {
     );
 }
 
+#[tokio::test]
+#[serial]
+async fn file_download_route() {
+    let app = MockTuonoServer::spawn().await;
+
+    let client = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .unwrap();
+
+    let server_url = format!("http://{}:{}", &app.address, &app.port);
+
+    let response = client
+        .get(format!("{server_url}/download"))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert!(response.status().is_success());
+    assert_eq!(
+        response.headers()["content-disposition"],
+        "attachment; filename=\"report.csv\""
+    );
+    assert_eq!(response.text().await.unwrap(), "id,name");
+
+    let response = client
+        .get(format!("{server_url}/tuono/data/download"))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert!(response.status().is_success());
+    assert_eq!(
+        response.text().await.unwrap(),
+        "{\"data\":null,\"info\":{\"redirect_destination\":null,\"reload\":true}}"
+    );
+}
+
 #[tokio::test]
 #[serial]
 async fn it_reads_the_catch_all_path_parameter() {
@@ -152,6 +190,488 @@ async fn it_reads_the_path_parameter() {
     assert_eq!(response.text().await.unwrap(), "url_parameter");
 }
 
+#[tokio::test]
+#[serial]
+async fn it_applies_the_config_redirects_and_rewrites() {
+    let app = MockTuonoServer::spawn().await;
+
+    let client = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .unwrap();
+
+    let server_url = format!("http://{}:{}", &app.address, &app.port);
+
+    let response = client
+        .get(format!("{server_url}/old/url_parameter?page=1"))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(response.status(), reqwest::StatusCode::TEMPORARY_REDIRECT);
+    assert_eq!(
+        response.headers()["location"],
+        "/dynamic/url_parameter?page=1"
+    );
+
+    let response = client
+        .get(format!("{server_url}/rewritten"))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert!(response.status().is_success());
+    assert_eq!(response.text().await.unwrap(), "foobar");
+}
+
+#[tokio::test]
+#[serial]
+async fn it_rate_limits_the_route() {
+    let app = MockTuonoServer::spawn().await;
+
+    let client = reqwest::Client::new();
+
+    let server_url = format!("http://{}:{}", &app.address, &app.port);
+
+    let response = client
+        .get(format!("{server_url}/rate_limited"))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert!(response.status().is_success());
+
+    let response = client
+        .get(format!("{server_url}/rate_limited"))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(response.status(), 429);
+    assert_eq!(response.headers()["retry-after"], "60");
+}
+
+#[tokio::test]
+#[serial]
+async fn it_guards_the_authenticated_routes() {
+    let app = MockTuonoServer::spawn().await;
+
+    let client = reqwest::Client::new();
+
+    let server_url = format!("http://{}:{}", &app.address, &app.port);
+
+    let response = client
+        .get(format!("{server_url}/me"))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(response.status(), 401);
+
+    let response = client
+        .get(format!("{server_url}/me"))
+        .header("authorization", "Bearer tuono")
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert!(response.status().is_success());
+    assert_eq!(response.text().await.unwrap(), "tuono");
+}
+
+#[tokio::test]
+#[serial]
+async fn it_finalizes_the_scoped_resources() {
+    use crate::utils::scoped::FINALIZED;
+
+    let app = MockTuonoServer::spawn().await;
+
+    let client = reqwest::Client::new();
+
+    let server_url = format!("http://{}:{}", &app.address, &app.port);
+
+    for status in ["201", "500"] {
+        let response = client
+            .get(format!("{server_url}/scoped/{status}"))
+            .send()
+            .await
+            .expect("Failed to execute request.");
+
+        assert_eq!(response.status().as_str(), status);
+    }
+
+    assert_eq!(
+        *FINALIZED.lock().unwrap(),
+        vec![("201".to_string(), true), ("500".to_string(), false)]
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn it_exposes_the_health_endpoints() {
+    let app = MockTuonoServer::spawn().await;
+
+    let client = reqwest::Client::new();
+
+    let server_url = format!("http://{}:{}", &app.address, &app.port);
+
+    let response = client
+        .get(format!("{server_url}/__tuono/health"))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(response.status(), 200);
+
+    let response = client
+        .get(format!("{server_url}/__tuono/ready"))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(response.status(), 200);
+
+    let report: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(report["status"], "ready");
+    for check in ["ssr", "manifest", "database"] {
+        assert_eq!(report["checks"][check]["status"], "ok", "{check}");
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn it_exposes_the_prometheus_metrics() {
+    let app = MockTuonoServer::spawn().await;
+
+    let client = reqwest::Client::new();
+
+    let server_url = format!("http://{}:{}", &app.address, &app.port);
+
+    client
+        .get(format!("{server_url}/dynamic/tuono"))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    let response = client
+        .get(format!("{server_url}/metrics"))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(response.status(), 200);
+
+    let metrics = response.text().await.unwrap();
+    assert!(metrics.contains(
+        "tuono_http_requests_total{method=\"GET\",route=\"/dynamic/{parameter}\",kind=\"other\",status=\"200\"}"
+    ));
+    assert!(metrics.contains("# TYPE tuono_http_request_duration_seconds histogram"));
+    assert!(metrics.contains("tuono_http_requests_in_flight 0"));
+    assert!(metrics.contains("# TYPE tuono_ssr_render_failures_total counter"));
+}
+
+#[tokio::test]
+#[serial]
+async fn it_exports_the_request_traces() {
+    use crate::utils::collector::MockCollector;
+
+    let app = MockTuonoServer::spawn().await;
+
+    let client = reqwest::Client::new();
+
+    let server_url = format!("http://{}:{}", &app.address, &app.port);
+
+    let trace_id = "4bf92f3577b34da6a3ce929d0e0e4736";
+    let response = client
+        .get(format!("{server_url}/"))
+        .header("traceparent", format!("00-{trace_id}-00f067aa0ba902b7-01"))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(response.status(), 200);
+
+    let spans = MockCollector::get().spans(trace_id, 3).await;
+    let span = |name: &str| {
+        spans
+            .iter()
+            .find(|span| span["name"] == name)
+            .unwrap_or_else(|| panic!("The {name} span was not exported"))
+    };
+
+    let request = span("GET /");
+    let handler = span("handler route");
+    let render = span("ssr render");
+
+    assert_eq!(request["kind"], 2);
+    assert_eq!(request["parentSpanId"], "00f067aa0ba902b7");
+    assert!(
+        request["attributes"]
+            .as_array()
+            .unwrap()
+            .contains(&serde_json::json!({
+                "key": "http.response.status_code",
+                "value": { "intValue": "200" }
+            }))
+    );
+    assert_eq!(handler["parentSpanId"], request["spanId"]);
+    assert_eq!(render["parentSpanId"], request["spanId"]);
+}
+
+#[tokio::test]
+#[serial]
+async fn it_upgrades_the_websocket_route() {
+    use futures_util::{SinkExt, StreamExt};
+    use tokio_tungstenite::tungstenite::Message;
+
+    let app = MockTuonoServer::spawn().await;
+
+    let (mut socket, _) =
+        tokio_tungstenite::connect_async(format!("ws://{}:{}/ws/lobby", &app.address, &app.port))
+            .await
+            .expect("Failed to connect to the websocket.");
+
+    socket.send(Message::text("hello")).await.unwrap();
+
+    let reply = socket.next().await.unwrap().unwrap();
+    assert_eq!(reply.into_text().unwrap().as_str(), "lobby: hello");
+}
+
+#[tokio::test]
+#[serial]
+async fn it_rejects_the_websocket_upgrades_from_other_origins() {
+    use tokio_tungstenite::tungstenite::Error;
+    use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+
+    let app = MockTuonoServer::spawn().await;
+
+    let mut request = format!("ws://{}:{}/ws/lobby", &app.address, &app.port)
+        .into_client_request()
+        .unwrap();
+    request
+        .headers_mut()
+        .insert("origin", "https://evil.example".parse().unwrap());
+
+    match tokio_tungstenite::connect_async(request).await {
+        Err(Error::Http(response)) => assert_eq!(response.status().as_u16(), 403),
+        _ => panic!("The upgrade from another origin must be rejected"),
+    }
+}
+
+#[tokio::test]
+#[serial]
+async fn it_streams_the_server_sent_events() {
+    let app = MockTuonoServer::spawn().await;
+
+    let client = reqwest::Client::new();
+
+    let server_url = format!("http://{}:{}", &app.address, &app.port);
+
+    let response = client
+        .get(format!("{server_url}/sse"))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(response.headers()["content-type"], "text/event-stream");
+    assert_eq!(
+        response.text().await.unwrap(),
+        "data: 2\nid: 2\n\ndata: 1\nid: 1\n\n"
+    );
+
+    let response = client
+        .get(format!("{server_url}/sse"))
+        .header("last-event-id", "2")
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(response.text().await.unwrap(), "data: 1\nid: 1\n\n");
+}
+
+#[tokio::test]
+#[serial]
+async fn it_keeps_the_session_between_requests() {
+    let app = MockTuonoServer::spawn().await;
+
+    let client = reqwest::Client::new();
+
+    let server_url = format!("http://{}:{}", &app.address, &app.port);
+
+    let response = client
+        .get(format!("{server_url}/session"))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    let cookie = response.headers()["set-cookie"]
+        .to_str()
+        .unwrap()
+        .split(';')
+        .next()
+        .unwrap()
+        .to_string();
+    assert!(cookie.starts_with("tuono_session="));
+    assert_eq!(response.text().await.unwrap(), "1");
+
+    let response = client
+        .get(format!("{server_url}/session"))
+        .header("cookie", &cookie)
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(response.text().await.unwrap(), "2");
+}
+
+#[tokio::test]
+#[serial]
+async fn it_requires_the_csrf_token_on_unsafe_requests() {
+    let app = MockTuonoServer::spawn().await;
+
+    let client = reqwest::Client::new();
+
+    let server_url = format!("http://{}:{}", &app.address, &app.port);
+
+    let response = client
+        .post(format!("{server_url}/csrf"))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(response.status(), reqwest::StatusCode::FORBIDDEN);
+
+    let response = client
+        .get(format!("{server_url}/csrf"))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    let cookie = response.headers()["set-cookie"]
+        .to_str()
+        .unwrap()
+        .split(';')
+        .next()
+        .unwrap()
+        .to_string();
+    let token = response.text().await.unwrap();
+
+    let response = client
+        .post(format!("{server_url}/csrf"))
+        .header("cookie", &cookie)
+        .header("x-csrf-token", &token)
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert!(response.status().is_success());
+    assert_eq!(response.text().await.unwrap(), "ok");
+}
+
+#[tokio::test]
+#[serial]
+async fn it_posts_a_form_from_a_page_without_handler() {
+    let app = MockTuonoServer::spawn().await;
+
+    let client = reqwest::Client::new();
+
+    let server_url = format!("http://{}:{}", &app.address, &app.port);
+
+    let response = client
+        .get(format!("{server_url}/not-found"))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    let cookie = response.headers()["set-cookie"]
+        .to_str()
+        .unwrap()
+        .split(';')
+        .next()
+        .unwrap()
+        .to_string();
+
+    assert!(cookie.starts_with("tuono_csrf="));
+
+    let response = client
+        .post(format!("{server_url}/not-found"))
+        .header("cookie", &cookie)
+        .form(&[("name", "tuono")])
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(response.status(), reqwest::StatusCode::FORBIDDEN);
+
+    let token = client
+        .get(format!("{server_url}/csrf"))
+        .header("cookie", &cookie)
+        .send()
+        .await
+        .expect("Failed to execute request.")
+        .text()
+        .await
+        .unwrap();
+
+    let response = client
+        .post(format!("{server_url}/csrf"))
+        .header("cookie", &cookie)
+        .form(&[("name", "tuono"), ("_csrf", token.as_str())])
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert!(response.status().is_success());
+    assert_eq!(response.text().await.unwrap(), "ok");
+}
+
+#[tokio::test]
+#[serial]
+async fn it_answers_the_cors_and_options_requests() {
+    let app = MockTuonoServer::spawn().await;
+
+    let client = reqwest::Client::new();
+
+    let server_url = format!("http://{}:{}", &app.address, &app.port);
+
+    let response = client
+        .request(reqwest::Method::OPTIONS, format!("{server_url}/api/post"))
+        .header("origin", "https://m.tuono.dev")
+        .header("access-control-request-method", "POST")
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(response.status(), reqwest::StatusCode::NO_CONTENT);
+    assert_eq!(
+        response.headers()["access-control-allow-origin"],
+        "https://m.tuono.dev"
+    );
+
+    let response = client
+        .request(reqwest::Method::OPTIONS, format!("{server_url}/api/post"))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(response.status(), reqwest::StatusCode::NO_CONTENT);
+    assert_eq!(response.headers()["allow"], "POST");
+
+    // CORS is enabled only on the api routes
+    let response = client
+        .get(format!("{server_url}/env"))
+        .header("origin", "https://m.tuono.dev")
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert!(
+        !response
+            .headers()
+            .contains_key("access-control-allow-origin")
+    );
+}
+
 #[tokio::test]
 #[serial]
 async fn it_reads_an_env_var() {
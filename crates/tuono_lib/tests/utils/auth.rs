@@ -0,0 +1,19 @@
+use tuono_lib::Request;
+
+#[derive(Clone)]
+pub struct User {
+    name: String,
+}
+
+pub async fn authenticate(req: Request) -> Option<User> {
+    let token = req.headers.get("authorization")?.to_str().ok()?;
+
+    token.strip_prefix("Bearer ").map(|name| User {
+        name: name.to_string(),
+    })
+}
+
+#[tuono_lib::api(GET, auth = "required")]
+async fn me(req: Request) -> String {
+    req.user::<User>().map(|user| user.name).unwrap_or_default()
+}
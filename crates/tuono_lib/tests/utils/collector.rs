@@ -0,0 +1,81 @@
+use serde_json::Value;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use tuono_lib::axum::{Json, Router, routing::post};
+
+/// A local OTLP/HTTP collector receiving the traces of the mock servers
+#[derive(Debug)]
+pub struct MockCollector {
+    pub endpoint: String,
+    spans: Arc<Mutex<Vec<Value>>>,
+}
+
+static COLLECTOR: OnceLock<MockCollector> = OnceLock::new();
+
+impl MockCollector {
+    /// The collector is shared by the tests since the exporter
+    /// is started once per process.
+    pub fn get() -> &'static MockCollector {
+        COLLECTOR.get_or_init(|| {
+            let spans = Arc::new(Mutex::new(Vec::new()));
+            let received = Arc::clone(&spans);
+
+            let listener =
+                std::net::TcpListener::bind("127.0.0.1:0").expect("Failed to bind the collector");
+            listener.set_nonblocking(true).unwrap();
+            let endpoint = format!("http://{}", listener.local_addr().unwrap());
+
+            // The collector outlives the runtime of the test starting it
+            std::thread::spawn(move || {
+                let runtime = tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .unwrap();
+
+                runtime.block_on(async move {
+                    let router = Router::new().route(
+                        "/v1/traces",
+                        post(move |Json(body): Json<Value>| async move {
+                            let mut spans = received.lock().unwrap();
+                            for resource in body["resourceSpans"].as_array().into_iter().flatten() {
+                                for scope in resource["scopeSpans"].as_array().into_iter().flatten()
+                                {
+                                    spans.extend(
+                                        scope["spans"].as_array().into_iter().flatten().cloned(),
+                                    );
+                                }
+                            }
+                        }),
+                    );
+
+                    let listener = tokio::net::TcpListener::from_std(listener).unwrap();
+                    tuono_lib::axum::serve(listener, router).await.unwrap();
+                });
+            });
+
+            MockCollector { endpoint, spans }
+        })
+    }
+
+    /// Wait for `count` spans of the `trace_id` trace to be exported
+    pub async fn spans(&self, trace_id: &str, count: usize) -> Vec<Value> {
+        for _ in 0..100 {
+            let spans: Vec<Value> = self
+                .spans
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|span| span["traceId"] == trace_id)
+                .cloned()
+                .collect();
+
+            if spans.len() >= count {
+                return spans;
+            }
+
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+
+        panic!("The collector did not receive {count} spans of the trace {trace_id}");
+    }
+}
@@ -0,0 +1,11 @@
+use tuono_lib::Request;
+
+#[tuono_lib::api(GET)]
+async fn csrf_token(req: Request) -> String {
+    req.csrf_token().unwrap_or_default()
+}
+
+#[tuono_lib::api(POST)]
+async fn protected(_req: Request) -> &'static str {
+    "ok"
+}
@@ -0,0 +1,6 @@
+use tuono_lib::{FileResponse, Request, Response};
+
+#[tuono_lib::handler]
+async fn route(_: Request) -> Response {
+    Response::File(FileResponse::new("report.csv", "id,name"))
+}
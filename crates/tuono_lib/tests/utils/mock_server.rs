@@ -5,16 +5,27 @@ use std::path::PathBuf;
 use std::{env, fs};
 use tempfile::{TempDir, tempdir};
 use tuono_lib::axum::routing::{get, post};
-use tuono_lib::{Mode, Server, axum::Router, tuono_internal_init_v8_platform};
+use tuono_lib::{HealthChecks, Mode, Server, axum::Router, tuono_internal_init_v8_platform};
 
+use crate::utils::auth::{authenticate, get_tuono_internal_api as auth_me};
 use crate::utils::catch_all::get_tuono_internal_api as catch_all;
+use crate::utils::collector::MockCollector;
+use crate::utils::csrf::{
+    get_tuono_internal_api as csrf_token, post_tuono_internal_api as csrf_protected,
+};
 use crate::utils::dynamic_parameter::get_tuono_internal_api as dynamic_parameter;
 use crate::utils::env::get_tuono_internal_api as test_env;
+use crate::utils::file_download as file_download_route;
 use crate::utils::form_data::post_tuono_internal_api as form_data_api;
 use crate::utils::health_check::get_tuono_internal_api as health_check;
 use crate::utils::post_api::post_tuono_internal_api as post_api;
+use crate::utils::rate_limited;
 use crate::utils::route as html_route;
 use crate::utils::route::tuono_internal_api as route_api;
+use crate::utils::scoped::get_tuono_internal_api as scoped_status;
+use crate::utils::session::get_tuono_internal_api as session_visits;
+use crate::utils::sse::get_tuono_internal_api as sse_countdown;
+use crate::utils::ws_echo::tuono_internal_ws as ws_echo;
 
 use std::sync::Once;
 
@@ -65,10 +76,18 @@ impl MockTuonoServer {
 
         env::set_current_dir(temp_dir.path()).expect("Failed to change current dir into temp_dir");
 
-        add_file_with_content(
-            "./.tuono/config/config.json",
-            r#"{"server": {"host": "127.0.0.1", "port": 0}}"#,
-        );
+        let config = r#"{
+                "server": {"host": "127.0.0.1", "port": 0},
+                "redirects": [{"source": "/old/{slug}", "destination": "/dynamic/{slug}"}],
+                "rewrites": [{"source": "/rewritten", "destination": "/env"}],
+                "csrf": {"exempt": ["/api/post", "/api/form_data"]},
+                "cors": {"routes": [{"path": "/api/{*path}", "origins": ["https://m.tuono.dev"]}]},
+                "metrics": {"enabled": true},
+                "telemetry": {"enabled": true, "endpoint": "[COLLECTOR_ENDPOINT]"}
+            }"#
+        .replace("[COLLECTOR_ENDPOINT]", &MockCollector::get().endpoint);
+
+        add_file_with_content("./.tuono/config/config.json", &config);
 
         add_file_with_content("./out/server/prod-server.js", react_prod_build.as_str());
 
@@ -88,9 +107,30 @@ impl MockTuonoServer {
             .route("/dynamic/{parameter}", get(dynamic_parameter))
             .route("/api/post", post(post_api))
             .route("/api/form_data", post(form_data_api))
-            .route("/env", get(test_env));
-
-        let server = Server::init(router, Mode::Prod).await;
+            .route("/env", get(test_env))
+            .route("/session", get(session_visits))
+            .route("/me", get(auth_me))
+            .route("/ws/{room}", get(ws_echo))
+            .route("/sse", get(sse_countdown))
+            .route("/scoped/{status}", get(scoped_status))
+            .route("/csrf", get(csrf_token).post(csrf_protected))
+            .route(
+                "/rate_limited",
+                get(rate_limited::get_tuono_internal_api)
+                    .layer(rate_limited::get_tuono_internal_limits()),
+            )
+            .route("/download", get(file_download_route::tuono_internal_route))
+            .route(
+                "/tuono/data/download",
+                get(file_download_route::tuono_internal_api),
+            );
+
+        let server = Server::init(router, Mode::Prod)
+            .await
+            .with_authenticator(authenticate)
+            .with_health_checks(
+                HealthChecks::new().check("database", || async { Ok::<(), String>(()) }),
+            );
 
         let socket = server
             .listener
@@ -1,8 +1,17 @@
+pub mod auth;
 pub mod catch_all;
+pub mod collector;
+pub mod csrf;
 pub mod dynamic_parameter;
 pub mod env;
+pub mod file_download;
 pub mod form_data;
 pub mod health_check;
 pub mod mock_server;
 pub mod post_api;
+pub mod rate_limited;
 pub mod route;
+pub mod scoped;
+pub mod session;
+pub mod sse;
+pub mod ws_echo;
@@ -0,0 +1,4 @@
+#[tuono_lib::api(GET, rate_limit = "1/1m")]
+async fn rate_limited(_req: tuono_lib::Request) -> &'static str {
+    "ok"
+}
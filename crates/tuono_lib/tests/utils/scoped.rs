@@ -0,0 +1,44 @@
+use std::sync::Mutex;
+use tuono_lib::axum::http::StatusCode;
+use tuono_lib::{Outcome, Request, RequestScoped, Scoped};
+
+/// The journals finalized by the server with the success of the request
+pub static FINALIZED: Mutex<Vec<(String, bool)>> = Mutex::new(Vec::new());
+
+pub struct Journal {
+    status: String,
+}
+
+impl RequestScoped for Journal {
+    type Error = StatusCode;
+
+    async fn create(req: &Request) -> Result<Self, Self::Error> {
+        req.params
+            .get("status")
+            .cloned()
+            .map(|status| Journal { status })
+            .ok_or(StatusCode::BAD_REQUEST)
+    }
+
+    async fn finalize(self, outcome: &Outcome) {
+        FINALIZED
+            .lock()
+            .unwrap()
+            .push((self.status, outcome.is_success()));
+    }
+}
+
+#[tuono_lib::api(GET)]
+async fn respond_with_status(
+    _req: Request,
+    journal: Scoped<Journal>,
+) -> Result<StatusCode, StatusCode> {
+    let journal = journal.get().await?;
+
+    journal
+        .status
+        .parse::<u16>()
+        .ok()
+        .and_then(|status| StatusCode::from_u16(status).ok())
+        .ok_or(StatusCode::BAD_REQUEST)
+}
@@ -0,0 +1,10 @@
+use tuono_lib::Request;
+
+#[tuono_lib::api(GET)]
+async fn visits(req: Request) -> String {
+    let session = req.session();
+    let visits = session.get::<u32>("visits").unwrap_or_default() + 1;
+    session.insert("visits", visits).unwrap();
+
+    visits.to_string()
+}
@@ -0,0 +1,15 @@
+use tuono_lib::{Request, SseEvent, SseResponse};
+
+#[tuono_lib::api(GET)]
+async fn countdown(req: Request) -> SseResponse<u32> {
+    let from = req
+        .last_event_id()
+        .and_then(|id| id.parse::<u32>().ok())
+        .unwrap_or(3);
+
+    SseResponse::new(futures_util::stream::iter(
+        (1..from)
+            .rev()
+            .map(|count| SseEvent::new(count).id(count.to_string())),
+    ))
+}
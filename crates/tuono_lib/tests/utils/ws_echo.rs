@@ -0,0 +1,14 @@
+use tuono_lib::Request;
+use tuono_lib::axum::extract::ws::{Message, WebSocket};
+
+#[tuono_lib::ws]
+async fn echo(req: Request, mut socket: WebSocket) {
+    let room = req.params.get("room").cloned().unwrap_or_default();
+
+    while let Some(Ok(Message::Text(text))) = socket.recv().await {
+        let reply = format!("{room}: {}", text.as_str());
+        if socket.send(Message::Text(reply.into())).await.is_err() {
+            break;
+        }
+    }
+}
@@ -1,52 +1,95 @@
+use crate::limits::LimitsArgs;
 use crate::utils::{
-    crate_application_state_extractor, create_struct_fn_arg, import_main_application_state,
-    params_argument, request_argument,
+    HandlerArguments, crate_application_state_extractor, create_struct_fn_arg,
+    import_main_application_state, params_argument, parse_auth_arg, request_argument,
 };
 use proc_macro::{Span, TokenStream};
 use quote::quote;
+use syn::parse::{Parse, ParseStream};
 use syn::punctuated::Punctuated;
 use syn::token::Comma;
-use syn::{FnArg, Ident, ItemFn, Pat, parse_macro_input};
+use syn::{FnArg, Ident, ItemFn, Token, parse_macro_input};
+
+/// The arguments accepted by the `#[tuono_lib::api(METHOD, ...)]` attribute
+struct ApiArgs {
+    http_method: Ident,
+    /// Reject the unauthenticated requests with `401`
+    auth_required: bool,
+    limits: LimitsArgs,
+}
+
+impl Parse for ApiArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let http_method: Ident = input.parse()?;
+        let mut auth_required = false;
+        let mut limits = LimitsArgs::default();
+
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+
+            let name: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+
+            if name == "auth" {
+                auth_required = parse_auth_arg(input)?;
+            } else if !limits.parse_arg(&name, input)? {
+                return Err(syn::Error::new_spanned(
+                    &name,
+                    format!(
+                        "Unknown api argument. Expected `auth = \"required\"`, {}",
+                        LimitsArgs::NAMES
+                    ),
+                ));
+            }
+        }
+
+        Ok(ApiArgs {
+            http_method,
+            auth_required,
+            limits,
+        })
+    }
+}
 
 pub fn api_core(attrs: TokenStream, item: TokenStream) -> TokenStream {
     let item = parse_macro_input!(item as ItemFn);
-    let http_method = parse_macro_input!(attrs as Ident)
-        .to_string()
-        .to_lowercase();
+    let args = parse_macro_input!(attrs as ApiArgs);
+    let http_method = args.http_method.to_string().to_lowercase();
 
     let api_fn_name = Ident::new(
         &format!("{http_method}_tuono_internal_api"),
         Span::call_site().into(),
     );
 
+    let limits_fn = args.limits.layer_fn(Ident::new(
+        &format!("{http_method}_tuono_internal_limits"),
+        Span::call_site().into(),
+    ));
+
     let fn_name = &item.sig.ident;
     let return_type = &item.sig.output;
 
-    let mut argument_names: Punctuated<Pat, Comma> = Punctuated::new();
-    let mut axum_arguments: Punctuated<FnArg, Comma> = Punctuated::new();
-
     // Fn Arguments minus the first which always is the request
-    for (i, arg) in item.sig.inputs.iter().enumerate() {
-        if i == 0 {
-            axum_arguments.insert(i, params_argument());
-            continue;
-        }
-
-        if i == 1 {
-            axum_arguments.insert(1, create_struct_fn_arg())
-        }
+    let arguments = match HandlerArguments::parse(item.sig.inputs.iter().skip(1)) {
+        Ok(arguments) => arguments,
+        Err(err) => return err.to_compile_error().into(),
+    };
 
-        if let FnArg::Typed(pat_type) = arg {
-            let index = i - 1;
-            let argument_name = *pat_type.pat.clone();
-            argument_names.insert(index, argument_name.clone());
-        }
+    let mut axum_arguments: Punctuated<FnArg, Comma> = Punctuated::new();
+    axum_arguments.push(params_argument());
+    if !arguments.state_names.is_empty() {
+        axum_arguments.push(create_struct_fn_arg());
     }
+    axum_arguments.push(request_argument());
 
-    axum_arguments.insert(axum_arguments.len(), request_argument());
-
-    let application_state_extractor = crate_application_state_extractor(argument_names.clone());
-    let application_state_import = import_main_application_state(argument_names.clone());
+    let application_state_extractor =
+        crate_application_state_extractor(arguments.state_names.clone());
+    let application_state_import = import_main_application_state(arguments.state_names.clone());
+    let scoped_constructors = arguments.scoped_constructors();
+    let handler_call = arguments.handler_call(fn_name);
 
     let modified_request = if http_method == "post"
         || http_method == "put"
@@ -59,30 +102,71 @@ pub fn api_core(attrs: TokenStream, item: TokenStream) -> TokenStream {
 
             let body = tuono_lib::axum::body::to_bytes(body, usize::MAX).await.unwrap_or(Vec::new().into()).to_vec();
 
-            let req = tuono_lib::Request::new(path, headers, params, Some(body));
+            let req = tuono_lib::Request::new(path, headers, params, Some(body))
+                .with_extensions(parts.extensions);
         }
     } else {
         quote! {
            let pathname = request.uri();
            let headers = request.headers();
 
-           let req = tuono_lib::Request::new(request.uri().to_owned(), request.headers().to_owned(), params, None);
+           let req = tuono_lib::Request::new(request.uri().to_owned(), request.headers().to_owned(), params, None)
+               .with_extensions(request.extensions().to_owned());
         }
     };
 
-    quote! {
-        #application_state_import
+    let guard = args.auth_required.then(|| {
+        quote! {
+            if let Some(response) = tuono_lib::tuono_internal_guard(&req, tuono_lib::AuthGuard::Api) {
+                return response;
+            }
+        }
+    });
 
-        #item
+    // The guarded routes can return either the 401 or the handler response
+    // while the scoped resources are finalized with the converted response
+    let api_fn = if args.auth_required || !arguments.scoped.is_empty() {
+        let response = arguments.finalize_response(quote! {
+            tuono_lib::axum::response::IntoResponse::into_response(
+                #handler_call
+            )
+        });
+
+        quote! {
+            pub async fn #api_fn_name(#axum_arguments) -> tuono_lib::axum::response::Response {
+
+               #application_state_extractor
 
-        pub async fn #api_fn_name(#axum_arguments)#return_type {
+               #modified_request
 
-           #application_state_extractor
+               #guard
 
-           #modified_request
+               #scoped_constructors
 
-           #fn_name(req.clone(), #argument_names).await
+               #response
+            }
         }
+    } else {
+        quote! {
+            pub async fn #api_fn_name(#axum_arguments)#return_type {
+
+               #application_state_extractor
+
+               #modified_request
+
+               #handler_call
+            }
+        }
+    };
+
+    quote! {
+        #application_state_import
+
+        #limits_fn
+
+        #item
+
+        #api_fn
     }
     .into()
 }
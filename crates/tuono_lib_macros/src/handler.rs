@@ -1,48 +1,137 @@
+use crate::limits::LimitsArgs;
 use crate::utils::{
-    crate_application_state_extractor, create_struct_fn_arg, import_main_application_state,
-    params_argument, request_argument,
+    HandlerArguments, crate_application_state_extractor, create_struct_fn_arg,
+    import_main_application_state, params_argument, parse_auth_arg, request_argument,
 };
 
 use proc_macro::TokenStream;
 use quote::quote;
+use syn::parse::{Parse, ParseStream};
 use syn::punctuated::Punctuated;
 use syn::token::Comma;
-use syn::{FnArg, ItemFn, Pat, parse_macro_input};
+use syn::{FnArg, Ident, ItemFn, Token, Type, parse_macro_input};
+
+/// The arguments accepted by the `#[tuono_lib::handler(...)]` attribute
+struct HandlerArgs {
+    /// The type of the data passed to `Props::new`.
+    /// The CLI reads it to generate the route typescript props.
+    props: Option<Type>,
+    /// Redirect the unauthenticated visitors to the login page
+    auth_required: bool,
+    limits: LimitsArgs,
+}
+
+impl Parse for HandlerArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut props = None;
+        let mut auth_required = false;
+        let mut limits = LimitsArgs::default();
+
+        while !input.is_empty() {
+            let name: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+
+            if name == "props" {
+                props = Some(input.parse::<Type>()?);
+            } else if name == "auth" {
+                auth_required = parse_auth_arg(input)?;
+            } else if !limits.parse_arg(&name, input)? {
+                return Err(syn::Error::new_spanned(
+                    &name,
+                    format!(
+                        "Unknown handler argument. Expected `props = Type`, `auth = \"required\"`, {}",
+                        LimitsArgs::NAMES
+                    ),
+                ));
+            }
+
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+
+        Ok(HandlerArgs {
+            props,
+            auth_required,
+            limits,
+        })
+    }
+}
 
-pub fn handler_core(_args: TokenStream, item: TokenStream) -> TokenStream {
+pub fn handler_core(args: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(args as HandlerArgs);
     let item = parse_macro_input!(item as ItemFn);
 
     let fn_name = &item.sig.ident;
 
-    let mut argument_names: Punctuated<Pat, Comma> = Punctuated::new();
-    let mut axum_arguments: Punctuated<FnArg, Comma> = Punctuated::new();
-
     // Fn Arguments minus the first which always is the request
-    for (i, arg) in item.sig.inputs.iter().enumerate() {
-        if i == 0 {
-            axum_arguments.insert(i, params_argument());
-            continue;
-        }
-
-        if i == 1 {
-            axum_arguments.insert(1, create_struct_fn_arg())
-        }
+    let arguments = match HandlerArguments::parse(item.sig.inputs.iter().skip(1)) {
+        Ok(arguments) => arguments,
+        Err(err) => return err.to_compile_error().into(),
+    };
 
-        if let FnArg::Typed(pat_type) = arg {
-            let index = i - 1;
-            let argument_name = *pat_type.pat.clone();
-            argument_names.insert(index, argument_name.clone());
-        }
+    let mut axum_arguments: Punctuated<FnArg, Comma> = Punctuated::new();
+    axum_arguments.push(params_argument());
+    if !arguments.state_names.is_empty() {
+        axum_arguments.push(create_struct_fn_arg());
     }
-
-    axum_arguments.insert(axum_arguments.len(), request_argument());
-
-    let application_state_extractor = crate_application_state_extractor(argument_names.clone());
-    let application_state_import = import_main_application_state(argument_names.clone());
+    axum_arguments.push(request_argument());
+
+    let application_state_extractor =
+        crate_application_state_extractor(arguments.state_names.clone());
+    let application_state_import = import_main_application_state(arguments.state_names.clone());
+    let scoped_constructors = arguments.scoped_constructors();
+    let handler_call = arguments.handler_call(fn_name);
+
+    let limits_fn = args.limits.layer_fn(Ident::new(
+        "tuono_internal_limits",
+        proc_macro2::Span::call_site(),
+    ));
+
+    // Fail at compile time if the declared props type does not exist
+    let props_type_check = args.props.map(|props| {
+        quote! {
+            const _: fn() = || {
+                let _: Option<#props> = None;
+            };
+        }
+    });
+
+    let (route_guard, data_guard) = if args.auth_required {
+        (
+            Some(quote! {
+                if let Some(response) = tuono_lib::tuono_internal_guard(&req, tuono_lib::AuthGuard::Page) {
+                    return response;
+                }
+            }),
+            Some(quote! {
+                if let Some(response) = tuono_lib::tuono_internal_guard(&req, tuono_lib::AuthGuard::Data) {
+                    return response;
+                }
+            }),
+        )
+    } else {
+        (None, None)
+    };
+
+    let route_response = arguments.finalize_response(quote! {
+        tuono_lib::axum::response::IntoResponse::into_response(
+            #handler_call.render_to_string(req)
+        )
+    });
+    let data_response = arguments.finalize_response(quote! {
+        tuono_lib::axum::response::IntoResponse::into_response(
+            #handler_call.json()
+        )
+    });
 
     quote! {
         #application_state_import
 
+        #props_type_check
+
+        #limits_fn
+
         #item
 
         pub async fn tuono_internal_route(
@@ -54,9 +143,14 @@ pub fn handler_core(_args: TokenStream, item: TokenStream) -> TokenStream {
            let pathname = request.uri();
            let headers = request.headers();
 
-           let req = tuono_lib::Request::new(pathname.to_owned(), headers.to_owned(), params, None);
+           let req = tuono_lib::Request::new(pathname.to_owned(), headers.to_owned(), params, None)
+               .with_extensions(request.extensions().to_owned());
+
+           #route_guard
+
+           #scoped_constructors
 
-           #fn_name(req.clone(), #argument_names).await.render_to_string(req)
+           #route_response
         }
 
         pub async fn tuono_internal_api(
@@ -68,9 +162,14 @@ pub fn handler_core(_args: TokenStream, item: TokenStream) -> TokenStream {
            let pathname = request.uri();
            let headers = request.headers();
 
-           let req = tuono_lib::Request::new(pathname.to_owned(), headers.to_owned(), params, None);
+           let req = tuono_lib::Request::new(pathname.to_owned(), headers.to_owned(), params, None)
+               .with_extensions(request.extensions().to_owned());
+
+           #data_guard
+
+           #scoped_constructors
 
-           #fn_name(req.clone(), #argument_names).await.json()
+           #data_response
         }
     }
     .into()
@@ -8,18 +8,98 @@ use proc_macro::TokenStream;
 
 mod api;
 mod handler;
+mod limits;
 mod utils;
+mod ws;
 
+/// Define the server side handler of a page route.
+///
+/// The type of the data passed to `Props::new` is detected by the CLI
+/// and exported as `RouteProps<'/route'>` in the `"tuono/types"` module.
+/// When it can't be inferred it can be declared explicitly:
+///
+/// ```ignore
+/// #[tuono_lib::handler(props = Post)]
+/// async fn get_post(req: Request) -> Response { ... }
+/// ```
+///
+/// Traffic controls can be declared on the route. Rejected requests get
+/// a `429` (rate limit), `503` (concurrency) or `504` (timeout) response:
+///
+/// ```ignore
+/// #[tuono_lib::handler(rate_limit = "10/1m", concurrency = 8, timeout = "10s")]
+/// async fn search(req: Request) -> Response { ... }
+/// ```
+///
+/// Visitors that are not authenticated by the `authenticate` hook of
+/// `src/app.rs` can be redirected to the login page (`auth.loginUrl`):
+///
+/// ```ignore
+/// #[tuono_lib::handler(auth = "required")]
+/// async fn dashboard(req: Request) -> Response { ... }
+/// ```
+///
+/// The arguments following the request are the `ApplicationState` fields
+/// or the `Scoped<T>` request-scoped resources. A resource is created on
+/// its first `get` call and finalized with the response outcome once the
+/// handler returned:
+///
+/// ```ignore
+/// #[tuono_lib::handler]
+/// async fn checkout(req: Request, tx: Scoped<Transaction>, mailer: Mailer) -> Response {
+///     let Ok(mut tx) = tx.get().await else {
+///         return Response::Custom(StatusCode::SERVICE_UNAVAILABLE.into_response());
+///     };
+///     ...
+/// }
+/// ```
 #[proc_macro_attribute]
 pub fn handler(args: TokenStream, item: TokenStream) -> TokenStream {
     handler::handler_core(args, item)
 }
 
+/// Define an api route handling the given HTTP method.
+///
+/// It accepts the same traffic controls of the `handler` macro.
+/// `rate_limit_key` is either `ip` (default) or `header:<name>`:
+///
+/// ```ignore
+/// #[tuono_lib::api(POST, rate_limit = "5/1m", rate_limit_key = "header:x-api-key")]
+/// async fn login(req: Request) -> StatusCode { ... }
+/// ```
+///
+/// With `auth = "required"` the unauthenticated requests get a `401` response.
+///
+/// Like in the `handler` macro, `Scoped<T>` arguments are finalized
+/// with the status of the returned response.
 #[proc_macro_attribute]
 pub fn api(args: TokenStream, item: TokenStream) -> TokenStream {
     api::api_core(args, item)
 }
 
+/// Define a WebSocket endpoint on the route path.
+///
+/// The handler receives the upgrade request and the socket, followed
+/// by the `ApplicationState` fields it needs. It accepts the `auth`
+/// and traffic control arguments of the `api` macro, checked on the
+/// upgrade request:
+///
+/// ```ignore
+/// use tuono_lib::axum::extract::ws::{Message, WebSocket};
+///
+/// #[tuono_lib::ws(auth = "required")]
+/// async fn chat(req: Request, mut socket: WebSocket, rooms: Rooms) {
+///     while let Some(Ok(Message::Text(text))) = socket.recv().await { ... }
+/// }
+/// ```
+///
+/// The upgrades sent by the pages of other origins are rejected with `403`:
+/// besides the server origin, only the `cors.origins` are allowed.
+#[proc_macro_attribute]
+pub fn ws(args: TokenStream, item: TokenStream) -> TokenStream {
+    ws::ws_core(args, item)
+}
+
 /// Automatically generate typescript's types
 /// from Rust's structs, types and enums.
 ///
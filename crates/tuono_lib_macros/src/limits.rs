@@ -0,0 +1,122 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse::ParseStream;
+use syn::{Ident, LitInt, LitStr};
+
+/// The traffic control arguments shared by the `handler` and `api` macros
+#[derive(Default)]
+pub struct LimitsArgs {
+    rate_limit: Option<LitStr>,
+    rate_limit_key: Option<LitStr>,
+    concurrency: Option<LitInt>,
+    timeout: Option<LitStr>,
+}
+
+impl LimitsArgs {
+    pub const NAMES: &'static str = "`rate_limit = \"10/1m\"`, `rate_limit_key = \"ip\"`, `concurrency = 8`, `timeout = \"30s\"`";
+
+    /// Parse the value of the `name = value` argument.
+    /// Returns `false` when `name` is not a limits argument.
+    pub fn parse_arg(&mut self, name: &Ident, input: ParseStream) -> syn::Result<bool> {
+        if name == "rate_limit" {
+            self.rate_limit = Some(parse_lit(input, validate_rate_limit)?);
+        } else if name == "rate_limit_key" {
+            self.rate_limit_key = Some(parse_lit(input, validate_rate_limit_key)?);
+        } else if name == "concurrency" {
+            let concurrency: LitInt = input.parse()?;
+            concurrency.base10_parse::<usize>()?;
+            self.concurrency = Some(concurrency);
+        } else if name == "timeout" {
+            self.timeout = Some(parse_lit(input, |value| {
+                validate_duration(value).map(drop)
+            })?);
+        } else {
+            return Ok(false);
+        }
+
+        Ok(true)
+    }
+
+    /// Create the function returning the layer applied by the generated router.
+    /// The layer is created once so that all the requests share the same limits.
+    pub fn layer_fn(&self, fn_name: Ident) -> TokenStream {
+        let rate_limit = self.rate_limit.iter();
+        let rate_limit_key = self.rate_limit_key.iter();
+        let concurrency = self.concurrency.iter();
+        let timeout = self.timeout.iter();
+
+        quote! {
+            pub fn #fn_name() -> tuono_lib::LimitsLayer {
+                static LAYER: std::sync::OnceLock<tuono_lib::LimitsLayer> = std::sync::OnceLock::new();
+
+                LAYER
+                    .get_or_init(|| {
+                        tuono_lib::LimitsLayer::new(
+                            tuono_lib::RouteLimits::new()
+                                #(.rate_limit(#rate_limit))*
+                                #(.rate_limit_key(#rate_limit_key))*
+                                #(.concurrency(#concurrency))*
+                                #(.timeout(#timeout))*
+                        )
+                    })
+                    .clone()
+            }
+        }
+    }
+}
+
+/// Parse a string literal and validate it at compile time:
+/// an invalid value would otherwise panic when the route is first hit.
+fn parse_lit(input: ParseStream, validate: fn(&str) -> Result<(), String>) -> syn::Result<LitStr> {
+    let lit: LitStr = input.parse()?;
+    validate(&lit.value()).map_err(|err| syn::Error::new_spanned(&lit, err))?;
+    Ok(lit)
+}
+
+// Keep in sync with the parsers in `tuono_lib::services::limits`
+
+/// Returns the amount of the duration
+fn validate_duration(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let unit_start = value
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(|| format!("missing unit in `{value}` (ms, s, m or h)"))?;
+
+    let (amount, unit) = value.split_at(unit_start);
+    let amount: u64 = amount
+        .parse()
+        .map_err(|_| format!("invalid amount in `{value}`"))?;
+
+    match unit {
+        "ms" | "s" | "m" | "h" => Ok(amount),
+        _ => Err(format!("invalid unit `{unit}` (ms, s, m or h)")),
+    }
+}
+
+fn validate_rate_limit(value: &str) -> Result<(), String> {
+    let (requests, per) = value.split_once('/').ok_or_else(|| {
+        format!("expected `<requests>/<duration>` (e.g. `10/1m`), found `{value}`")
+    })?;
+
+    let requests: u32 = requests
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid number of requests in `{value}`"))?;
+    let per = validate_duration(per)?;
+
+    if requests == 0 || per == 0 {
+        return Err(format!("`{value}` never allows a request"));
+    }
+
+    Ok(())
+}
+
+fn validate_rate_limit_key(value: &str) -> Result<(), String> {
+    match value.split_once(':') {
+        None if value == "ip" => Ok(()),
+        Some(("header", name)) if !name.trim().is_empty() => Ok(()),
+        _ => Err(format!(
+            "expected `ip` or `header:<name>` as rate limit key, found `{value}`"
+        )),
+    }
+}
@@ -1,7 +1,9 @@
-use quote::quote;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::parse::ParseStream;
 use syn::punctuated::Punctuated;
 use syn::token::Comma;
-use syn::{FnArg, Pat, Stmt, parse_quote, parse2};
+use syn::{FnArg, Ident, LitStr, Pat, Stmt, Type, parse_quote, parse2};
 
 pub fn create_struct_fn_arg() -> FnArg {
     parse2(quote! {
@@ -45,3 +47,117 @@ pub fn request_argument() -> FnArg {
     })
     .unwrap()
 }
+
+/// Parse the `auth = "required" | "optional"` argument.
+/// Returns `true` when the route requires an authenticated user.
+pub fn parse_auth_arg(input: ParseStream) -> syn::Result<bool> {
+    let auth: LitStr = input.parse()?;
+    match auth.value().as_str() {
+        "required" => Ok(true),
+        "optional" => Ok(false),
+        _ => Err(syn::Error::new_spanned(
+            auth,
+            "Invalid auth value. Expected \"required\" or \"optional\"",
+        )),
+    }
+}
+
+/// The handler arguments following the request
+#[derive(Default)]
+pub struct HandlerArguments {
+    /// Passed to the handler in the declaration order
+    pub names: Punctuated<Pat, Comma>,
+    /// The fields extracted from the `ApplicationState`
+    pub state_names: Punctuated<Pat, Comma>,
+    /// The request-scoped resources declared as `Scoped<T>`
+    pub scoped: Vec<Ident>,
+}
+
+fn is_scoped_type(ty: &Type) -> bool {
+    match ty {
+        Type::Path(type_path) => type_path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Scoped"),
+        _ => false,
+    }
+}
+
+impl HandlerArguments {
+    pub fn parse<'a>(inputs: impl Iterator<Item = &'a FnArg>) -> syn::Result<Self> {
+        let mut arguments = HandlerArguments::default();
+
+        for arg in inputs {
+            let FnArg::Typed(pat_type) = arg else {
+                continue;
+            };
+
+            if !is_scoped_type(&pat_type.ty) {
+                arguments.names.push(*pat_type.pat.clone());
+                arguments.state_names.push(*pat_type.pat.clone());
+                continue;
+            }
+
+            let Pat::Ident(pat_ident) = &*pat_type.pat else {
+                return Err(syn::Error::new_spanned(
+                    &pat_type.pat,
+                    "The Scoped arguments must be named with an identifier",
+                ));
+            };
+
+            let name = pat_ident.ident.clone();
+            arguments.names.push(parse_quote!(#name));
+            arguments.scoped.push(name);
+        }
+
+        Ok(arguments)
+    }
+
+    /// Create the request-scoped resources.
+    /// They are built lazily on the first `Scoped::get` call.
+    pub fn scoped_constructors(&self) -> TokenStream {
+        let scoped = &self.scoped;
+        let finalizers = self.scoped.iter().map(finalizer_ident);
+
+        quote! {
+            #(
+                let #scoped = tuono_lib::Scoped::tuono_internal_new(req.clone());
+                let #finalizers = #scoped.clone();
+            )*
+        }
+    }
+
+    /// Call the user handler in its own trace span
+    pub fn handler_call(&self, fn_name: &Ident) -> TokenStream {
+        let names = &self.names;
+        let span_name = LitStr::new(&fn_name.to_string(), fn_name.span());
+
+        quote! {
+            tuono_lib::tuono_internal_trace_handler(#span_name, #fn_name(req.clone(), #names)).await
+        }
+    }
+
+    /// Finalize the request-scoped resources with the outcome of `response`
+    /// before sending it.
+    pub fn finalize_response(&self, response: TokenStream) -> TokenStream {
+        if self.scoped.is_empty() {
+            return response;
+        }
+
+        let finalizers = self.scoped.iter().map(finalizer_ident);
+
+        quote! {
+            {
+                let response = #response;
+                let outcome = tuono_lib::Outcome::new(response.status());
+                #( #finalizers.tuono_internal_finalize(&outcome).await; )*
+                response
+            }
+        }
+    }
+}
+
+fn finalizer_ident(name: &Ident) -> Ident {
+    format_ident!("tuono_internal_scoped_{}", name)
+}
@@ -0,0 +1,139 @@
+use crate::limits::LimitsArgs;
+use crate::utils::{
+    crate_application_state_extractor, create_struct_fn_arg, import_main_application_state,
+    params_argument, parse_auth_arg, request_argument,
+};
+
+use proc_macro::{Span, TokenStream};
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::token::Comma;
+use syn::{FnArg, Ident, ItemFn, Pat, Token, parse_macro_input, parse2};
+
+/// The arguments accepted by the `#[tuono_lib::ws(...)]` attribute
+struct WsArgs {
+    /// Reject the unauthenticated upgrade requests with `401`
+    auth_required: bool,
+    limits: LimitsArgs,
+}
+
+impl Parse for WsArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut auth_required = false;
+        let mut limits = LimitsArgs::default();
+
+        while !input.is_empty() {
+            let name: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+
+            if name == "auth" {
+                auth_required = parse_auth_arg(input)?;
+            } else if !limits.parse_arg(&name, input)? {
+                return Err(syn::Error::new_spanned(
+                    &name,
+                    format!(
+                        "Unknown ws argument. Expected `auth = \"required\"`, {}",
+                        LimitsArgs::NAMES
+                    ),
+                ));
+            }
+
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+
+        Ok(WsArgs {
+            auth_required,
+            limits,
+        })
+    }
+}
+
+fn upgrade_argument() -> FnArg {
+    parse2(quote! {
+        upgrade: tuono_lib::axum::extract::ws::WebSocketUpgrade
+    })
+    .unwrap()
+}
+
+pub fn ws_core(args: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(args as WsArgs);
+    let item = parse_macro_input!(item as ItemFn);
+
+    if item.sig.inputs.len() < 2 {
+        return syn::Error::new_spanned(
+            &item.sig,
+            "The WebSocket handler expects the request and the socket as first arguments",
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    let fn_name = &item.sig.ident;
+
+    let mut argument_names: Punctuated<Pat, Comma> = Punctuated::new();
+    let mut axum_arguments: Punctuated<FnArg, Comma> = Punctuated::new();
+
+    axum_arguments.push(params_argument());
+
+    // Fn Arguments minus the first two which always are the request and the socket
+    for arg in item.sig.inputs.iter().skip(2) {
+        if argument_names.is_empty() {
+            axum_arguments.push(create_struct_fn_arg());
+        }
+
+        if let FnArg::Typed(pat_type) = arg {
+            argument_names.push(*pat_type.pat.clone());
+        }
+    }
+
+    axum_arguments.push(upgrade_argument());
+    axum_arguments.push(request_argument());
+
+    let application_state_extractor = crate_application_state_extractor(argument_names.clone());
+    let application_state_import = import_main_application_state(argument_names.clone());
+
+    let limits_fn = args.limits.layer_fn(Ident::new(
+        "tuono_internal_limits",
+        Span::call_site().into(),
+    ));
+
+    let guard = args.auth_required.then(|| {
+        quote! {
+            if let Some(response) = tuono_lib::tuono_internal_guard(&req, tuono_lib::AuthGuard::Api) {
+                return response;
+            }
+        }
+    });
+
+    quote! {
+        #application_state_import
+
+        #limits_fn
+
+        #item
+
+        pub async fn tuono_internal_ws(
+            #axum_arguments
+        ) -> tuono_lib::axum::response::Response {
+
+           if let Some(response) = tuono_lib::tuono_internal_ws_origin_guard(request.headers()) {
+               return response;
+           }
+
+           #application_state_extractor
+
+           let req = tuono_lib::Request::new(request.uri().to_owned(), request.headers().to_owned(), params, None)
+               .with_extensions(request.extensions().to_owned());
+
+           #guard
+
+           upgrade.on_upgrade(move |socket| async move {
+               #fn_name(req, socket, #argument_names).await
+           })
+        }
+    }
+    .into()
+}
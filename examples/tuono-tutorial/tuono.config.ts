@@ -1,6 +1,12 @@
 import type { TuonoConfig } from 'tuono/config'
 
 const config: TuonoConfig = {
+  redirects: [
+    {
+      source: '/pokemons/GOAT',
+      destination: '/pokemons/mewtwo',
+    },
+  ],
   vite: {
     alias: {
       '@': 'src',
@@ -5,7 +5,7 @@ import type { Plugin, ViteDevServer } from 'vite'
 import { routeGenerator } from './fs-routing/generator'
 import { getStylesForComponentId, isCssModulesFile } from './styles'
 
-const CRITICAL_CSS_PATH = '/vite-server/tuono_internal__critical_css'
+const CRITICAL_CSS_PATH = 'tuono_internal__critical_css'
 
 const ROUTES_DIRECTORY_PATH = './src/routes'
 
@@ -64,7 +64,8 @@ export function TuonoReactPlugin(): Plugin {
 
         // Give the request handler access to the critical CSS in dev to avoid a
         // flash of unstyled content since Vite injects CSS file contents via JS
-        if (url.pathname === CRITICAL_CSS_PATH) {
+        // The path is relative to the vite `base` (i.e. `/vite-server/`)
+        if (url.pathname === `${server.config.base}${CRITICAL_CSS_PATH}`) {
           const componentId = url.searchParams.get('componentId')
           const css = await getStylesForComponentId(
             server,
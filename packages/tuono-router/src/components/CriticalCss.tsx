@@ -1,6 +1,9 @@
 import type { JSX } from 'react'
 
 import type { Mode } from '../types'
+import { withBasePath } from '../utils'
+
+import { useRouterContext } from './RouterContext'
 
 const VITE_PROXY_PATH = '/vite-server'
 const CRITICAL_CSS_PATH = VITE_PROXY_PATH + '/tuono_internal__critical_css'
@@ -19,13 +22,15 @@ export function CriticalCss({
   routeFilePath,
   mode,
 }: CriticalCssProps): JSX.Element | null {
+  const { router } = useRouterContext()
+
   if (!routeFilePath || mode !== 'Dev') {
     return null
   }
 
   return (
     <link
-      href={`${CRITICAL_CSS_PATH}?componentId=${routeFilePath}`}
+      href={`${withBasePath(CRITICAL_CSS_PATH, router.basePath)}?componentId=${routeFilePath}`}
       precedence="high"
       rel="stylesheet"
     />
@@ -6,11 +6,17 @@ import { Link } from './Link'
 const pushMock = vi.fn()
 const replaceMock = vi.fn()
 const preloadMock = vi.fn()
+let basePathMock = '/'
 
 vi.mock('../hooks/useRouter', () => ({
-  useRouter: (): { push: typeof pushMock; replace: typeof replaceMock } => ({
+  useRouter: (): {
+    push: typeof pushMock
+    replace: typeof replaceMock
+    basePath: string
+  } => ({
     push: pushMock,
     replace: replaceMock,
+    basePath: basePathMock,
   }),
 }))
 
@@ -39,6 +45,7 @@ describe('<Link />', () => {
     pushMock.mockReset()
     preloadMock.mockReset()
     intersectionObserverCallback = undefined
+    basePathMock = '/'
   })
 
   it('renders with correct href and text', () => {
@@ -48,6 +55,17 @@ describe('<Link />', () => {
     expect(link.getAttribute('href')).toBe('/test')
   })
 
+  it('prefixes the href with the router base path', () => {
+    basePathMock = '/shop'
+    render(<Link href="/test">Test Link</Link>)
+    const link = screen.getByRole('link')
+
+    expect(link.getAttribute('href')).toBe('/shop/test')
+
+    fireEvent.click(link)
+    expect(pushMock).toHaveBeenCalledWith('/test', { scroll: true })
+  })
+
   it('calls router.push on normal click', () => {
     render(<Link href="/test">Test Link</Link>)
     const link = screen.getByRole('link')
@@ -3,6 +3,7 @@ import { useInView } from 'react-intersection-observer'
 
 import { useRouter } from '../hooks/useRouter'
 import { useRoute } from '../hooks/useRoute'
+import { withBasePath } from '../utils'
 
 interface TuonoLinkProps extends React.AnchorHTMLAttributes<HTMLAnchorElement> {
   /**
@@ -79,7 +80,12 @@ export function Link(componentProps: TuonoLinkProps): JSX.Element {
   }
 
   return (
-    <a {...rest} href={href} ref={ref} onClick={handleTransition}>
+    <a
+      {...rest}
+      href={href && withBasePath(href, router.basePath)}
+      ref={ref}
+      onClick={handleTransition}
+    >
       {children}
     </a>
   )
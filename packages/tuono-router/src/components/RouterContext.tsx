@@ -10,11 +10,13 @@ import type { ReactNode } from 'react'
 
 import type { Router } from '../router'
 import type { ServerInitialLocation } from '../types'
+import { stripBasePath } from '../utils'
 
 const isServerSide = typeof window === 'undefined'
 
 export interface ParsedLocation {
   href: string
+  /** The pathname relative to the router base path */
   pathname: string
   search: Record<string, string>
   searchStr: string
@@ -33,10 +35,11 @@ const RouterContext = createContext({} as RouterContextValue)
 
 function getInitialLocation(
   serverPayloadLocation: ServerInitialLocation,
+  basePath: string,
 ): ParsedLocation {
   if (isServerSide) {
     return {
-      pathname: serverPayloadLocation.pathname || '',
+      pathname: stripBasePath(serverPayloadLocation.pathname || '', basePath),
       hash: '',
       href: serverPayloadLocation.href || '',
       searchStr: serverPayloadLocation.searchStr || '',
@@ -48,7 +51,7 @@ function getInitialLocation(
 
   const { pathname, hash, href, search } = window.location
   return {
-    pathname,
+    pathname: stripBasePath(pathname, basePath),
     hash,
     href,
     searchStr: search,
@@ -71,7 +74,7 @@ export function RouterContextProvider({
   router.update({ ...router.options } as Parameters<typeof router.update>[0])
 
   const [location, setLocation] = useState<ParsedLocation>(() =>
-    getInitialLocation(serverInitialLocation),
+    getInitialLocation(serverInitialLocation, router.basePath),
   )
   // Global state to track whether a page transition is in progress.
   // Set to `false` once the page is fully loaded, including server-side data.
@@ -88,7 +91,7 @@ export function RouterContextProvider({
       const { pathname, hash, href, search } = targetLocation
 
       setLocation({
-        pathname,
+        pathname: stripBasePath(pathname, router.basePath),
         hash,
         href,
         searchStr: search,
@@ -101,7 +104,7 @@ export function RouterContextProvider({
     return (): void => {
       window.removeEventListener('popstate', updateLocationOnPopStateChange)
     }
-  }, [])
+  }, [router])
 
   const updateLocation = useCallback((newLocation: ParsedLocation): void => {
     setIsTransitioning(true)
@@ -1,6 +1,7 @@
 import { useCallback } from 'react'
 
 import { useRouterContext } from '../components/RouterContext'
+import { withBasePath } from '../utils'
 
 type NavigationType = 'pushState' | 'replaceState'
 type NavigationFn = (path: string, opts?: NavigationOptions) => void
@@ -30,34 +31,40 @@ interface UseRouterResult {
   query: Record<string, string>
 
   /**
-   * Returns the current pathname
+   * Returns the current pathname, relative to the router base path
    */
   pathname: string
+
+  /**
+   * The base path the application is served under (e.g. `/shop`)
+   */
+  basePath: string
 }
 
 export const useRouter = (): UseRouterResult => {
-  const { location, updateLocation } = useRouterContext()
+  const { router, location, updateLocation } = useRouterContext()
 
   const navigate = useCallback(
     (type: NavigationType, path: string, opts?: NavigationOptions): void => {
       const { scroll = true } = opts || {}
       const url = new URL(path, window.location.origin)
+      const browserPath = withBasePath(path, router.basePath)
 
       updateLocation({
-        href: url.href,
+        href: new URL(browserPath, window.location.origin).href,
         pathname: url.pathname,
         search: Object.fromEntries(url.searchParams),
         searchStr: url.search,
         hash: url.hash,
       })
 
-      history[type](path, '', path)
+      history[type](path, '', browserPath)
 
       if (scroll) {
         window.scroll(0, 0)
       }
     },
-    [updateLocation],
+    [router, updateLocation],
   )
 
   const push = useCallback(
@@ -79,5 +86,6 @@ export const useRouter = (): UseRouterResult => {
     replace,
     query: location.search,
     pathname: location.pathname,
+    basePath: router.basePath,
   }
 }
@@ -1,7 +1,10 @@
 import { useState, useEffect, useRef } from 'react'
 
 import type { Route } from '../route'
+import type { HeadMetadata } from '../types'
+import { applyHead } from '../utils/apply-head'
 import { fromUrlToParsedLocation } from '../utils/from-url-to-parsed-location'
+import { withBasePath } from '../utils'
 
 import { useRouterContext } from '../components/RouterContext'
 
@@ -9,11 +12,20 @@ interface TuonoApi {
   data?: unknown
   info: {
     redirect_destination?: string
+    /**
+     * The route handler returned a response that can't be
+     * rendered by react (e.g. a file download or a stream)
+     */
+    reload?: boolean
   }
+  head?: HeadMetadata
 }
 
-const fetchClientSideData = async (): Promise<TuonoApi> => {
-  const res = await fetch(`/__tuono/data${location.pathname}`)
+const fetchClientSideData = async (
+  pathname: string,
+  basePath: string,
+): Promise<TuonoApi> => {
+  const res = await fetch(withBasePath(`/__tuono/data${pathname}`, basePath))
   const data = (await res.json()) as TuonoApi
   return data
 }
@@ -34,7 +46,8 @@ export function useServerPayloadData<TServerPayloadData>(
   serverInitialData: TServerPayloadData,
 ): UseServerPayloadDataResult<TServerPayloadData> {
   const isFirstRendering = useRef<boolean>(true)
-  const { location, updateLocation, stopTransitioning } = useRouterContext()
+  const { router, location, updateLocation, stopTransitioning } =
+    useRouterContext()
 
   const [data, setData] = useState<TServerPayloadData | undefined>(
     serverInitialData,
@@ -54,21 +67,32 @@ export function useServerPayloadData<TServerPayloadData>(
       // eslint-disable-next-line @typescript-eslint/no-floating-promises
       ;(async (): Promise<void> => {
         try {
-          const response = await fetchClientSideData()
+          const response = await fetchClientSideData(
+            location.pathname,
+            router.basePath,
+          )
           if (response.info.redirect_destination) {
             const parsedLocation = fromUrlToParsedLocation(
               response.info.redirect_destination,
+              router.basePath,
             )
 
             history.pushState(
               parsedLocation.pathname,
               '',
-              parsedLocation.pathname,
+              withBasePath(parsedLocation.pathname, router.basePath),
             )
 
             updateLocation(parsedLocation)
             return
           }
+          if (response.info.reload) {
+            window.location.reload()
+            return
+          }
+          if (response.head) {
+            applyHead(response.head)
+          }
           setData(response.data as TServerPayloadData)
         } catch (error) {
           throw Error('Failed loading Server Side Data', { cause: error })
@@ -86,6 +110,7 @@ export function useServerPayloadData<TServerPayloadData>(
     }
   }, [
     location.pathname,
+    router.basePath,
     route.options.hasHandler,
     updateLocation,
     stopTransitioning,
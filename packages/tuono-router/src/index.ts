@@ -4,4 +4,5 @@ export { createRouter } from './router'
 export type { RouterInstanceType } from './router'
 export { createRoute, createRootRoute } from './route'
 export { useRouter } from './hooks/useRouter'
-export type { RouteProps, RouteComponent } from './types'
+export { applyHead } from './utils/apply-head'
+export type { RouteProps, RouteComponent, HeadMetadata } from './types'
@@ -86,12 +86,14 @@ export class Router {
   }
 
   #updateBasePath = (basePath?: string): void => {
-    if (!this.basePath || (basePath && basePath !== basePath)) {
-      if (basePath === undefined || basePath === '' || basePath === '/') {
-        this.basePath = '/'
-      } else {
-        this.basePath = `/${trimPath(basePath)}`
-      }
+    if (basePath === undefined) return
+
+    const trimmedBasePath = trimPath(basePath)
+
+    if (trimmedBasePath === '' || trimmedBasePath === '/') {
+      this.basePath = '/'
+    } else {
+      this.basePath = `/${trimmedBasePath}`
     }
   }
 }
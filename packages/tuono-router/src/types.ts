@@ -17,6 +17,17 @@ export interface ServerInitialLocation {
   searchStr: string
 }
 
+/**
+ * Document head metadata defined by the route handler
+ * @see crates/tuono_lib/src/head.rs
+ */
+export interface HeadMetadata {
+  title?: string
+  description?: string
+  canonical?: string
+  meta?: Array<{ name?: string; property?: string; content: string }>
+}
+
 export interface RouteProps<TData = unknown> {
   data: TData
 
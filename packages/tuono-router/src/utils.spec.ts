@@ -0,0 +1,32 @@
+import { describe, it, expect } from 'vitest'
+
+import { stripBasePath, withBasePath } from './utils'
+
+describe('stripBasePath', () => {
+  it('returns the pathname relative to the base path', () => {
+    expect(stripBasePath('/shop', '/shop')).toBe('/')
+    expect(stripBasePath('/shop/cart', '/shop')).toBe('/cart')
+    expect(stripBasePath('/shopping', '/shop')).toBe('/shopping')
+    expect(stripBasePath('/cart', '/')).toBe('/cart')
+  })
+})
+
+describe('withBasePath', () => {
+  it('prefixes the absolute paths with the base path', () => {
+    expect(withBasePath('/', '/shop')).toBe('/shop')
+    expect(withBasePath('/cart', '/shop')).toBe('/shop/cart')
+    expect(withBasePath('/?page=2', '/shop')).toBe('/shop?page=2')
+    expect(withBasePath('/cart', '/')).toBe('/cart')
+  })
+
+  it('does not prefix relative paths, hashes and external URLs', () => {
+    expect(withBasePath('cart', '/shop')).toBe('cart')
+    expect(withBasePath('#top', '/shop')).toBe('#top')
+    expect(withBasePath('//cdn.tuono.dev/logo.png', '/shop')).toBe(
+      '//cdn.tuono.dev/logo.png',
+    )
+    expect(withBasePath('https://tuono.dev', '/shop')).toBe(
+      'https://tuono.dev',
+    )
+  })
+})
@@ -75,3 +75,31 @@ export function trimPathRight(path: string): string {
 export function trimPath(path: string): string {
   return trimPathRight(trimPathLeft(path))
 }
+
+/**
+ * Remove the router base path from a pathname.
+ * The root of the application is the base path itself.
+ */
+export function stripBasePath(pathname: string, basePath: string): string {
+  if (basePath === '/') return pathname
+  if (pathname === basePath) return '/'
+  if (pathname.startsWith(`${basePath}/`)) {
+    return pathname.slice(basePath.length)
+  }
+  return pathname
+}
+
+/**
+ * Prefix the application absolute paths with the router base path.
+ * Relative paths, hashes and external URLs are returned untouched.
+ */
+export function withBasePath(path: string, basePath: string): string {
+  if (basePath === '/' || !path.startsWith('/') || path.startsWith('//')) {
+    return path
+  }
+  if (path === '/') return basePath
+  if (path.startsWith('/?') || path.startsWith('/#')) {
+    return `${basePath}${path.slice(1)}`
+  }
+  return `${basePath}${path}`
+}
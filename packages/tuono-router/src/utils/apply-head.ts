@@ -0,0 +1,61 @@
+import type { HeadMetadata } from '../types'
+
+function upsertMeta(
+  attribute: 'name' | 'property',
+  key: string,
+  content: string,
+): void {
+  let element = document.head.querySelector<HTMLMetaElement>(
+    `meta[${attribute}="${CSS.escape(key)}"]`,
+  )
+
+  if (!element) {
+    element = document.createElement('meta')
+    element.setAttribute(attribute, key)
+    document.head.appendChild(element)
+  }
+
+  element.setAttribute('content', content)
+}
+
+function upsertCanonical(href: string): void {
+  let element = document.head.querySelector<HTMLLinkElement>(
+    'link[rel="canonical"]',
+  )
+
+  if (!element) {
+    element = document.createElement('link')
+    element.setAttribute('rel', 'canonical')
+    document.head.appendChild(element)
+  }
+
+  element.setAttribute('href', href)
+}
+
+/**
+ * Apply the head metadata defined by the route handler
+ * to the current document.
+ *
+ * @see crates/tuono_lib/src/head.rs
+ */
+export function applyHead(head: HeadMetadata): void {
+  if (head.title !== undefined) {
+    document.title = head.title
+  }
+
+  if (head.description !== undefined) {
+    upsertMeta('name', 'description', head.description)
+  }
+
+  if (head.canonical !== undefined) {
+    upsertCanonical(head.canonical)
+  }
+
+  head.meta?.forEach(({ name, property, content }) => {
+    if (name) {
+      upsertMeta('name', name, content)
+    } else if (property) {
+      upsertMeta('property', property, content)
+    }
+  })
+}
@@ -1,10 +1,14 @@
 import type { ParsedLocation } from '../components/RouterContext'
+import { stripBasePath } from '../utils'
 
-export function fromUrlToParsedLocation(href: string): ParsedLocation {
+export function fromUrlToParsedLocation(
+  href: string,
+  basePath = '/',
+): ParsedLocation {
   const location = new URL(href, window.location.origin)
   return {
     href: location.href,
-    pathname: location.pathname,
+    pathname: stripBasePath(location.pathname, basePath),
     search: Object.fromEntries(location.searchParams),
     searchStr: location.search,
     hash: location.hash,
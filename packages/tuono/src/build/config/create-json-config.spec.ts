@@ -13,7 +13,28 @@ describe('createJsonConfig', () => {
   })
 
   it('should process config with only server property', async () => {
-    const sampleConfig = { server: { host: 'h', origin: null, port: 1 } }
+    const sampleConfig = {
+      server: {
+        host: 'h',
+        origin: null,
+        port: 1,
+        tls: null,
+        unixSocket: null,
+        socketActivation: false,
+      },
+      basePath: '',
+      redirects: [],
+      rewrites: [],
+      session: {},
+      csrf: {},
+      cors: {},
+      limits: {},
+      auth: {},
+      health: {},
+      metrics: {},
+      telemetry: {},
+      i18n: {},
+    }
 
     await createJsonConfig(sampleConfig)
 
@@ -25,7 +46,28 @@ describe('createJsonConfig', () => {
   })
 
   it('should process config with plugins', async () => {
-    const sampleConfig = { server: { host: 'h', origin: null, port: 1 } }
+    const sampleConfig = {
+      server: {
+        host: 'h',
+        origin: null,
+        port: 1,
+        tls: null,
+        unixSocket: null,
+        socketActivation: false,
+      },
+      basePath: '',
+      redirects: [],
+      rewrites: [],
+      session: {},
+      csrf: {},
+      cors: {},
+      limits: {},
+      auth: {},
+      health: {},
+      metrics: {},
+      telemetry: {},
+      i18n: {},
+    }
 
     await createJsonConfig({ ...sampleConfig, vite: { plugins: [react()] } })
 
@@ -37,7 +79,28 @@ describe('createJsonConfig', () => {
   })
 
   it('should process config with only server property including origin', async () => {
-    const sampleConfig = { server: { host: 'h', origin: 'o', port: 1 } }
+    const sampleConfig = {
+      server: {
+        host: 'h',
+        origin: 'o',
+        port: 1,
+        tls: null,
+        unixSocket: null,
+        socketActivation: false,
+      },
+      basePath: '',
+      redirects: [],
+      rewrites: [],
+      session: {},
+      csrf: {},
+      cors: {},
+      limits: {},
+      auth: {},
+      health: {},
+      metrics: {},
+      telemetry: {},
+      i18n: {},
+    }
 
     await createJsonConfig(sampleConfig)
 
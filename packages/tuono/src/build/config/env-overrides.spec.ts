@@ -0,0 +1,25 @@
+import { describe, expect, it } from 'vitest'
+
+import { applyEnvOverrides } from './env-overrides'
+import { normalizeConfig } from './normalize-config'
+
+describe('applyEnvOverrides', () => {
+  const config = normalizeConfig({ basePath: '/shop' })
+
+  it('should keep the config without overrides', () => {
+    expect(applyEnvOverrides(config, {})).toStrictEqual(config)
+  })
+
+  it('should override the dev server options', () => {
+    const overridden = applyEnvOverrides(config, {
+      TUONO_SERVER__HOST: '0.0.0.0',
+      TUONO_SERVER__PORT: '4000',
+      TUONO_BASE_PATH: '/store/',
+    })
+
+    expect(overridden.server.host).toBe('0.0.0.0')
+    expect(overridden.server.port).toBe(4000)
+    expect(overridden.basePath).toBe('/store')
+    expect(overridden.csrf).toStrictEqual(config.csrf)
+  })
+})
@@ -0,0 +1,32 @@
+import type { InternalTuonoConfig } from '../types'
+
+import { normalizeBasePath } from './normalize-config'
+
+/**
+ * Apply the `TUONO_*` environment variable overrides read by the rust server
+ * to the options the vite dev server depends on, so that both stay in sync
+ * (e.g. `tuono dev --port 4000`).
+ *
+ * @see crates/tuono_internal/src/config.rs
+ */
+export const applyEnvOverrides = (
+  config: InternalTuonoConfig,
+  env: NodeJS.ProcessEnv = process.env,
+): InternalTuonoConfig => {
+  const { TUONO_SERVER__HOST, TUONO_SERVER__PORT, TUONO_BASE_PATH } = env
+
+  return {
+    ...config,
+    server: {
+      ...config.server,
+      host: TUONO_SERVER__HOST || config.server.host,
+      port: TUONO_SERVER__PORT
+        ? Number(TUONO_SERVER__PORT)
+        : config.server.port,
+    },
+    basePath:
+      TUONO_BASE_PATH === undefined
+        ? config.basePath
+        : normalizeBasePath(TUONO_BASE_PATH),
+  }
+}
@@ -1,2 +1,3 @@
 export { loadConfig } from './load-config'
 export { createJsonConfig } from './create-json-config'
+export { applyEnvOverrides } from './env-overrides'
@@ -19,7 +19,22 @@ describe('normalizeConfig', () => {
         host: 'localhost',
         origin: null,
         port: 3000,
+        tls: null,
+        unixSocket: null,
+        socketActivation: false,
       },
+      basePath: '',
+      redirects: [],
+      rewrites: [],
+      session: {},
+      csrf: {},
+      cors: {},
+      limits: {},
+      auth: {},
+      health: {},
+      metrics: {},
+      telemetry: {},
+      i18n: {},
       vite: {
         alias: undefined,
         css: undefined,
@@ -36,7 +51,22 @@ describe('normalizeConfig', () => {
         host: 'localhost',
         origin: null,
         port: 3000,
+        tls: null,
+        unixSocket: null,
+        socketActivation: false,
       },
+      basePath: '',
+      redirects: [],
+      rewrites: [],
+      session: {},
+      csrf: {},
+      cors: {},
+      limits: {},
+      auth: {},
+      health: {},
+      metrics: {},
+      telemetry: {},
+      i18n: {},
       vite: {
         alias: undefined,
         css: undefined,
@@ -63,6 +93,50 @@ describe('normalizeConfig', () => {
     })
   })
 
+  describe('redirects and rewrites', () => {
+    it('should default the redirects to temporary', () => {
+      const config: TuonoConfig = {
+        redirects: [
+          { source: '/old', destination: '/new' },
+          {
+            source: '/blog/{slug}',
+            destination: '/posts/{slug}',
+            permanent: true,
+          },
+        ],
+        rewrites: [{ source: '/about', destination: '/company/about' }],
+      }
+
+      expect(normalizeConfig(config)).toStrictEqual(
+        expect.objectContaining({
+          redirects: [
+            { source: '/old', destination: '/new', permanent: false },
+            {
+              source: '/blog/{slug}',
+              destination: '/posts/{slug}',
+              permanent: true,
+            },
+          ],
+          rewrites: [{ source: '/about', destination: '/company/about' }],
+        }),
+      )
+    })
+  })
+
+  describe('session', () => {
+    it('should keep only the options defined by the user', () => {
+      const config: TuonoConfig = {
+        session: { store: 'file', idleTimeout: 3600 },
+      }
+
+      expect(normalizeConfig(config)).toStrictEqual(
+        expect.objectContaining({
+          session: { store: 'file', idleTimeout: 3600 },
+        }),
+      )
+    })
+  })
+
   describe('server - origin', () => {
     it('should assign the origin defined by the user', () => {
       const config: TuonoConfig = {
@@ -85,6 +159,34 @@ describe('normalizeConfig', () => {
     })
   })
 
+  describe('server - tls', () => {
+    it('should assign the tls options defined by the user', () => {
+      const tls = { cert: 'certs/cert.pem', key: 'certs/key.pem' }
+      const config: TuonoConfig = {
+        server: { port: 3443, tls },
+      }
+
+      expect(normalizeConfig(config)).toStrictEqual(
+        expect.objectContaining({
+          server: expect.objectContaining({ port: 3443, tls }) as unknown,
+        }),
+      )
+    })
+  })
+
+  describe('basePath', () => {
+    it('should default the base path to the root', () => {
+      expect(normalizeConfig({ basePath: '/' }).basePath).toBe('')
+    })
+
+    it('should add the leading slash and remove the trailing one', () => {
+      expect(normalizeConfig({ basePath: 'shop/' }).basePath).toBe('/shop')
+      expect(normalizeConfig({ basePath: '//apps//shop' }).basePath).toBe(
+        '/apps/shop',
+      )
+    })
+  })
+
   describe('vite - alias', () => {
     it('should not modify alias pointing to packages', () => {
       const libraryName = '@tabler/icons-react'
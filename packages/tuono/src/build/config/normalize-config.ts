@@ -60,6 +60,15 @@ const normalizeViteAlias = (alias?: AliasOptions): AliasOptions | undefined => {
   return alias
 }
 
+/**
+ * Normalize the `basePath` option to either an empty string (root)
+ * or a path starting with `/` and without the trailing one (e.g. `/shop`)
+ */
+export const normalizeBasePath = (basePath?: string): string => {
+  const segments = (basePath ?? '').split('/').filter(Boolean)
+  return segments.length ? `/${segments.join('/')}` : ''
+}
+
 /**
  * Wrapper function to normalize the tuono.config.ts file
  *
@@ -73,7 +82,31 @@ export const normalizeConfig = (config: TuonoConfig): InternalTuonoConfig => {
       host: config.server?.host ?? 'localhost',
       origin: config.server?.origin ?? null,
       port: config.server?.port ?? 3000,
+      tls: config.server?.tls ?? null,
+      unixSocket: config.server?.unixSocket ?? null,
+      socketActivation: config.server?.socketActivation ?? false,
     },
+    basePath: normalizeBasePath(config.basePath),
+    redirects: (config.redirects ?? []).map(
+      ({ source, destination, permanent }) => ({
+        source,
+        destination,
+        permanent: permanent ?? false,
+      }),
+    ),
+    rewrites: (config.rewrites ?? []).map(({ source, destination }) => ({
+      source,
+      destination,
+    })),
+    session: { ...config.session },
+    csrf: { ...config.csrf },
+    cors: { ...config.cors },
+    limits: { ...config.limits },
+    auth: { ...config.auth },
+    health: { ...config.health },
+    metrics: { ...config.metrics },
+    telemetry: { ...config.telemetry },
+    i18n: { ...config.i18n },
     vite: {
       alias: normalizeViteAlias(config.vite?.alias),
       css: config.vite?.css,
@@ -9,7 +9,7 @@ import type { TuonoConfig } from '../config'
 import { ErrorOverlayVitePlugin } from './error-overlay'
 
 import { blockingAsync } from './utils'
-import { createJsonConfig, loadConfig } from './config'
+import { applyEnvOverrides, createJsonConfig, loadConfig } from './config'
 import { ENV_PREFIX } from './constants'
 
 const VITE_SSR_PLUGINS: Array<Plugin> = [
@@ -115,14 +115,15 @@ const developmentSSRBundle = (): void => {
 
 const developmentCSRWatch = (): void => {
   blockingAsync(async () => {
-    const config = await loadConfig()
+    // The rust server proxies the dev server using the overridden options
+    const config = applyEnvOverrides(await loadConfig())
 
     const server = await createServer(
       mergeConfig<InlineConfig, InlineConfig>(
         createBaseViteConfigFromTuonoConfig(config),
         {
           // Entry point for the development vite proxy
-          base: '/vite-server/',
+          base: `${config.basePath}/vite-server/`,
           plugins: [ErrorOverlayVitePlugin],
 
           server: {
@@ -152,6 +153,8 @@ const buildProd = (): void => {
       mergeConfig<InlineConfig, InlineConfig>(
         createBaseViteConfigFromTuonoConfig(config),
         {
+          // The assets are served under the application base path
+          base: `${config.basePath}/`,
           build: {
             manifest: true,
             emptyOutDir: true,
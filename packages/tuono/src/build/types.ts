@@ -1,5 +1,57 @@
-import type { TuonoConfig, TuonoConfigServer } from '../config'
+import type {
+  TuonoConfig,
+  TuonoConfigAuth,
+  TuonoConfigCors,
+  TuonoConfigCsrf,
+  TuonoConfigHealth,
+  TuonoConfigI18n,
+  TuonoConfigLimits,
+  TuonoConfigMetrics,
+  TuonoConfigRedirect,
+  TuonoConfigRewrite,
+  TuonoConfigServer,
+  TuonoConfigSession,
+  TuonoConfigTelemetry,
+} from '../config'
 
-export interface InternalTuonoConfig extends Omit<TuonoConfig, 'server'> {
+export interface InternalTuonoConfig
+  extends Omit<
+    TuonoConfig,
+    | 'server'
+    | 'basePath'
+    | 'redirects'
+    | 'rewrites'
+    | 'session'
+    | 'csrf'
+    | 'cors'
+    | 'limits'
+    | 'auth'
+    | 'health'
+    | 'metrics'
+    | 'telemetry'
+    | 'i18n'
+  > {
   server: TuonoConfigServer
+  /** Either empty or starting with `/` without the trailing one */
+  basePath: string
+  redirects: Array<Required<TuonoConfigRedirect>>
+  rewrites: Array<TuonoConfigRewrite>
+  /** Missing options are defaulted by the server */
+  session: TuonoConfigSession
+  /** Missing options are defaulted by the server */
+  csrf: TuonoConfigCsrf
+  /** Missing options are defaulted by the server */
+  cors: TuonoConfigCors
+  /** Missing options are defaulted by the server */
+  limits: TuonoConfigLimits
+  /** Missing options are defaulted by the server */
+  auth: TuonoConfigAuth
+  /** Missing options are defaulted by the server */
+  health: TuonoConfigHealth
+  /** Missing options are defaulted by the server */
+  metrics: TuonoConfigMetrics
+  /** Missing options are defaulted by the server */
+  telemetry: TuonoConfigTelemetry
+  /** Missing options are defaulted by the server */
+  i18n: TuonoConfigI18n
 }
@@ -1 +1,19 @@
-export type { TuonoConfig, TuonoConfigServer } from './types'
+export type {
+  TuonoConfig,
+  TuonoConfigAuth,
+  TuonoConfigCors,
+  TuonoConfigCorsRoute,
+  TuonoConfigCsrf,
+  TuonoConfigHealth,
+  TuonoConfigI18n,
+  TuonoConfigI18nDomain,
+  TuonoConfigLimits,
+  TuonoConfigLimitsRoute,
+  TuonoConfigMetrics,
+  TuonoConfigRedirect,
+  TuonoConfigRewrite,
+  TuonoConfigServer,
+  TuonoConfigServerTls,
+  TuonoConfigSession,
+  TuonoConfigTelemetry,
+} from './types'
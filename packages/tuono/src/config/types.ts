@@ -9,13 +9,294 @@ export interface TuonoConfigServer {
   host: string
   origin: string | null
   port: number
+  /** Serve HTTPS and HTTP/2 instead of plain HTTP */
+  tls: TuonoConfigServerTls | null
+  /** Listen on a Unix domain socket instead of `host:port` */
+  unixSocket: string | null
+  /**
+   * Listen on the socket inherited from the service manager
+   * (i.e. systemd `LISTEN_FDS`) instead of `host:port`
+   */
+  socketActivation: boolean
+}
+
+export interface TuonoConfigServerTls {
+  /** Path of the PEM encoded certificate chain */
+  cert: string
+  /** Path of the PEM encoded private key */
+  key: string
+  /** The port of a plain HTTP listener redirecting to HTTPS */
+  redirectPort?: number
+}
+
+/**
+ * `source` and `destination` accept the path parameters syntax
+ * (e.g. `/blog/{slug}` or `/docs/{*path}`)
+ */
+export interface TuonoConfigRedirect {
+  source: string
+  destination: string
+  /**
+   * Use a permanent (308) redirect instead of a temporary (307) one
+   * @default false
+   */
+  permanent?: boolean
+}
+
+export interface TuonoConfigRewrite {
+  source: string
+  destination: string
+}
+
+export interface TuonoConfigSession {
+  /**
+   * The `file` store keeps the sessions across the server restarts
+   * @default 'memory'
+   */
+  store?: 'memory' | 'file'
+  /**
+   * Folder used by the `file` store
+   * @default '.tuono/sessions'
+   */
+  directory?: string
+  /** @default 'tuono_session' */
+  cookieName?: string
+  /**
+   * Seconds of inactivity after which the session expires
+   * @default 86400
+   */
+  idleTimeout?: number
+  /**
+   * Seconds after the session creation after which it always expires
+   * @default 604800
+   */
+  absoluteTimeout?: number
+  /**
+   * Send the cookie only over HTTPS.
+   * Defaults to `true` on the production server.
+   */
+  secure?: boolean
+  /** @default 'lax' */
+  sameSite?: 'lax' | 'strict' | 'none'
+}
+
+export interface TuonoConfigCsrf {
+  /**
+   * Require a token on `POST`, `PUT`, `PATCH` and `DELETE` requests
+   * @default true
+   */
+  enabled?: boolean
+  /** @default 'tuono_csrf' */
+  cookieName?: string
+  /**
+   * Header read on `fetch` calls
+   * @default 'x-csrf-token'
+   */
+  headerName?: string
+  /**
+   * Field read on url encoded form submissions
+   * @default '_csrf'
+   */
+  fieldName?: string
+  /**
+   * Paths excluded from the check (e.g. webhooks).
+   * They accept the path parameters syntax (e.g. `/api/webhooks/{*path}`)
+   */
+  exempt?: Array<string>
+  /**
+   * Send the cookie only over HTTPS.
+   * Defaults to `true` on the production server.
+   */
+  secure?: boolean
+}
+
+interface TuonoConfigCorsOptions {
+  /** Allowed origins. `'*'` allows any origin. */
+  origins?: Array<string>
+  /** @default ['GET', 'HEAD', 'POST', 'PUT', 'PATCH', 'DELETE'] */
+  methods?: Array<string>
+  /**
+   * Allowed request headers.
+   * When empty the headers requested by the preflight are allowed.
+   */
+  headers?: Array<string>
+  exposeHeaders?: Array<string>
+  /** @default false */
+  credentials?: boolean
+  /** Seconds the preflight response can be cached */
+  maxAge?: number
+}
+
+export interface TuonoConfigCorsRoute extends TuonoConfigCorsOptions {
+  /** Accepts the path parameters syntax (e.g. `/api/{*path}`) */
+  path: string
+}
+
+/**
+ * Cross-origin resource sharing. Disabled until `origins` is defined.
+ */
+export interface TuonoConfigCors extends TuonoConfigCorsOptions {
+  /**
+   * Per-route overrides. The first route matching the request path is used
+   * and the options it doesn't define fallback to the top level ones.
+   */
+  routes?: Array<TuonoConfigCorsRoute>
+}
+
+interface TuonoConfigLimitsOptions {
+  /** Requests per window per client (e.g. `'10/1m'`, `'100/1s'`) */
+  rateLimit?: string
+  /**
+   * How the rate limited clients are identified: `'ip'` or `'header:<name>'`
+   * @default 'ip'
+   */
+  rateLimitKey?: string
+  /** Maximum number of requests handled at the same time */
+  concurrency?: number
+  /** Request timeout (e.g. `'500ms'`, `'30s'`) */
+  timeout?: string
+}
+
+export interface TuonoConfigLimitsRoute extends TuonoConfigLimitsOptions {
+  /** Accepts the path parameters syntax (e.g. `/api/{*path}`) */
+  path: string
+}
+
+/**
+ * Traffic controls. The top level options apply to the whole server.
+ */
+export interface TuonoConfigLimits extends TuonoConfigLimitsOptions {
+  /**
+   * Number of reverse proxies in front of the server. When set, the client IP
+   * is the right-most address of the forwarded headers not added by them.
+   * @default 0
+   */
+  trustedProxies?: number
+  /** Per-route limits. The first route matching the request path is used. */
+  routes?: Array<TuonoConfigLimitsRoute>
+}
+
+/**
+ * Where the routes declared with `auth = "required"` send
+ * the visitors that are not authenticated.
+ */
+export interface TuonoConfigAuth {
+  /** @default '/login' */
+  loginUrl?: string
+  /**
+   * Query parameter holding the path requested before the login
+   * @default 'redirect'
+   */
+  returnParam?: string
+}
+
+/**
+ * The liveness and readiness endpoints polled by the orchestrators
+ * (e.g. Kubernetes probes).
+ */
+export interface TuonoConfigHealth {
+  /** @default true */
+  enabled?: boolean
+  /** @default '/__tuono/health' */
+  livenessPath?: string
+  /** @default '/__tuono/ready' */
+  readinessPath?: string
+  /**
+   * Seconds after which a readiness check is considered failed
+   * @default 5
+   */
+  timeout?: number
+}
+
+/**
+ * The Prometheus metrics endpoint
+ */
+export interface TuonoConfigMetrics {
+  /** @default false */
+  enabled?: boolean
+  /** @default '/metrics' */
+  path?: string
+}
+
+/**
+ * The OpenTelemetry traces exported to an OTLP/HTTP collector
+ */
+export interface TuonoConfigTelemetry {
+  /** @default false */
+  enabled?: boolean
+  /**
+   * The collector base URL. The traces are sent to `{endpoint}/v1/traces`.
+   * @default 'http://localhost:4318'
+   */
+  endpoint?: string
+  /** @default 'tuono' */
+  serviceName?: string
+}
+
+export interface TuonoConfigI18nDomain {
+  /** The host name without the port (e.g. `tuono.it`) */
+  domain: string
+  locale: string
+}
+
+/**
+ * The internationalized routing
+ */
+export interface TuonoConfigI18n {
+  /**
+   * The supported locales (e.g. `['en', 'it']`).
+   * The i18n routing is disabled when empty.
+   */
+  locales?: Array<string>
+  /**
+   * The locale of the paths without prefix
+   * @default the first of `locales`
+   */
+  defaultLocale?: string
+  /**
+   * Prefix also the paths of the default locale (e.g. `/en/about`)
+   * @default false
+   */
+  prefixDefaultLocale?: boolean
+  /** The domains serving a single locale without prefix */
+  domains?: Array<TuonoConfigI18nDomain>
+  /**
+   * Redirect the application root to the locale preferred by the client
+   * @default true
+   */
+  localeDetection?: boolean
+  /**
+   * The cookie storing the locale chosen by the user
+   * @default 'TUONO_LOCALE'
+   */
+  cookieName?: string
 }
 
 /**
+ * Every option can be overridden at runtime by a `TUONO_*` environment
+ * variable: the section and the option names in `SCREAMING_SNAKE_CASE`
+ * separated by a double underscore (e.g. `TUONO_SERVER__PORT=8080`).
+ *
  * @see http://tuono.dev/documentation/configuration
  */
 export interface TuonoConfig {
   server?: Partial<TuonoConfigServer>
+  /**
+   * Serve the application under a sub path (e.g. `/shop`)
+   * @default '/'
+   */
+  basePath?: string
+  redirects?: Array<TuonoConfigRedirect>
+  rewrites?: Array<TuonoConfigRewrite>
+  session?: TuonoConfigSession
+  csrf?: TuonoConfigCsrf
+  cors?: TuonoConfigCors
+  limits?: TuonoConfigLimits
+  auth?: TuonoConfigAuth
+  health?: TuonoConfigHealth
+  metrics?: TuonoConfigMetrics
+  telemetry?: TuonoConfigTelemetry
+  i18n?: TuonoConfigI18n
   vite?: {
     alias?: AliasOptions
     css?: CSSOptions
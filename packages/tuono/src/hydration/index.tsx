@@ -2,13 +2,20 @@ import { hydrateRoot } from 'react-dom/client'
 import { createRouter } from 'tuono-router'
 import type { createRoute } from 'tuono-router'
 
+import { SERVER_PAYLOAD_VARIABLE_NAME } from '../constants'
 import { TuonoEntryPoint } from '../shared/TuonoEntryPoint'
 
 type RouteTree = ReturnType<typeof createRoute>
 
 export function hydrate(routeTree: RouteTree): void {
-  // Create a new router instance
-  const router = createRouter({ routeTree })
+  const serverPayload = window[SERVER_PAYLOAD_VARIABLE_NAME]
+
+  // Create a new router instance.
+  // The locale prefix is part of the router base path.
+  const router = createRouter({
+    routeTree,
+    basePath: `${serverPayload?.basePath ?? ''}${serverPayload?.locale?.pathPrefix ?? ''}`,
+  })
 
   hydrateRoot(document, <TuonoEntryPoint router={router} />)
 }
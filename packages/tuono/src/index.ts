@@ -12,5 +12,7 @@ export {
 } from './shared/dynamic'
 
 export { TuonoScripts } from './shared/TuonoScripts'
+export { CsrfInput, useCsrfToken } from './shared/Csrf'
+export { useLocale, useLocales } from './shared/Locale'
 
 export type { TuonoRouteProps, TuonoLayoutProps } from './types'
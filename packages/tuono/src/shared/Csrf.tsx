@@ -0,0 +1,32 @@
+import type { JSX } from 'react'
+
+import { useTuonoContextServerPayload } from './TuonoContext'
+
+/**
+ * The token required by the server on `POST`, `PUT`, `PATCH` and `DELETE` requests.
+ * Send it with the `x-csrf-token` header on `fetch` calls.
+ *
+ * `null` when the CSRF protection is disabled.
+ */
+export function useCsrfToken(): string | null {
+  return useTuonoContextServerPayload().csrfToken
+}
+
+interface CsrfInputProps {
+  /**
+   * Must match the `csrf.fieldName` config option
+   * @default '_csrf'
+   */
+  name?: string
+}
+
+/**
+ * Hidden input carrying the CSRF token in url encoded forms
+ */
+export function CsrfInput({ name = '_csrf' }: CsrfInputProps): JSX.Element | null {
+  const csrfToken = useCsrfToken()
+
+  if (!csrfToken) return null
+
+  return <input type="hidden" name={name} value={csrfToken} />
+}
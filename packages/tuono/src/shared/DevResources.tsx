@@ -2,22 +2,33 @@ import type { JSX } from 'react'
 
 import type { TuonoConfigServer } from '../config'
 
-const DEFAULT_SERVER_CONFIG = { host: 'localhost', origin: null, port: 3000 }
+const DEFAULT_SERVER_CONFIG: TuonoConfigServer = {
+  host: 'localhost',
+  origin: null,
+  port: 3000,
+  tls: null,
+  unixSocket: null,
+  socketActivation: false,
+}
 const VITE_PROXY_PATH = '/vite-server'
 
 interface DevResourcesProps {
   devServerConfig?: TuonoConfigServer
+  /** The application base path the vite proxy is served under */
+  basePath?: string
 }
 
 export const DevResources = ({
   devServerConfig,
+  basePath = '',
 }: DevResourcesProps): JSX.Element => {
-  const { host, origin, port } = devServerConfig ?? DEFAULT_SERVER_CONFIG
+  const { host, origin, port, tls } = devServerConfig ?? DEFAULT_SERVER_CONFIG
+  const scheme = tls != null ? 'https' : 'http'
 
   const viteBaseUrl =
     origin != null
-      ? `${origin}${VITE_PROXY_PATH}`
-      : `http://${host}:${port}${VITE_PROXY_PATH}`
+      ? `${origin}${basePath}${VITE_PROXY_PATH}`
+      : `${scheme}://${host}:${port}${basePath}${VITE_PROXY_PATH}`
 
   return (
     <>
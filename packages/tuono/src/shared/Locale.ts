@@ -0,0 +1,17 @@
+import { useTuonoContextServerPayload } from './TuonoContext'
+
+/**
+ * The locale of the current page (e.g. `it`).
+ *
+ * `null` when the i18n routing is disabled.
+ */
+export function useLocale(): string | null {
+  return useTuonoContextServerPayload().locale?.code ?? null
+}
+
+/**
+ * The locales supported by the application
+ */
+export function useLocales(): Array<string> {
+  return useTuonoContextServerPayload().locales ?? []
+}
@@ -1,4 +1,6 @@
 import type { JSX } from 'react'
+import { useEffect } from 'react'
+import { applyHead } from 'tuono-router'
 
 import { SERVER_PAYLOAD_VARIABLE_NAME } from '../constants'
 
@@ -9,11 +11,22 @@ import { useTuonoContextServerPayload } from './TuonoContext'
 export function TuonoScripts(): JSX.Element {
   const serverPayload = useTuonoContextServerPayload()
 
+  // The head rendered by react during hydration might override
+  // the metadata injected by the server
+  useEffect(() => {
+    if (serverPayload.head) {
+      applyHead(serverPayload.head)
+    }
+  }, [serverPayload.head])
+
   return (
     <>
       <script>{`window['${SERVER_PAYLOAD_VARIABLE_NAME}']=${JSON.stringify(serverPayload)}`}</script>
       {serverPayload.mode === 'Dev' && (
-        <DevResources devServerConfig={serverPayload.devServerConfig} />
+        <DevResources
+          devServerConfig={serverPayload.devServerConfig}
+          basePath={serverPayload.basePath}
+        />
       )}
       {serverPayload.mode === 'Prod' && (
         <ProdResources
@@ -56,7 +56,12 @@ export function serverSideRendering(routeTree: RouteTree) {
   return async function render(payload: string | undefined): Promise<string> {
     const serverPayload = (payload ? JSON.parse(payload) : {}) as ServerPayload
 
-    const router = createRouter({ routeTree }) // Render the app
+    // Render the app.
+    // The locale prefix is part of the router base path.
+    const router = createRouter({
+      routeTree,
+      basePath: `${serverPayload.basePath ?? ''}${serverPayload.locale?.pathPrefix ?? ''}`,
+    })
 
     const stream = await renderToReadableStream(
       <TuonoEntryPoint router={router} serverPayload={serverPayload} />,
@@ -1,4 +1,5 @@
 import type { ReactNode } from 'react'
+import type { HeadMetadata } from 'tuono-router'
 
 import type { TuonoConfigServer } from './config'
 
@@ -14,6 +15,16 @@ export interface ServerPayloadLocation {
   searchStr: string
 }
 
+/**
+ * The locale resolved by the rust server
+ * @see crates/tuono_lib/src/i18n.rs
+ */
+export interface ServerPayloadLocale {
+  code: string
+  /** The locale path prefix (e.g. `/it`). Empty for the unprefixed locales */
+  pathPrefix: string
+}
+
 /**
  * @see crates/tuono_lib/src/payload.rs
  */
@@ -21,6 +32,24 @@ export type ServerPayload<TData = unknown> = {
   location: ServerPayloadLocation
 
   data: TData
+
+  /**
+   * Document head metadata defined by the route handler.
+   * Already injected in the server side rendered HTML.
+   */
+  head: HeadMetadata | null
+
+  /** `null` when the CSRF protection is disabled */
+  csrfToken: string | null
+
+  /** Either empty or starting with `/` without the trailing one */
+  basePath: string
+
+  /** `null` when the i18n routing is disabled */
+  locale: ServerPayloadLocale | null
+
+  /** The supported locales */
+  locales: Array<string>
 } & (
   | {
       mode: 'Prod'